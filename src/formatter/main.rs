@@ -0,0 +1,22 @@
+use std::{collections::HashMap, env, fs};
+
+use compiler::{ParseTable, Parser, format_program_with_comments};
+
+// Reescreve o programa (por padrão `data.txt`) no formato canônico
+fn main() {
+    let path = env::args().nth(1).unwrap_or("data.txt".to_string());
+
+    let mut symbol_table = HashMap::new();
+    let parse_table = ParseTable::create_parse_table("inicio");
+    let contents = fs::read_to_string(&path).expect("Failed to open the file entry.");
+    let mut parser = Parser::new(contents, parse_table, &mut symbol_table).with_comments();
+
+    match parser.parse() {
+        Ok(ast) => {
+            print!("{}", format_program_with_comments(&ast, parser.comments()));
+        }
+        Err(error) => {
+            eprintln!("✗ {}", error);
+        }
+    };
+}
@@ -0,0 +1,486 @@
+use std::{iter::Peekable, slice::Iter};
+
+use crate::{AstNode, Comment, OperatorKind, Position, Span, Spanned, Type};
+
+// Quantidade de espaços por nível de indentação
+const INDENT: &str = "    ";
+
+// Gera o código-fonte canônico de uma AST
+pub fn format_program(ast: &AstNode) -> String {
+    format_program_with_comments(ast, &[])
+}
+
+// Gera o código-fonte canônico mantendo os comentários `{% %}`. Cada comentário é
+// escrito antes do primeiro comando ou declaração que começa depois dele, ou no fim
+// da linha do comando anterior quando era lá que estava; por isso a AST precisa vir
+// do parser, com os intervalos de cada nó.
+pub fn format_program_with_comments(ast: &AstNode, comments: &[Comment]) -> String {
+    let mut formatter = SourceFormatter {
+        out: String::new(),
+        indent: 0,
+        block_end: None,
+        comments: comments.iter().peekable(),
+    };
+
+    formatter.write_inline_statement(ast, None);
+    formatter.out.push('\n');
+    formatter.flush_comments(None);
+
+    formatter.out
+}
+
+struct SourceFormatter<'a> {
+    out: String,
+    indent: usize,
+    // Fim do bloco ou `switch` sendo escrito; comentários depois dele não ficam
+    // no fim da linha dos seus comandos
+    block_end: Option<Position>,
+    comments: Peekable<Iter<'a, Comment>>,
+}
+
+impl SourceFormatter<'_> {
+    fn write_indent(&mut self) {
+        self.out.push_str(&INDENT.repeat(self.indent));
+    }
+
+    // Escreve, na indentação atual, os comentários que começam antes de `until`.
+    // As linhas seguintes de um comentário mudam de indentação junto com a
+    // primeira, mantendo o recuo relativo a ela; linhas em branco continuam vazias.
+    fn flush_comments(&mut self, until: Option<Position>) {
+        while let Some(comment) = self
+            .comments
+            .next_if(|comment| until.is_none_or(|until| comment.span.start < until))
+        {
+            let base = comment.span.start.column - 1;
+            let mut lines = comment.text.split('\n').peekable();
+
+            self.write_indent();
+            self.out.push_str("{%");
+            self.out.push_str(lines.next().unwrap_or_default());
+            while let Some(line) = lines.next() {
+                self.out.push('\n');
+                let margin = line.len() - line.trim_start_matches([' ', '\t']).len();
+                let line = &line[margin.min(base)..];
+                if lines.peek().is_none() && line.trim().is_empty() {
+                    // Só falta o `%}`, que fica alinhado com o `{%`
+                    self.write_indent();
+                } else if !line.trim().is_empty() {
+                    self.write_indent();
+                    self.out.push_str(line.trim_end());
+                }
+            }
+            self.out.push_str("%}\n");
+        }
+    }
+
+    // Escreve no fim da linha atual os comentários de uma linha só que começam
+    // depois de `span`, na mesma linha em que ele termina
+    fn write_trailing_comments(&mut self, span: Option<Span>) {
+        let Some(span) = span else {
+            return;
+        };
+        let block_end = self.block_end;
+        while let Some(comment) = self.comments.next_if(|comment| {
+            comment.span.start.line == span.end.line
+                && comment.span.start >= span.end
+                && block_end.is_none_or(|end| comment.span.start < end)
+                && !comment.text.contains('\n')
+        }) {
+            self.out.push_str(&format!(" {{%{}%}}", comment.text));
+        }
+    }
+
+    // Escreve um comando (ou declaração) em linha própria
+    fn write_statement(&mut self, node: &AstNode, span: Option<Span>) {
+        self.flush_comments(span.map(|span| span.start));
+        self.write_indent();
+        self.write_inline_statement(node, span);
+        self.write_trailing_comments(span);
+        self.out.push('\n');
+    }
+
+    // Escreve um comando a partir da posição atual da linha, sem a quebra final
+    fn write_inline_statement(&mut self, node: &AstNode, span: Option<Span>) {
+        match node {
+            AstNode::Program {
+                records,
+                globals,
+                functions,
+                kind,
+                body,
+            } => {
+                // Cada registro e cada função em seu próprio parágrafo, antes de
+                // `main`, e as globais juntas, uma por linha. Os comentários entre
+                // eles ficam antes do item seguinte.
+                let items: Vec<&Spanned<AstNode>> =
+                    records.iter().chain(globals).chain(functions).collect();
+                let first = items.first().map_or(body.span, |first| first.span);
+                self.flush_comments(first.map(|span| span.start));
+                for (i, item) in items.iter().enumerate() {
+                    self.write_inline_statement(item, item.span);
+                    self.write_trailing_comments(item.span);
+                    let next_global = i >= records.len() && i + 1 < records.len() + globals.len();
+                    self.out.push_str(if next_global { "\n" } else { "\n\n" });
+                    let next = items.get(i + 1).map_or(body.span, |next| next.span);
+                    self.flush_comments(next.map(|span| span.start));
+                }
+                self.out.push_str(&format!("{} main() ", kind));
+                self.write_block(body, body.span);
+                self.write_trailing_comments(body.span);
+            }
+            AstNode::Record {
+                kind: Type::Record { name, fields, .. },
+            } => {
+                self.out.push_str(&format!("tipo {} [\n", name));
+                self.indent += 1;
+                for field in fields {
+                    self.write_indent();
+                    let decl = match &field.kind {
+                        Type::Array { elem, size } => {
+                            format!("{} {}[{}];\n", elem, field.name, size)
+                        }
+                        kind => format!("{} {};\n", kind, field.name),
+                    };
+                    self.out.push_str(&decl);
+                }
+                self.indent -= 1;
+                self.write_indent();
+                self.out.push(']');
+            }
+            AstNode::Function {
+                kind,
+                name,
+                params,
+                body,
+            } => {
+                let params: Vec<String> = params
+                    .iter()
+                    .map(|param| match &param.node {
+                        AstNode::VarDecl { .. } => format_declaration(param),
+                        param => format_expression(param),
+                    })
+                    .collect();
+                self.out
+                    .push_str(&format!("{} {}({}) ", kind, name, params.join(", ")));
+                self.write_block(body, body.span);
+            }
+            AstNode::Block { .. } => self.write_block(node, span),
+            AstNode::VarDecl { .. } => {
+                self.out.push_str(&format!("{};", format_declaration(node)));
+            }
+            AstNode::Assignment { id, expr } => {
+                self.out
+                    .push_str(&format!("{} := {};", id, format_expression(expr)));
+            }
+            AstNode::ArrayAssignment { array, index, expr } => {
+                self.out.push_str(&format!(
+                    "{}[{}] := {};",
+                    format_expression(array),
+                    format_expression(index),
+                    format_expression(expr)
+                ));
+            }
+            AstNode::FieldAssignment {
+                record,
+                field,
+                expr,
+            } => {
+                self.out.push_str(&format!(
+                    "{}.{} := {};",
+                    format_expression(record),
+                    field,
+                    format_expression(expr)
+                ));
+            }
+            AstNode::Call { .. } => {
+                self.out.push_str(&format!("{};", format_expression(node)));
+            }
+            AstNode::Return { expr } => {
+                self.out
+                    .push_str(&format!("return {};", format_expression(expr)));
+            }
+            AstNode::Break => self.out.push_str("break;"),
+            AstNode::Continue => self.out.push_str("continue;"),
+            AstNode::If { .. } => self.write_if(node, span),
+            AstNode::While { cond, body } => {
+                self.out
+                    .push_str(&format!("while ({}) do ", format_expression(cond)));
+                self.write_inline_statement(body, body.span);
+            }
+            AstNode::DoWhile { body, cond } => {
+                self.out.push_str("do ");
+                self.write_inline_statement(body, body.span);
+                self.out
+                    .push_str(&format!(" while ({});", format_expression(cond)));
+            }
+            AstNode::For {
+                id,
+                start,
+                end,
+                step,
+                body,
+            } => {
+                self.out.push_str(&format!(
+                    "for ({}; {}; {}; {}) ",
+                    id,
+                    format_expression(start),
+                    format_expression(end),
+                    format_expression(step)
+                ));
+                self.write_inline_statement(body, body.span);
+            }
+            AstNode::Switch { .. } => self.write_switch(node, span),
+            AstNode::Case { label, body } => {
+                self.out
+                    .push_str(&format!("case {}: ", format_expression(label)));
+                self.write_inline_statement(body, body.span);
+            }
+            // Expressões e nós auxiliares não são comandos, mas são escritos como
+            // expressão para que qualquer nó possa ser formatado
+            _ => self.out.push_str(&format_expression(node)),
+        }
+    }
+
+    // if (...) then ... elsif (...) then ... else ...
+    fn write_if(&mut self, node: &AstNode, span: Option<Span>) {
+        let mut current = node;
+        let mut current_span = span;
+        let mut keyword = "if";
+
+        loop {
+            match current {
+                AstNode::If {
+                    cond,
+                    then_block,
+                    else_block,
+                } => {
+                    self.out
+                        .push_str(&format!("{} ({}) then ", keyword, format_expression(cond)));
+                    self.write_inline_statement(then_block, then_block.span);
+
+                    match else_block {
+                        Some(else_block) => {
+                            // Comentários entre o `]` e o `else` ficam fora dos
+                            // dois blocos, com o `else` na linha seguinte
+                            let until = else_block.span.map(|span| span.start);
+                            let before_else = self.comments.peek().is_some_and(|comment| {
+                                until.is_some_and(|until| comment.span.start < until)
+                            });
+                            if before_else {
+                                self.write_trailing_comments(then_block.span);
+                                self.out.push('\n');
+                                self.flush_comments(until);
+                                self.write_indent();
+                            } else {
+                                self.out.push(' ');
+                            }
+                            keyword = "elsif";
+                            current_span = else_block.span;
+                            current = else_block;
+                        }
+                        None => break,
+                    }
+                }
+                else_block => {
+                    self.out.push_str("else ");
+                    self.write_inline_statement(else_block, current_span);
+                    break;
+                }
+            }
+        }
+    }
+
+    // switch (...) [ com um ramo por linha e o `default` por último
+    fn write_switch(&mut self, node: &AstNode, span: Option<Span>) {
+        let AstNode::Switch {
+            expr,
+            cases,
+            default,
+        } = node
+        else {
+            self.out.push_str(&format_expression(node));
+            return;
+        };
+        self.out
+            .push_str(&format!("switch ({}) [\n", format_expression(expr)));
+        self.indent += 1;
+        let outer_end = std::mem::replace(&mut self.block_end, span.map(|span| span.end));
+
+        for case in cases {
+            self.write_statement(case, case.span);
+        }
+
+        if let Some(default) = default {
+            self.flush_comments(default.span.map(|span| span.start));
+            self.write_indent();
+            self.out.push_str("default: ");
+            self.write_inline_statement(default, default.span);
+            self.write_trailing_comments(default.span);
+            self.out.push('\n');
+        }
+
+        if let Some(span) = span {
+            self.flush_comments(Some(span.end));
+        }
+
+        self.block_end = outer_end;
+        self.indent -= 1;
+        self.write_indent();
+        self.out.push(']');
+    }
+
+    fn write_block(&mut self, node: &AstNode, span: Option<Span>) {
+        let AstNode::Block { decls, stmts } = node else {
+            self.out.push_str(&format_expression(node));
+            return;
+        };
+
+        self.out.push_str("[\n");
+        self.indent += 1;
+        let outer_end = std::mem::replace(&mut self.block_end, span.map(|span| span.end));
+
+        for decl in decls {
+            self.write_statement(decl, decl.span);
+        }
+
+        if !decls.is_empty() && !stmts.is_empty() {
+            self.out.push('\n');
+        }
+
+        for stmt in stmts {
+            self.write_statement(stmt, stmt.span);
+        }
+
+        // Comentários no fim do bloco, antes do ']'
+        if let Some(span) = span {
+            self.flush_comments(Some(span.end));
+        }
+
+        self.block_end = outer_end;
+        self.indent -= 1;
+        self.write_indent();
+        self.out.push(']');
+    }
+}
+
+// `const int a := 1, b` ou, para vetores, `int v[10]`
+fn format_declaration(node: &AstNode) -> String {
+    let AstNode::VarDecl {
+        kind,
+        names,
+        inits,
+        constant,
+    } = node
+    else {
+        return format_expression(node);
+    };
+
+    let qualifier = if *constant { "const " } else { "" };
+    let names: Vec<String> = names
+        .iter()
+        .zip(inits)
+        .map(|(name, init)| match init {
+            Some(init) => format!("{} := {}", name, format_expression(init)),
+            None => name.clone(),
+        })
+        .collect();
+
+    match kind {
+        Type::Array { elem, size } => {
+            format!("{}{} {}[{}]", qualifier, elem, names.join(", "), size)
+        }
+        kind => format!("{}{} {}", qualifier, kind, names.join(", ")),
+    }
+}
+
+// Precedência de cada nó de expressão, seguindo a tabela da gramática (E a U):
+// comparação < | < ^ < & < << >> < + - < * / % < unário < ** < operandos
+fn precedence(node: &AstNode) -> u8 {
+    match node {
+        AstNode::BinaryComp { .. } => 0,
+        AstNode::BinaryOp { op, .. } => match op {
+            OperatorKind::BitOr => 1,
+            OperatorKind::BitXor => 2,
+            OperatorKind::BitAnd => 3,
+            OperatorKind::Shl | OperatorKind::Shr => 4,
+            OperatorKind::Sum | OperatorKind::Sub => 5,
+            OperatorKind::Mult | OperatorKind::Div | OperatorKind::Mod => 6,
+            _ => 8,
+        },
+        AstNode::UnaryOp { .. } => 7,
+        _ => 9,
+    }
+}
+
+// Escreve uma expressão usando apenas os parênteses necessários
+pub fn format_expression(node: &AstNode) -> String {
+    format_operand(node, 0)
+}
+
+// Envolve a expressão em parênteses se sua precedência for menor que `min`
+fn format_operand(node: &AstNode, min: u8) -> String {
+    let text = match node {
+        AstNode::BinaryComp { relop, left, right } => format!(
+            "{} {} {}",
+            format_operand(left, 1),
+            relop,
+            format_operand(right, 1)
+        ),
+        // `**` associa à direita: à esquerda só cabe um operando simples, e à
+        // direita também um unário ou outro `**`
+        AstNode::BinaryOp {
+            op: OperatorKind::Exp,
+            left,
+            right,
+        } => format!(
+            "{} ** {}",
+            format_operand(left, 9),
+            format_operand(right, 7)
+        ),
+        AstNode::BinaryOp { op, left, right } => {
+            let level = precedence(node);
+            format!(
+                "{} {} {}",
+                format_operand(left, level),
+                op,
+                format_operand(right, level + 1)
+            )
+        }
+        AstNode::UnaryOp { op, expr } => {
+            let operand = format_operand(expr, 7);
+            // Evita juntar dois '-' seguidos, que seriam lidos como `--`
+            if *op == OperatorKind::Sub && operand.starts_with('-') {
+                format!("- {}", operand)
+            } else {
+                format!("{}{}", op, operand)
+            }
+        }
+        AstNode::Call { name, args } => format!(
+            "{}({})",
+            name,
+            args.iter()
+                .map(|arg| format_expression(arg))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        AstNode::Number { value } => value.clone(),
+        AstNode::Identifier { name } => name.clone(),
+        AstNode::ArrayAccess { array, index } => {
+            format!("{}[{}]", format_expression(array), format_expression(index))
+        }
+        AstNode::FieldAccess { record, field } => {
+            format!("{}.{}", format_expression(record), field)
+        }
+        AstNode::Literal { value } => format!("'{}'", value),
+        AstNode::Bool { value } => value.to_string(),
+        AstNode::TypeWrapper(kind) => kind.to_string(),
+        AstNode::CondWrapper(relop) => relop.to_string(),
+        other => other.to_sexp(),
+    };
+
+    if precedence(node) < min {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,840 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{Display, Error, Formatter},
+    rc::Rc,
+};
+
+use crate::{AstNode, OperatorKind, RelopKind, Spanned, Type, format_expression};
+
+// Total de voltas (somando todos os laços) antes de desistir da execução
+const MAX_ITERATIONS: u64 = 1_000_000;
+
+// Pilha do Rust que a execução pode ocupar antes de desistir. O limite
+// é em bytes, e não em níveis, porque o custo de cada nível muda entre debug e
+// release; cabe com folga nas threads de 2MB dos testes.
+const STACK_BUDGET: usize = 1 << 20;
+
+// Bytes que as variáveis de um programa podem declarar, segundo `Type::size`
+const MAX_MEMORY: u32 = 1 << 22;
+
+// Elementos de um vetor (ou campos de um registro), compartilhados entre quem
+// recebe o vetor
+type Elements = Rc<RefCell<Vec<Value>>>;
+
+// Valor produzido pela avaliação de uma expressão
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Char(char),
+    Bool(bool),
+    // Vetores são passados por referência, como em C
+    Array(Elements),
+    // Campos na ordem da declaração; registros são copiados ao serem atribuídos
+    Record(Elements),
+}
+
+impl Value {
+    // Valor inicial dos elementos de um vetor e dos campos de um registro. Cada
+    // elemento recebe seu próprio valor, para que não compartilhem registros.
+    fn zero(kind: &Type) -> Value {
+        match kind {
+            Type::Float => Value::Float(0.0),
+            Type::Char => Value::Char('\0'),
+            Type::Bool => Value::Bool(false),
+            Type::Array { elem, size } => Value::Array(Rc::new(RefCell::new(
+                (0..*size).map(|_| Value::zero(elem)).collect(),
+            ))),
+            Type::Record { fields, .. } => Value::Record(Rc::new(RefCell::new(
+                fields
+                    .iter()
+                    .map(|field| Value::zero(&field.kind))
+                    .collect(),
+            ))),
+            Type::Int | Type::Void => Value::Int(0),
+        }
+    }
+
+    // Cópia independente de um registro, incluindo os vetores dentro dele
+    fn deep_copy(&self) -> Value {
+        let copy = |items: &Elements| {
+            Rc::new(RefCell::new(
+                items.borrow().iter().map(Value::deep_copy).collect(),
+            ))
+        };
+
+        match self {
+            Value::Array(items) => Value::Array(copy(items)),
+            Value::Record(fields) => Value::Record(copy(fields)),
+            value => value.clone(),
+        }
+    }
+
+    // Caracteres entram nas contas pelo código
+    fn as_number(&self) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Char(value) => Ok(Value::Int(*value as i64)),
+            Value::Bool(_) => Err(RuntimeError::TypeMismatch(
+                "Lógico usado como número".to_string(),
+            )),
+            Value::Array(_) => Err(RuntimeError::TypeMismatch(
+                "Vetor usado como número".to_string(),
+            )),
+            Value::Record(_) => Err(RuntimeError::TypeMismatch(
+                "Registro usado como número".to_string(),
+            )),
+            number => Ok(number.clone()),
+        }
+    }
+
+    fn as_float(&self) -> Result<f64, RuntimeError> {
+        match self.as_number()? {
+            Value::Int(value) => Ok(value as f64),
+            Value::Float(value) => Ok(value),
+            _ => unreachable!(),
+        }
+    }
+
+    fn as_int(&self) -> Result<i64, RuntimeError> {
+        match self.as_number()? {
+            Value::Int(value) => Ok(value),
+            _ => Err(RuntimeError::TypeMismatch(format!(
+                "Era esperado um inteiro, recebido {}",
+                self
+            ))),
+        }
+    }
+
+    // Converte o valor para o tipo declarado da variável que vai recebê-lo
+    fn coerce(self, kind: &Type) -> Result<Value, RuntimeError> {
+        match (kind, self) {
+            (Type::Int, Value::Float(value)) => Ok(Value::Int(value as i64)),
+            (Type::Int, value @ (Value::Int(_) | Value::Char(_))) => value.as_number(),
+            (Type::Float, value @ (Value::Int(_) | Value::Float(_) | Value::Char(_))) => {
+                Ok(Value::Float(value.as_float()?))
+            }
+            (Type::Char, Value::Char(value)) => Ok(Value::Char(value)),
+            (Type::Char, Value::Int(value)) => u32::try_from(value)
+                .ok()
+                .and_then(char::from_u32)
+                .map(Value::Char)
+                .ok_or_else(|| {
+                    RuntimeError::TypeMismatch(format!("{} não é um caractere válido", value))
+                }),
+            (Type::Bool, value @ Value::Bool(_)) => Ok(value),
+            (Type::Array { .. }, value @ Value::Array(_)) => Ok(value),
+            (Type::Record { fields, .. }, Value::Record(values))
+                if fields.len() == values.borrow().len() =>
+            {
+                Ok(Value::Record(values).deep_copy())
+            }
+            (kind, value) => Err(RuntimeError::TypeMismatch(format!(
+                "{} não pode ser guardado em {}",
+                value, kind
+            ))),
+        }
+    }
+
+    // Condições precisam ser lógicas; números não viram verdadeiro ou falso
+    fn is_true(&self) -> Result<bool, RuntimeError> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            other => Err(RuntimeError::TypeMismatch(format!(
+                "Condição {} não é do tipo bool",
+                other
+            ))),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{:?}", value),
+            Value::Char(value) => write!(f, "'{}'", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Array(items) => {
+                let items: Vec<String> =
+                    items.borrow().iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Record(fields) => {
+                let fields: Vec<String> = fields
+                    .borrow()
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect();
+                write!(f, "{{{}}}", fields.join(", "))
+            }
+        }
+    }
+}
+
+// Erro durante a execução do programa
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    UndefinedVariable(String),
+    UninitializedVariable(String),
+    UndefinedFunction(String),
+    RedefinedFunction(String),
+    ConstantAssignment(String),
+    ArgumentCount {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    IndexOutOfBounds {
+        name: String,
+        index: i64,
+        size: usize,
+    },
+    DivisionByZero,
+    // `<<` ou `>>` com deslocamento negativo ou de 64 bits ou mais
+    InvalidShift(i64),
+    TypeMismatch(String),
+    // Função `void` (ou sem `return`) usada como expressão
+    MissingValue(String),
+    // Nó que não é comando nem expressão, como `List` ou `TypeWrapper`
+    InvalidNode(String),
+    IterationLimit,
+    // Vetor ou registro maior que `MAX_MEMORY`
+    OutOfMemory(String),
+    // Recursão (ou aninhamento) que ocupa mais que `STACK_BUDGET`
+    StackOverflow,
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::UndefinedVariable(name) => write!(f, "Variável '{}' não declarada", name),
+            Self::UninitializedVariable(name) => {
+                write!(f, "Variável '{}' usada sem valor", name)
+            }
+            Self::UndefinedFunction(name) => write!(f, "Função '{}' não definida", name),
+            Self::RedefinedFunction(name) => {
+                write!(f, "Função '{}' definida mais de uma vez", name)
+            }
+            Self::ConstantAssignment(name) => {
+                write!(f, "Atribuição à constante '{}'", name)
+            }
+            Self::ArgumentCount {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Função '{}' espera {} argumento(s), recebeu {}",
+                name, expected, found
+            ),
+            Self::IndexOutOfBounds { name, index, size } => write!(
+                f,
+                "Índice {} fora do vetor '{}' de tamanho {}",
+                index, name, size
+            ),
+            Self::DivisionByZero => write!(f, "Divisão por zero"),
+            Self::InvalidShift(amount) => {
+                write!(f, "Deslocamento de {} bits fora do intervalo 0..64", amount)
+            }
+            Self::TypeMismatch(message) => write!(f, "{}", message),
+            Self::MissingValue(name) => write!(f, "Função '{}' não devolveu valor", name),
+            Self::InvalidNode(sexp) => write!(f, "Nó não pode ser executado: {}", sexp),
+            Self::IterationLimit => {
+                write!(f, "Limite de {} voltas de laço atingido", MAX_ITERATIONS)
+            }
+            Self::OutOfMemory(name) => write!(
+                f,
+                "Variável '{}' ocupa mais que o limite de {} bytes",
+                name, MAX_MEMORY
+            ),
+            Self::StackOverflow => write!(
+                f,
+                "Limite de {}KB da pilha de chamadas atingido",
+                STACK_BUDGET >> 10
+            ),
+        }
+    }
+}
+
+// Executa `main` e devolve o valor do seu `return` (`None` se o programa for
+// `void` ou terminar sem `return`). É a semântica de referência da linguagem:
+//   - globais são iniciadas em ordem antes de `main` e valem em todas as funções;
+//   - variáveis começam sem valor, exceto vetores e registros, cujos elementos e
+//     campos começam em 0;
+//   - atribuições convertem o valor para o tipo declarado (real para inteiro trunca)
+//     e copiam registros, enquanto vetores são compartilhados;
+//   - `for (i; a; b; p)` avalia `a` e `b` uma vez, e executa o corpo enquanto
+//     `i < b`, fazendo `i := p` depois de cada volta (também após `continue`);
+//   - comparações dão um lógico, e condições precisam ser lógicas;
+//   - `%` segue o sinal do dividendo, como `/` trunca; `& | ^ ~ << >>` só valem
+//     para inteiros (e caracteres), com o deslocamento entre 0 e 63.
+pub fn evaluate(program: &AstNode) -> Result<Option<Value>, RuntimeError> {
+    let AstNode::Program {
+        globals,
+        functions,
+        kind,
+        body,
+        ..
+    } = program
+    else {
+        return Err(RuntimeError::InvalidNode(program.to_sexp()));
+    };
+
+    let mut table = HashMap::new();
+    for function in functions {
+        if let AstNode::Function { name, .. } = &function.node
+            && table.insert(name.as_str(), &function.node).is_some()
+        {
+            return Err(RuntimeError::RedefinedFunction(name.clone()));
+        }
+    }
+
+    let base = 0u8;
+    let mut interpreter = Interpreter {
+        functions: table,
+        globals: HashMap::new(),
+        scopes: vec![],
+        iterations: 0,
+        stack_base: &raw const base as usize,
+    };
+
+    // Cada global é iniciada num escopo próprio e só então passa a ser vista, em
+    // qualquer função chamada pelas iniciações seguintes
+    for global in globals {
+        interpreter.scopes = vec![HashMap::new()];
+        interpreter.statement(global)?;
+        let scope = interpreter.scopes.pop().unwrap_or_default();
+        interpreter.globals.extend(scope);
+    }
+
+    match interpreter.statement(body)? {
+        Flow::Return(value) if *kind != Type::Void => value.coerce(kind).map(Some),
+        _ => Ok(None),
+    }
+}
+
+// Resultado de um comando: segue para o próximo, desvia o laço atual ou
+// encerra a função
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+struct Variable {
+    kind: Type,
+    value: Option<Value>,
+    constant: bool,
+}
+
+struct Interpreter<'a> {
+    functions: HashMap<&'a str, &'a AstNode>,
+    // Declarações globais, compartilhadas por todas as funções
+    globals: HashMap<String, Variable>,
+    // Um escopo por bloco aberto na função atual
+    scopes: Vec<HashMap<String, Variable>>,
+    iterations: u64,
+    // Endereço de uma variável local de `evaluate`; a distância até ela mede a
+    // pilha ocupada pelas chamadas em andamento
+    stack_base: usize,
+}
+
+impl Interpreter<'_> {
+    fn statement(&mut self, node: &AstNode) -> Result<Flow, RuntimeError> {
+        self.check_stack()?;
+        match node {
+            AstNode::Block { decls, stmts } => {
+                self.scopes.push(HashMap::new());
+                let flow = self.block(decls, stmts);
+                self.scopes.pop();
+                flow
+            }
+            AstNode::VarDecl {
+                kind,
+                names,
+                inits,
+                constant,
+            } => {
+                for (name, init) in names.iter().zip(inits) {
+                    let value = match (kind, init) {
+                        (_, Some(init)) => Some(self.expression(init)?.coerce(kind)?),
+                        (Type::Array { .. } | Type::Record { .. }, None) => {
+                            if kind.size().is_none_or(|size| size > MAX_MEMORY) {
+                                return Err(RuntimeError::OutOfMemory(name.clone()));
+                            }
+                            Some(Value::zero(kind))
+                        }
+                        (_, None) => None,
+                    };
+                    self.declare(name, kind.clone(), value, *constant);
+                }
+                Ok(Flow::Normal)
+            }
+            AstNode::Assignment { id, expr } => {
+                let value = self.expression(expr)?;
+                self.assign(id, value)?;
+                Ok(Flow::Normal)
+            }
+            AstNode::ArrayAssignment { array, index, expr } => {
+                let (items, position, elem) = self.element(array, index)?;
+                let value = self.expression(expr)?;
+                items.borrow_mut()[position] = value.coerce(&elem)?;
+                Ok(Flow::Normal)
+            }
+            AstNode::FieldAssignment {
+                record,
+                field,
+                expr,
+            } => {
+                let (fields, position, kind) = self.field(record, field)?;
+                let value = self.expression(expr)?;
+                fields.borrow_mut()[position] = value.coerce(&kind)?;
+                Ok(Flow::Normal)
+            }
+            AstNode::Call { name, args } => {
+                self.call(name, args)?;
+                Ok(Flow::Normal)
+            }
+            AstNode::Return { expr } => Ok(Flow::Return(self.expression(expr)?)),
+            AstNode::Break => Ok(Flow::Break),
+            AstNode::Continue => Ok(Flow::Continue),
+            AstNode::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                if self.expression(cond)?.is_true()? {
+                    self.statement(then_block)
+                } else if let Some(else_block) = else_block {
+                    self.statement(else_block)
+                } else {
+                    Ok(Flow::Normal)
+                }
+            }
+            AstNode::While { cond, body } => {
+                while self.expression(cond)?.is_true()? {
+                    self.tick()?;
+                    match self.statement(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+                Ok(Flow::Normal)
+            }
+            AstNode::DoWhile { body, cond } => {
+                loop {
+                    self.tick()?;
+                    // `continue` ainda testa a condição
+                    match self.statement(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                    if !self.expression(cond)?.is_true()? {
+                        break;
+                    }
+                }
+                Ok(Flow::Normal)
+            }
+            AstNode::For {
+                id,
+                start,
+                end,
+                step,
+                body,
+            } => {
+                let start = self.expression(start)?;
+                let end = self.expression(end)?;
+                self.assign(id, start)?;
+
+                while compare(&RelopKind::LT, &self.read(id)?, &end)? {
+                    self.tick()?;
+                    // `continue` ainda executa o passo
+                    match self.statement(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                    let next = self.expression(step)?;
+                    self.assign(id, next)?;
+                }
+                Ok(Flow::Normal)
+            }
+            // O fluxo do ramo escolhido segue adiante: `break` e `continue` são
+            // do laço em volta
+            AstNode::Switch {
+                expr,
+                cases,
+                default,
+            } => {
+                let value = self.expression(expr)?;
+                for case in cases {
+                    if let AstNode::Case { label, body } = &case.node
+                        && compare(&RelopKind::EQ, &value, &self.expression(label)?)?
+                    {
+                        return self.statement(body);
+                    }
+                }
+                match default {
+                    Some(default) => self.statement(default),
+                    None => Ok(Flow::Normal),
+                }
+            }
+            expr => {
+                self.expression(expr)?;
+                Ok(Flow::Normal)
+            }
+        }
+    }
+
+    fn block(
+        &mut self,
+        decls: &[Spanned<AstNode>],
+        stmts: &[Spanned<AstNode>],
+    ) -> Result<Flow, RuntimeError> {
+        for node in decls.iter().chain(stmts) {
+            match self.statement(node)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn expression(&mut self, node: &AstNode) -> Result<Value, RuntimeError> {
+        self.check_stack()?;
+        match node {
+            AstNode::Number { value } => {
+                let invalid = || RuntimeError::TypeMismatch(format!("Número inválido: {}", value));
+                match value.parse::<i64>() {
+                    Ok(value) => Ok(Value::Int(value)),
+                    // Só um literal real, com `.` ou expoente, vira float; um inteiro
+                    // que não cabe em 64 bits é erro
+                    Err(_) if value.contains(['.', 'E']) => value
+                        .parse::<f64>()
+                        .map(Value::Float)
+                        .map_err(|_| invalid()),
+                    Err(_) => Err(invalid()),
+                }
+            }
+            AstNode::Literal { value } => Ok(Value::Char(*value)),
+            AstNode::Bool { value } => Ok(Value::Bool(*value)),
+            AstNode::Identifier { name } => self.read(name),
+            AstNode::ArrayAccess { .. } | AstNode::FieldAccess { .. } => {
+                let (items, position, _) = self.place(node)?;
+                let value = items.borrow()[position].clone();
+                Ok(value)
+            }
+            AstNode::Call { name, args } => self
+                .call(name, args)?
+                .ok_or_else(|| RuntimeError::MissingValue(name.clone())),
+            AstNode::UnaryOp {
+                op: OperatorKind::BitNot,
+                expr,
+            } => Ok(Value::Int(!self.expression(expr)?.as_int()?)),
+            AstNode::UnaryOp { expr, .. } => match self.expression(expr)?.as_number()? {
+                Value::Int(value) => Ok(Value::Int(value.wrapping_neg())),
+                Value::Float(value) => Ok(Value::Float(-value)),
+                _ => unreachable!(),
+            },
+            AstNode::BinaryOp { op, left, right } => {
+                let left = self.expression(left)?;
+                let right = self.expression(right)?;
+                arithmetic(op, &left, &right)
+            }
+            AstNode::BinaryComp { relop, left, right } => {
+                let left = self.expression(left)?;
+                let right = self.expression(right)?;
+                Ok(Value::Bool(compare(relop, &left, &right)?))
+            }
+            other => Err(RuntimeError::InvalidNode(other.to_sexp())),
+        }
+    }
+
+    // Executa a função em escopo próprio; só enxerga seus parâmetros e as
+    // variáveis que declarar
+    fn call(
+        &mut self,
+        name: &str,
+        args: &[Spanned<AstNode>],
+    ) -> Result<Option<Value>, RuntimeError> {
+        let Some(AstNode::Function {
+            kind, params, body, ..
+        }) = self.functions.get(name).copied()
+        else {
+            return Err(RuntimeError::UndefinedFunction(name.to_string()));
+        };
+
+        if params.len() != args.len() {
+            return Err(RuntimeError::ArgumentCount {
+                name: name.to_string(),
+                expected: params.len(),
+                found: args.len(),
+            });
+        }
+
+        let mut frame = HashMap::new();
+        for (param, arg) in params.iter().zip(args) {
+            if let AstNode::VarDecl {
+                kind: param_kind,
+                names,
+                ..
+            } = &param.node
+            {
+                let value = self.expression(arg)?.coerce(param_kind)?;
+                for name in names {
+                    frame.insert(
+                        name.clone(),
+                        Variable {
+                            kind: param_kind.clone(),
+                            value: Some(value.clone()),
+                            constant: false,
+                        },
+                    );
+                }
+            }
+        }
+
+        let caller = std::mem::replace(&mut self.scopes, vec![frame]);
+        let flow = self.statement(body);
+        self.scopes = caller;
+
+        match flow? {
+            Flow::Return(value) if *kind != Type::Void => value.coerce(kind).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    // Toda recursão passa por `statement` ou `expression`, que conferem aqui se a
+    // pilha ainda está dentro de `STACK_BUDGET`
+    fn check_stack(&self) -> Result<(), RuntimeError> {
+        let top = 0u8;
+        if self.stack_base.abs_diff(&raw const top as usize) > STACK_BUDGET {
+            return Err(RuntimeError::StackOverflow);
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) -> Result<(), RuntimeError> {
+        self.iterations += 1;
+        if self.iterations > MAX_ITERATIONS {
+            return Err(RuntimeError::IterationLimit);
+        }
+        Ok(())
+    }
+
+    fn declare(&mut self, name: &str, kind: Type, value: Option<Value>, constant: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(
+                name.to_string(),
+                Variable {
+                    kind,
+                    value,
+                    constant,
+                },
+            );
+        }
+    }
+
+    // Procura do escopo mais interno para o mais externo, e por fim nas globais
+    fn variable(&mut self, name: &str) -> Result<&mut Variable, RuntimeError> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .chain([&mut self.globals])
+            .find_map(|scope| scope.get_mut(name))
+            .ok_or_else(|| RuntimeError::UndefinedVariable(name.to_string()))
+    }
+
+    fn read(&mut self, name: &str) -> Result<Value, RuntimeError> {
+        self.variable(name)?
+            .value
+            .clone()
+            .ok_or_else(|| RuntimeError::UninitializedVariable(name.to_string()))
+    }
+
+    fn assign(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
+        let variable = self.variable(name)?;
+        if variable.constant {
+            return Err(RuntimeError::ConstantAssignment(name.to_string()));
+        }
+        variable.value = Some(value.coerce(&variable.kind)?);
+        Ok(())
+    }
+
+    // Valor de um identificador ou acesso, junto do seu tipo declarado
+    fn typed(&mut self, node: &AstNode) -> Result<(Value, Type), RuntimeError> {
+        match node {
+            AstNode::Identifier { name } => {
+                let value = self.read(name)?;
+                Ok((value, self.variable(name)?.kind.clone()))
+            }
+            AstNode::ArrayAccess { .. } | AstNode::FieldAccess { .. } => {
+                let (items, position, kind) = self.place(node)?;
+                let value = items.borrow()[position].clone();
+                Ok((value, kind))
+            }
+            other => Err(RuntimeError::TypeMismatch(format!(
+                "'{}' não é um vetor nem um registro",
+                format_expression(other)
+            ))),
+        }
+    }
+
+    // Onde fica o elemento ou campo acessado por `node`: os valores que o
+    // contêm, a posição já conferida e o tipo declarado
+    fn place(&mut self, node: &AstNode) -> Result<(Elements, usize, Type), RuntimeError> {
+        match node {
+            AstNode::ArrayAccess { array, index } => self.element(array, index),
+            AstNode::FieldAccess { record, field } => self.field(record, field),
+            other => Err(RuntimeError::InvalidNode(other.to_sexp())),
+        }
+    }
+
+    fn element(
+        &mut self,
+        array: &AstNode,
+        index: &AstNode,
+    ) -> Result<(Elements, usize, Type), RuntimeError> {
+        let index = self.expression(index)?.as_int()?;
+        let (Value::Array(items), Type::Array { elem, .. }) = self.typed(array)? else {
+            return Err(RuntimeError::TypeMismatch(format!(
+                "'{}' não é um vetor",
+                format_expression(array)
+            )));
+        };
+
+        let size = items.borrow().len();
+        match usize::try_from(index) {
+            Ok(position) if position < size => Ok((items, position, *elem)),
+            _ => Err(RuntimeError::IndexOutOfBounds {
+                name: format_expression(array),
+                index,
+                size,
+            }),
+        }
+    }
+
+    fn field(
+        &mut self,
+        record: &AstNode,
+        field: &str,
+    ) -> Result<(Elements, usize, Type), RuntimeError> {
+        let (Value::Record(values), Type::Record { name, fields, .. }) = self.typed(record)? else {
+            return Err(RuntimeError::TypeMismatch(format!(
+                "'{}' não é um registro",
+                format_expression(record)
+            )));
+        };
+
+        match fields.iter().position(|other| other.name == field) {
+            Some(position) => Ok((values, position, fields[position].kind.clone())),
+            None => Err(RuntimeError::TypeMismatch(format!(
+                "Registro '{}' não tem o campo '{}'",
+                name, field
+            ))),
+        }
+    }
+}
+
+// Inteiros com inteiros dão inteiro; qualquer real torna a conta real
+fn arithmetic(op: &OperatorKind, left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+    // Parênteses só existem no léxico, e `~` é unário
+    if matches!(
+        op,
+        OperatorKind::Paresq | OperatorKind::Pardir | OperatorKind::BitNot
+    ) {
+        return Err(RuntimeError::InvalidNode(op.to_string()));
+    }
+
+    if op.is_bitwise() {
+        return bitwise(op, left.as_int()?, right.as_int()?).map(Value::Int);
+    }
+
+    match (left.as_number()?, right.as_number()?) {
+        (Value::Int(left), Value::Int(right)) => match op {
+            OperatorKind::Sum => Ok(Value::Int(left.wrapping_add(right))),
+            OperatorKind::Sub => Ok(Value::Int(left.wrapping_sub(right))),
+            OperatorKind::Mult => Ok(Value::Int(left.wrapping_mul(right))),
+            OperatorKind::Div if right == 0 => Err(RuntimeError::DivisionByZero),
+            OperatorKind::Div => Ok(Value::Int(left.wrapping_div(right))),
+            OperatorKind::Mod if right == 0 => Err(RuntimeError::DivisionByZero),
+            OperatorKind::Mod => Ok(Value::Int(left.wrapping_rem(right))),
+            // Expoente negativo dá um real
+            OperatorKind::Exp => match u32::try_from(right) {
+                Ok(right) => Ok(Value::Int(left.wrapping_pow(right))),
+                Err(_) => Ok(Value::Float((left as f64).powf(right as f64))),
+            },
+            _ => unreachable!(),
+        },
+        (left, right) => {
+            let (left, right) = (left.as_float()?, right.as_float()?);
+            match op {
+                OperatorKind::Sum => Ok(Value::Float(left + right)),
+                OperatorKind::Sub => Ok(Value::Float(left - right)),
+                OperatorKind::Mult => Ok(Value::Float(left * right)),
+                OperatorKind::Div if right == 0.0 => Err(RuntimeError::DivisionByZero),
+                OperatorKind::Div => Ok(Value::Float(left / right)),
+                OperatorKind::Mod if right == 0.0 => Err(RuntimeError::DivisionByZero),
+                OperatorKind::Mod => Ok(Value::Float(left % right)),
+                OperatorKind::Exp => Ok(Value::Float(left.powf(right))),
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+// `>>` é aritmético: preserva o sinal do inteiro
+fn bitwise(op: &OperatorKind, left: i64, right: i64) -> Result<i64, RuntimeError> {
+    let shift = || {
+        u32::try_from(right)
+            .ok()
+            .filter(|amount| *amount < i64::BITS)
+            .ok_or(RuntimeError::InvalidShift(right))
+    };
+
+    match op {
+        OperatorKind::BitAnd => Ok(left & right),
+        OperatorKind::BitOr => Ok(left | right),
+        OperatorKind::BitXor => Ok(left ^ right),
+        OperatorKind::Shl => Ok(left << shift()?),
+        OperatorKind::Shr => Ok(left >> shift()?),
+        _ => unreachable!(),
+    }
+}
+
+fn compare(relop: &RelopKind, left: &Value, right: &Value) -> Result<bool, RuntimeError> {
+    // Lógicos só se comparam por igualdade
+    if let (Value::Bool(left), Value::Bool(right)) = (left, right) {
+        return match relop {
+            RelopKind::EQ => Ok(left == right),
+            RelopKind::NE => Ok(left != right),
+            relop => Err(RuntimeError::TypeMismatch(format!(
+                "Lógicos não se comparam com {}",
+                relop
+            ))),
+        };
+    }
+
+    let ordering = match (left.as_number()?, right.as_number()?) {
+        (Value::Int(left), Value::Int(right)) => left.partial_cmp(&right),
+        (left, right) => left.as_float()?.partial_cmp(&right.as_float()?),
+    };
+
+    // NaN não é menor, maior nem igual a nada
+    let Some(ordering) = ordering else {
+        return Ok(matches!(relop, RelopKind::NE));
+    };
+
+    Ok(match relop {
+        RelopKind::LT => ordering.is_lt(),
+        RelopKind::LE => ordering.is_le(),
+        RelopKind::GT => ordering.is_gt(),
+        RelopKind::GE => ordering.is_ge(),
+        RelopKind::EQ => ordering.is_eq(),
+        RelopKind::NE => ordering.is_ne(),
+    })
+}
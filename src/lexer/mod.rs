@@ -2,14 +2,50 @@ use std::collections::HashMap;
 mod token;
 
 pub use token::{
-    KeywordKind, LexerError, NumberKind, OperatorKind, PunctuationKind, RelopKind, Token,
-    TokenType, Type,
+    KeywordKind, LexerError, NumberKind, OperatorKind, Position, PunctuationKind, RelopKind, Span,
+    Token, TokenType, Type,
 };
 
 pub type SymbolTable = HashMap<String, SymbolEntry>;
 
 pub struct SymbolEntry {
     pub kind: Option<Type>,
+    // Preenchido pelo sintático quando o nome é declarado; funções, campos e
+    // nomes não declarados ficam sem escopo
+    pub scope: Option<Scope>,
+}
+
+// Onde um nome foi declarado. Um nome global continua `Global` mesmo que algum
+// bloco declare uma local com o mesmo nome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    Local,
+}
+
+// Comentário `{% ... %}` preservado quando o léxico roda com `keep_comments`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    // Texto entre `{%` e `%}`, sem alterações
+    pub text: String,
+    pub span: Span,
+}
+
+// Se um comentário `{% %}` continua aberto no fim de `line`, dado que já estava
+// aberto (`open`) no começo dela. Serve a quem olha o texto linha a linha antes
+// do léxico, como as diretivas de importação e de macros.
+pub(crate) fn comment_open_after(line: &str, mut open: bool) -> bool {
+    let mut rest = line;
+    loop {
+        let delimiter = if open { "%}" } else { "{%" };
+        match rest.find(delimiter) {
+            Some(index) => {
+                rest = &rest[index + 2..];
+                open = !open;
+            }
+            None => return open,
+        }
+    }
 }
 
 pub struct Lexer<'a> {
@@ -18,12 +54,15 @@ pub struct Lexer<'a> {
     pub prox: usize,
     pub line: usize,
     pub column: usize,
+    // Guarda os comentários em `comments` em vez de descartá-los
+    pub keep_comments: bool,
+    pub comments: Vec<Comment>,
     has_atleast_one_digit: bool,
     has_lookahead: bool,
     lookahead: Option<char>,
     prev_column: usize,
     prev_line: usize,
-    symbol_table: &'a mut SymbolTable,
+    pub symbol_table: &'a mut SymbolTable,
 }
 
 impl<'a> Lexer<'a> {
@@ -34,6 +73,8 @@ impl<'a> Lexer<'a> {
             prox: 0,
             line: 1,
             column: 1,
+            keep_comments: false,
+            comments: Vec::new(),
             has_atleast_one_digit: false,
             has_lookahead: false,
             lookahead: None,
@@ -90,6 +131,12 @@ impl<'a> Lexer<'a> {
                     self.ini = self.prox;
                 }
                 Some('{') => {
+                    // Posição do '{', caso o comentário precise ser preservado
+                    let start = Position {
+                        line: self.prev_line,
+                        column: self.prev_column,
+                    };
+
                     // Pega o próximo char para verificar se é '%'
                     let next = {
                         let _ = self.prox_char(); // Consome '{'
@@ -99,21 +146,35 @@ impl<'a> Lexer<'a> {
                     if next == Some('%') {
                         self.prox_char(); // Consome '%'
                         self.ini = self.prox;
+                        let text_start = self.prox;
+                        let mut text_end = self.file_content.len();
 
                         // Procura por '%}'
                         loop {
                             match self.prox_char() {
-                                Some('%') => {
-                                    if self.peek_char() == Some('}') {
-                                        self.prox_char(); // Consome '}'
-                                        self.ini = self.prox;
-                                        break; // Comentário terminado
-                                    }
+                                Some('%') if self.peek_char() == Some('}') => {
+                                    self.prox_char(); // Consome '}'
+                                    self.ini = self.prox;
+                                    text_end = self.prox - 2;
+                                    break; // Comentário terminado
                                 }
                                 None => break, // EOF
                                 _ => {}        // Continua procurando
                             }
                         }
+
+                        if self.keep_comments {
+                            self.comments.push(Comment {
+                                text: self.file_content[text_start..text_end].iter().collect(),
+                                span: Span {
+                                    start,
+                                    end: Position {
+                                        line: self.line,
+                                        column: self.column,
+                                    },
+                                },
+                            });
+                        }
                     } else {
                         // Não era um comentário, devolve o '{'
                         self.trata_lookahead();
@@ -152,9 +213,10 @@ impl<'a> Lexer<'a> {
     fn insert_table(&mut self, token: Token) {
         match token {
             Token::Id { value, .. } => {
-                self.symbol_table
-                    .entry(value)
-                    .or_insert(SymbolEntry { kind: None });
+                self.symbol_table.entry(value).or_insert(SymbolEntry {
+                    kind: None,
+                    scope: None,
+                });
             }
             _ => {
                 panic!("Token não pode ser inserido: {}", token);
@@ -185,11 +247,21 @@ impl<'a> Lexer<'a> {
                         } else if ch == '*' {
                             state = 9; // t2
                         } else if ch == '/' {
-                            state = 12; // s3
+                            state = 151; // s11
                         } else if ch == '-' {
-                            state = 13; // r3
+                            state = 153; // r4
                         } else if ch == '+' {
-                            state = 14; // r2
+                            state = 156; // r7
+                        } else if ch == '%' {
+                            state = 162; // t5
+                        } else if ch == '&' {
+                            state = 163; // t6
+                        } else if ch == '|' {
+                            state = 164; // t7
+                        } else if ch == '^' {
+                            state = 165; // t8
+                        } else if ch == '~' {
+                            state = 166; // t9
                         } else if ch == '!' {
                             state = 15; // q10
                         } else if ch == '<' {
@@ -200,6 +272,8 @@ impl<'a> Lexer<'a> {
                             state = 23; // q2
                         } else if ch == ',' {
                             state = 25; // y3
+                        } else if ch == '.' {
+                            state = 118; // y4
                         } else if ch == ';' {
                             state = 26; // y2
                         } else if ch == ':' {
@@ -224,6 +298,12 @@ impl<'a> Lexer<'a> {
                             state = 80; // n2
                         } else if ch == 'f' {
                             state = 83; // o2
+                        } else if ch == 'r' {
+                            state = 94; // a2
+                        } else if ch == 'b' {
+                            state = 106; // l3
+                        } else if ch == 's' {
+                            state = 143; // s4
                         } else if ch == '[' {
                             state = 92; // p2
                         } else if ch == ']' {
@@ -331,13 +411,18 @@ impl<'a> Lexer<'a> {
                 // t2
                 9 => {
                     c = self.prox_char();
-                    if let Some(ch) = c
-                        && ch == '*'
-                    {
-                        state = 10; // t3
+                    if let Some(ch) = c {
+                        if ch == '*' {
+                            state = 10; // t3
+                        } else if ch == '=' {
+                            state = 159; // t4
+                        } else {
+                            self.trata_lookahead();
+                            state = 11; // s2
+                        }
                     } else {
                         self.trata_lookahead();
-                        state = 11;
+                        state = 11; // s2
                     }
                 }
                 // t3
@@ -416,13 +501,18 @@ impl<'a> Lexer<'a> {
                 // q7
                 17 => {
                     c = self.prox_char();
-                    if let Some(ch) = c
-                        && ch == '='
-                    {
-                        state = 18; // q8
+                    if let Some(ch) = c {
+                        if ch == '=' {
+                            state = 18; // q8
+                        } else if ch == '<' {
+                            state = 160; // q12
+                        } else {
+                            self.trata_lookahead();
+                            state = 19; // q9
+                        }
                     } else {
-                        state = 19; // q9
                         self.trata_lookahead();
+                        state = 19; // q9
                     }
                 }
                 // q8
@@ -444,13 +534,18 @@ impl<'a> Lexer<'a> {
                 // q4
                 20 => {
                     c = self.prox_char();
-                    if let Some(ch) = c
-                        && ch == '='
-                    {
-                        state = 21; // q5
+                    if let Some(ch) = c {
+                        if ch == '=' {
+                            state = 21; // q5
+                        } else if ch == '>' {
+                            state = 161; // q13
+                        } else {
+                            self.trata_lookahead();
+                            state = 22; // q6
+                        }
                     } else {
-                        state = 22; // q6
                         self.trata_lookahead();
+                        state = 22; // q6
                     }
                 }
                 // q5
@@ -519,14 +614,9 @@ impl<'a> Lexer<'a> {
                     {
                         state = 28; // z3
                     } else {
+                        // Sem o '=', é o ':' dos rótulos do switch
                         self.trata_lookahead();
-                        return Err(format!(
-                            "Erro léxico: {value}\n Tipo: {kind:?}\n linha: {line}\n coluna: {col}",
-                            value = self.get_value(),
-                            kind = LexerError::MissingEqual,
-                            line = self.line,
-                            col = self.get_column()
-                        ));
+                        state = 150; // z4
                     }
                 }
                 // z3
@@ -894,6 +984,10 @@ impl<'a> Lexer<'a> {
                     if let Some(ch) = c {
                         if ch == 'h' {
                             state = 53; // g3
+                        } else if ch == 'o' {
+                            state = 101; // c7
+                        } else if ch == 'a' {
+                            state = 132; // g7
                         } else if !self.is_letter_digit_or_underscore(ch) {
                             self.trata_lookahead();
                             state = 8; // u3
@@ -967,6 +1061,8 @@ impl<'a> Lexer<'a> {
                             state = 58; // c2
                         } else if ch == 'h' {
                             state = 59; // // j3
+                        } else if ch == 'r' {
+                            state = 123; // j7
                         } else if !self.is_letter_digit_or_underscore(ch) {
                             self.trata_lookahead();
                             state = 8; // u3
@@ -1290,6 +1386,8 @@ impl<'a> Lexer<'a> {
                     if let Some(ch) = c {
                         if ch == 'o' {
                             state = 81; // n3
+                        } else if ch == 'e' {
+                            state = 136; // n5
                         } else if !self.is_letter_digit_or_underscore(ch) {
                             self.trata_lookahead();
                             state = 8; // u3
@@ -1329,6 +1427,8 @@ impl<'a> Lexer<'a> {
                             state = 84; // h3
                         } else if ch == 'o' {
                             state = 85; // o3
+                        } else if ch == 'a' {
+                            state = 127; // o6
                         } else if !self.is_letter_digit_or_underscore(ch) {
                             self.trata_lookahead();
                             state = 8; // u3
@@ -1464,6 +1564,974 @@ impl<'a> Lexer<'a> {
                         column: self.get_column(),
                     });
                 }
+                // a2
+                94 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'e' {
+                            state = 95; // a3
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // a3
+                95 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 't' {
+                            state = 96; // a4
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // a4
+                96 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'u' {
+                            state = 97; // a5
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // a5
+                97 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'r' {
+                            state = 98; // a6
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // a6
+                98 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'n' {
+                            state = 99; // a7
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // a7
+                99 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 100; // a8
+                    }
+                }
+                // a8
+                100 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Return,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // c7
+                101 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'n' {
+                            state = 102; // c8
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // c8
+                102 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 's' {
+                            state = 103; // c9
+                        } else if ch == 't' {
+                            state = 112; // c12
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // c9
+                103 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 't' {
+                            state = 104; // c10
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // c10
+                104 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 105; // c11
+                    }
+                }
+                // c11
+                105 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Const,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // l3
+                106 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'r' {
+                            state = 107; // l4
+                        } else if ch == 'o' {
+                            state = 119; // l9
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // l4
+                107 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'e' {
+                            state = 108; // l5
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // l5
+                108 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'a' {
+                            state = 109; // l6
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // l6
+                109 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'k' {
+                            state = 110; // l7
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // l7
+                110 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 111; // l8
+                    }
+                }
+                // l8
+                111 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Break,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // c12
+                112 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'i' {
+                            state = 113; // c13
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // c13
+                113 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'n' {
+                            state = 114; // c14
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // c14
+                114 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'u' {
+                            state = 115; // c15
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // c15
+                115 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'e' {
+                            state = 116; // c16
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // c16
+                116 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 117; // c17
+                    }
+                }
+                // c17
+                117 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Continue,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // l9
+                119 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'o' {
+                            state = 120; // l10
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // l10
+                120 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'l' {
+                            state = 121; // l11
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // l11
+                121 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 122; // l12
+                    }
+                }
+                // l12
+                122 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Bool,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // j7
+                123 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'u' {
+                            state = 124; // j8
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // j8
+                124 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'e' {
+                            state = 125; // j9
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // j9
+                125 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 126; // j10
+                    }
+                }
+                // j10
+                126 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::True,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // o6
+                127 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'l' {
+                            state = 128; // o7
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // o7
+                128 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 's' {
+                            state = 129; // o8
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // o8
+                129 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'e' {
+                            state = 130; // o9
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // o9
+                130 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 131; // o10
+                    }
+                }
+                // o10
+                131 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::False,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // g7
+                132 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 's' {
+                            state = 133; // g8
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // g8
+                133 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'e' {
+                            state = 134; // g9
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // g9
+                134 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 135; // g10
+                    }
+                }
+                // g10
+                135 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Case,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // n5
+                136 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'f' {
+                            state = 137; // n6
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // n6
+                137 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'a' {
+                            state = 138; // n7
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // n7
+                138 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'u' {
+                            state = 139; // n8
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // n8
+                139 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'l' {
+                            state = 140; // n9
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // n9
+                140 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 't' {
+                            state = 141; // n10
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // n10
+                141 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 142; // n11
+                    }
+                }
+                // n11
+                142 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Default,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // s4
+                143 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'w' {
+                            state = 144; // s5
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // s5
+                144 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'i' {
+                            state = 145; // s6
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // s6
+                145 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 't' {
+                            state = 146; // s7
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // s7
+                146 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'c' {
+                            state = 147; // s8
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // s8
+                147 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == 'h' {
+                            state = 148; // s9
+                        } else if !self.is_letter_digit_or_underscore(ch) {
+                            self.trata_lookahead();
+                            state = 8; // u3
+                        } else {
+                            state = 7; // u2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 8; // u3
+                    }
+                }
+                // s9
+                148 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c
+                        && self.is_letter_digit_or_underscore(ch)
+                    {
+                        state = 7; // u2
+                    } else {
+                        self.trata_lookahead();
+                        state = 149; // s10
+                    }
+                }
+                // s10
+                149 => {
+                    return Ok(Token::Keyword {
+                        kind: KeywordKind::Switch,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // z4
+                150 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::Colon,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // s11
+                151 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == '=' {
+                            state = 152; // s12
+                        } else {
+                            self.trata_lookahead();
+                            state = 12; // s3
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 12; // s3
+                    }
+                }
+                // s12
+                152 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::DivAssign,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // r4
+                153 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == '=' {
+                            state = 154; // r5
+                        } else if ch == '-' {
+                            state = 155; // r6
+                        } else {
+                            self.trata_lookahead();
+                            state = 13; // r3
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 13; // r3
+                    }
+                }
+                // r5
+                154 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::SubAssign,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // r6
+                155 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::Decrement,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // r7
+                156 => {
+                    c = self.prox_char();
+                    if let Some(ch) = c {
+                        if ch == '=' {
+                            state = 157; // r8
+                        } else if ch == '+' {
+                            state = 158; // r9
+                        } else {
+                            self.trata_lookahead();
+                            state = 14; // r2
+                        }
+                    } else {
+                        self.trata_lookahead();
+                        state = 14; // r2
+                    }
+                }
+                // r8
+                157 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::AddAssign,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // r9
+                158 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::Increment,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // t4
+                159 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::MultAssign,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // q12
+                160 => {
+                    return Ok(Token::Operator {
+                        kind: OperatorKind::Shl,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // q13
+                161 => {
+                    return Ok(Token::Operator {
+                        kind: OperatorKind::Shr,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // t5
+                162 => {
+                    return Ok(Token::Operator {
+                        kind: OperatorKind::Mod,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // t6
+                163 => {
+                    return Ok(Token::Operator {
+                        kind: OperatorKind::BitAnd,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // t7
+                164 => {
+                    return Ok(Token::Operator {
+                        kind: OperatorKind::BitOr,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // t8
+                165 => {
+                    return Ok(Token::Operator {
+                        kind: OperatorKind::BitXor,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // t9
+                166 => {
+                    return Ok(Token::Operator {
+                        kind: OperatorKind::BitNot,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
+                // y4
+                118 => {
+                    return Ok(Token::Punctuation {
+                        kind: PunctuationKind::Dot,
+                        line: self.line,
+                        column: self.get_column(),
+                    });
+                }
                 _ => {
                     break;
                 }
@@ -1,16 +1,124 @@
 use std::fmt::{Display, Error, Formatter};
 
+// Posição no código-fonte (linha e coluna começam em 1)
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+// Intervalo do código-fonte, de `start` (inclusivo) até `end` (exclusivo)
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "linha {}, coluna {}", self.line, self.column)
+    }
+}
+
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
     Int,
     Float,
     Char,
+    Bool,
     Void,
+    // Vetor de tamanho fixo, como em `int v[10]`
+    Array {
+        elem: Box<Type>,
+        size: u32,
+    },
+    // Registro declarado com `tipo Nome [ ... ]`, já com o layout calculado
+    Record {
+        name: String,
+        fields: Vec<Field>,
+        size: u32,
+        align: u32,
+    },
+}
+
+// Campo de um registro e seu deslocamento, em bytes, a partir do início
+#[allow(unused)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Field {
+    pub name: String,
+    pub kind: Type,
+    pub offset: u32,
+}
+
+#[allow(unused)]
+impl Type {
+    // Tamanho em bytes: char e bool 1, int 4, float 8; vetores e registros somam os
+    // elementos, com o preenchimento exigido pelo alinhamento. `None` se o tamanho
+    // não cabe em 32 bits.
+    pub fn size(&self) -> Option<u32> {
+        match self {
+            Type::Int => Some(4),
+            Type::Float => Some(8),
+            Type::Char | Type::Bool => Some(1),
+            Type::Void => Some(0),
+            Type::Array { elem, size } => elem.size()?.checked_mul(*size),
+            Type::Record { size, .. } => Some(*size),
+        }
+    }
+
+    pub fn align(&self) -> u32 {
+        match self {
+            Type::Int => 4,
+            Type::Float => 8,
+            Type::Char | Type::Bool | Type::Void => 1,
+            Type::Array { elem, .. } => elem.align(),
+            Type::Record { align, .. } => *align,
+        }
+    }
+
+    // Monta o registro como em C: cada campo começa no primeiro múltiplo do seu
+    // alinhamento e o tamanho final é múltiplo do maior alinhamento. `None` se
+    // algum deslocamento ou o tamanho não cabe em 32 bits.
+    pub fn record(name: String, fields: Vec<(String, Type)>) -> Option<Type> {
+        let mut offset: u32 = 0;
+        let mut align: u32 = 1;
+        let fields = fields
+            .into_iter()
+            .map(|(name, kind)| {
+                offset = offset.checked_next_multiple_of(kind.align())?;
+                align = align.max(kind.align());
+                let field = Field { name, offset, kind };
+                offset = offset.checked_add(field.kind.size()?)?;
+                Some(field)
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Type::Record {
+            name,
+            fields,
+            size: offset.checked_next_multiple_of(align)?,
+            align,
+        })
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        match self {
+            Type::Record { fields, .. } => fields.iter().find(|field| field.name == name),
+            _ => None,
+        }
+    }
 }
 
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NumberKind {
     Integer,
     Float,
@@ -18,6 +126,7 @@ pub enum NumberKind {
 
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RelopKind {
     GT,
     LT,
@@ -29,28 +138,113 @@ pub enum RelopKind {
 
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OperatorKind {
     Sum,
     Sub,
     Mult,
     Div,
     Exp,
+    Mod,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    Shl,
+    Shr,
     Paresq,
     Pardir,
 }
 
+#[allow(unused)]
+impl OperatorKind {
+    // Operadores binários que só aceitam inteiros: `& | ^ << >>`
+    pub fn is_bitwise(&self) -> bool {
+        matches!(
+            self,
+            OperatorKind::BitAnd
+                | OperatorKind::BitOr
+                | OperatorKind::BitXor
+                | OperatorKind::Shl
+                | OperatorKind::Shr
+        )
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let value = match self {
+            Type::Int => "int",
+            Type::Float => "float",
+            Type::Char => "char",
+            Type::Bool => "bool",
+            Type::Void => "void",
+            Type::Array { elem, size } => return write!(f, "{}[{}]", elem, size),
+            Type::Record { name, .. } => return write!(f, "tipo {}", name),
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl Display for RelopKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let value = match self {
+            RelopKind::GT => ">",
+            RelopKind::LT => "<",
+            RelopKind::EQ => "==",
+            RelopKind::NE => "!=",
+            RelopKind::LE => "<=",
+            RelopKind::GE => ">=",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl Display for OperatorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let value = match self {
+            OperatorKind::Sum => "+",
+            OperatorKind::Sub => "-",
+            OperatorKind::Mult => "*",
+            OperatorKind::Div => "/",
+            OperatorKind::Exp => "**",
+            OperatorKind::Mod => "%",
+            OperatorKind::BitAnd => "&",
+            OperatorKind::BitOr => "|",
+            OperatorKind::BitXor => "^",
+            OperatorKind::BitNot => "~",
+            OperatorKind::Shl => "<<",
+            OperatorKind::Shr => ">>",
+            OperatorKind::Paresq => "(",
+            OperatorKind::Pardir => ")",
+        };
+        write!(f, "{}", value)
+    }
+}
+
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PunctuationKind {
     Assigment,
     Comma,
     EndExp,
     BeginBlock,
     EndBlock,
+    Dot,
+    Colon,
+    // Atribuições compostas e incremento/decremento
+    AddAssign,
+    SubAssign,
+    MultAssign,
+    DivAssign,
+    Increment,
+    Decrement,
 }
 
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KeywordKind {
     If,
     Int,
@@ -65,6 +259,68 @@ pub enum KeywordKind {
     Do,
     Main,
     Void,
+    Return,
+    Const,
+    Break,
+    Continue,
+    Bool,
+    True,
+    False,
+    Switch,
+    Case,
+    Default,
+}
+
+impl Display for PunctuationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let value = match self {
+            PunctuationKind::Assigment => ":=",
+            PunctuationKind::Comma => ",",
+            PunctuationKind::EndExp => ";",
+            PunctuationKind::BeginBlock => "[",
+            PunctuationKind::EndBlock => "]",
+            PunctuationKind::Dot => ".",
+            PunctuationKind::Colon => ":",
+            PunctuationKind::AddAssign => "+=",
+            PunctuationKind::SubAssign => "-=",
+            PunctuationKind::MultAssign => "*=",
+            PunctuationKind::DivAssign => "/=",
+            PunctuationKind::Increment => "++",
+            PunctuationKind::Decrement => "--",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl Display for KeywordKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let value = match self {
+            KeywordKind::If => "if",
+            KeywordKind::Int => "int",
+            KeywordKind::Float => "float",
+            KeywordKind::Char => "char",
+            KeywordKind::Then => "then",
+            KeywordKind::Type => "tipo",
+            KeywordKind::Else => "else",
+            KeywordKind::Elsif => "elsif",
+            KeywordKind::While => "while",
+            KeywordKind::For => "for",
+            KeywordKind::Do => "do",
+            KeywordKind::Main => "main",
+            KeywordKind::Void => "void",
+            KeywordKind::Return => "return",
+            KeywordKind::Const => "const",
+            KeywordKind::Break => "break",
+            KeywordKind::Continue => "continue",
+            KeywordKind::Bool => "bool",
+            KeywordKind::True => "true",
+            KeywordKind::False => "false",
+            KeywordKind::Switch => "switch",
+            KeywordKind::Case => "case",
+            KeywordKind::Default => "default",
+        };
+        write!(f, "{}", value)
+    }
 }
 
 #[allow(unused)]
@@ -81,6 +337,7 @@ pub enum LexerError {
 
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Token {
     Id {
         value: String,
@@ -121,6 +378,57 @@ pub enum Token {
     Eof,
 }
 
+#[allow(unused)]
+impl Token {
+    // Posição inicial do token; `Eof` não guarda posição
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Self::Id { line, column, .. }
+            | Self::Char { line, column, .. }
+            | Self::Number { line, column, .. }
+            | Self::Relop { line, column, .. }
+            | Self::Operator { line, column, .. }
+            | Self::Punctuation { line, column, .. }
+            | Self::Keyword { line, column, .. } => Some(Position {
+                line: *line,
+                column: *column,
+            }),
+            Self::Eof => None,
+        }
+    }
+
+    // O mesmo token em outra posição; `Eof` continua sem posição
+    pub fn with_position(mut self, position: Position) -> Self {
+        match &mut self {
+            Self::Id { line, column, .. }
+            | Self::Char { line, column, .. }
+            | Self::Number { line, column, .. }
+            | Self::Relop { line, column, .. }
+            | Self::Operator { line, column, .. }
+            | Self::Punctuation { line, column, .. }
+            | Self::Keyword { line, column, .. } => {
+                *line = position.line;
+                *column = position.column;
+            }
+            Self::Eof => {}
+        }
+        self
+    }
+
+    // Texto do token como aparece no código-fonte; vazio para `Eof`
+    pub fn lexeme(&self) -> String {
+        match self {
+            Self::Id { value, .. } | Self::Number { value, .. } => value.clone(),
+            Self::Char { value, .. } => format!("'{}'", value),
+            Self::Relop { kind, .. } => kind.to_string(),
+            Self::Operator { kind, .. } => kind.to_string(),
+            Self::Punctuation { kind, .. } => kind.to_string(),
+            Self::Keyword { kind, .. } => kind.to_string(),
+            Self::Eof => String::new(),
+        }
+    }
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         match self {
@@ -159,73 +467,31 @@ impl Display for Token {
                 );
             }
             Self::Relop { kind, line, column } => {
-                let value = match kind {
-                    RelopKind::GT => ">".to_string(),
-                    RelopKind::LT => "<".to_string(),
-                    RelopKind::EQ => "==".to_string(),
-                    RelopKind::NE => "!=".to_string(),
-                    RelopKind::LE => "<=".to_string(),
-                    RelopKind::GE => ">=".to_string(),
-                };
-
                 let _ = write!(
                     f,
                     "<Relop, value='{}', kind={:?}, line={}, column={}>",
-                    value, kind, line, column,
+                    kind, kind, line, column,
                 );
             }
             Self::Operator { kind, line, column } => {
-                let value = match kind {
-                    OperatorKind::Sum => "+".to_string(),
-                    OperatorKind::Sub => "-".to_string(),
-                    OperatorKind::Mult => "*".to_string(),
-                    OperatorKind::Div => "/".to_string(),
-                    OperatorKind::Exp => "**".to_string(),
-                    OperatorKind::Paresq => "(".to_string(),
-                    OperatorKind::Pardir => ")".to_string(),
-                };
-
                 let _ = write!(
                     f,
                     "<Operator, value='{}', kind={:?}, line={}, column={}>",
-                    value, kind, line, column,
+                    kind, kind, line, column,
                 );
             }
             Self::Punctuation { kind, line, column } => {
-                let value = match kind {
-                    PunctuationKind::Assigment => ":=".to_string(),
-                    PunctuationKind::Comma => ",".to_string(),
-                    PunctuationKind::EndExp => ";".to_string(),
-                    PunctuationKind::BeginBlock => "[".to_string(),
-                    PunctuationKind::EndBlock => "]".to_string(),
-                };
-
                 let _ = write!(
                     f,
                     "<Punctuation, value='{}', kind={:?}, line={}, column={}>",
-                    value, kind, line, column,
+                    kind, kind, line, column,
                 );
             }
             Self::Keyword { kind, line, column } => {
-                let value = match kind {
-                    KeywordKind::If => "if".to_string(),
-                    KeywordKind::Int => "int".to_string(),
-                    KeywordKind::Float => "float".to_string(),
-                    KeywordKind::Char => "char".to_string(),
-                    KeywordKind::Then => "then".to_string(),
-                    KeywordKind::Type => "tipo".to_string(),
-                    KeywordKind::Else => "else".to_string(),
-                    KeywordKind::Elsif => "elsif".to_string(),
-                    KeywordKind::While => "while".to_string(),
-                    KeywordKind::For => "for".to_string(),
-                    KeywordKind::Do => "do".to_string(),
-                    KeywordKind::Main => "main".to_string(),
-                    KeywordKind::Void => "void".to_string(),
-                };
                 let _ = write!(
                     f,
                     "<Keyword, value='{}', kind={:?}, line={}, column={}>",
-                    value, kind, line, column,
+                    kind, kind, line, column,
                 );
             }
             Self::Eof {} => {
@@ -253,6 +519,13 @@ pub enum TokenType {
     MultOperator,
     DivOperator,
     ExpOperator,
+    ModOperator,
+    BitAndOperator,
+    BitOrOperator,
+    BitXorOperator,
+    BitNotOperator,
+    ShlOperator,
+    ShrOperator,
     LParenOperator,
     RParenOperator,
     AssignPunctuation,
@@ -260,6 +533,14 @@ pub enum TokenType {
     SemiColonPunctuation,
     BeginBlockPunctuation,
     EndBlockPunctuation,
+    DotPunctuation,
+    ColonPunctuation,
+    AddAssignPunctuation,
+    SubAssignPunctuation,
+    MultAssignPunctuation,
+    DivAssignPunctuation,
+    IncrementPunctuation,
+    DecrementPunctuation,
     IfKeyword,
     IntKeyword,
     FloatKeyword,
@@ -273,9 +554,155 @@ pub enum TokenType {
     DoKeyword,
     MainKeyword,
     VoidKeyword,
+    ReturnKeyword,
+    ConstKeyword,
+    BreakKeyword,
+    ContinueKeyword,
+    BoolKeyword,
+    TrueKeyword,
+    FalseKeyword,
+    SwitchKeyword,
+    CaseKeyword,
+    DefaultKeyword,
     Eof,
 }
 
+#[allow(unused)]
+impl TokenType {
+    // Todos os terminais, na ordem da declaração
+    pub const ALL: [TokenType; 60] = [
+        TokenType::Id,
+        TokenType::Number,
+        TokenType::CharValue,
+        TokenType::GTOperator,
+        TokenType::LTOperator,
+        TokenType::LEOperator,
+        TokenType::NEOperator,
+        TokenType::EQOperator,
+        TokenType::GEOperator,
+        TokenType::PlusOperator,
+        TokenType::MinusOperator,
+        TokenType::MultOperator,
+        TokenType::DivOperator,
+        TokenType::ExpOperator,
+        TokenType::ModOperator,
+        TokenType::BitAndOperator,
+        TokenType::BitOrOperator,
+        TokenType::BitXorOperator,
+        TokenType::BitNotOperator,
+        TokenType::ShlOperator,
+        TokenType::ShrOperator,
+        TokenType::LParenOperator,
+        TokenType::RParenOperator,
+        TokenType::AssignPunctuation,
+        TokenType::CommaPunctuation,
+        TokenType::SemiColonPunctuation,
+        TokenType::BeginBlockPunctuation,
+        TokenType::EndBlockPunctuation,
+        TokenType::DotPunctuation,
+        TokenType::ColonPunctuation,
+        TokenType::AddAssignPunctuation,
+        TokenType::SubAssignPunctuation,
+        TokenType::MultAssignPunctuation,
+        TokenType::DivAssignPunctuation,
+        TokenType::IncrementPunctuation,
+        TokenType::DecrementPunctuation,
+        TokenType::IfKeyword,
+        TokenType::IntKeyword,
+        TokenType::FloatKeyword,
+        TokenType::CharKeyword,
+        TokenType::ThenKeyword,
+        TokenType::TypeKeyword,
+        TokenType::ElseKeyword,
+        TokenType::ElsifKeyword,
+        TokenType::WhileKeyword,
+        TokenType::ForKeyword,
+        TokenType::DoKeyword,
+        TokenType::MainKeyword,
+        TokenType::VoidKeyword,
+        TokenType::ReturnKeyword,
+        TokenType::ConstKeyword,
+        TokenType::BreakKeyword,
+        TokenType::ContinueKeyword,
+        TokenType::BoolKeyword,
+        TokenType::TrueKeyword,
+        TokenType::FalseKeyword,
+        TokenType::SwitchKeyword,
+        TokenType::CaseKeyword,
+        TokenType::DefaultKeyword,
+        TokenType::Eof,
+    ];
+}
+
+// Terminal na notação da gramática
+impl Display for TokenType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let value = match self {
+            TokenType::Id => "id",
+            TokenType::Number => "num",
+            TokenType::CharValue => "caractere",
+            TokenType::GTOperator => ">",
+            TokenType::LTOperator => "<",
+            TokenType::LEOperator => "<=",
+            TokenType::NEOperator => "!=",
+            TokenType::EQOperator => "==",
+            TokenType::GEOperator => ">=",
+            TokenType::PlusOperator => "+",
+            TokenType::MinusOperator => "-",
+            TokenType::MultOperator => "*",
+            TokenType::DivOperator => "/",
+            TokenType::ExpOperator => "**",
+            TokenType::ModOperator => "%",
+            TokenType::BitAndOperator => "&",
+            TokenType::BitOrOperator => "|",
+            TokenType::BitXorOperator => "^",
+            TokenType::BitNotOperator => "~",
+            TokenType::ShlOperator => "<<",
+            TokenType::ShrOperator => ">>",
+            TokenType::LParenOperator => "(",
+            TokenType::RParenOperator => ")",
+            TokenType::AssignPunctuation => ":=",
+            TokenType::CommaPunctuation => ",",
+            TokenType::SemiColonPunctuation => ";",
+            TokenType::BeginBlockPunctuation => "[",
+            TokenType::EndBlockPunctuation => "]",
+            TokenType::DotPunctuation => ".",
+            TokenType::ColonPunctuation => ":",
+            TokenType::AddAssignPunctuation => "+=",
+            TokenType::SubAssignPunctuation => "-=",
+            TokenType::MultAssignPunctuation => "*=",
+            TokenType::DivAssignPunctuation => "/=",
+            TokenType::IncrementPunctuation => "++",
+            TokenType::DecrementPunctuation => "--",
+            TokenType::IfKeyword => "if",
+            TokenType::IntKeyword => "int",
+            TokenType::FloatKeyword => "float",
+            TokenType::CharKeyword => "char",
+            TokenType::ThenKeyword => "then",
+            TokenType::TypeKeyword => "tipo",
+            TokenType::ElseKeyword => "else",
+            TokenType::ElsifKeyword => "elsif",
+            TokenType::WhileKeyword => "while",
+            TokenType::ForKeyword => "for",
+            TokenType::DoKeyword => "do",
+            TokenType::MainKeyword => "main",
+            TokenType::VoidKeyword => "void",
+            TokenType::ReturnKeyword => "return",
+            TokenType::ConstKeyword => "const",
+            TokenType::BreakKeyword => "break",
+            TokenType::ContinueKeyword => "continue",
+            TokenType::BoolKeyword => "bool",
+            TokenType::TrueKeyword => "true",
+            TokenType::FalseKeyword => "false",
+            TokenType::SwitchKeyword => "switch",
+            TokenType::CaseKeyword => "case",
+            TokenType::DefaultKeyword => "default",
+            TokenType::Eof => "$",
+        };
+        write!(f, "{}", value)
+    }
+}
+
 impl From<Token> for TokenType {
     fn from(token: Token) -> Self {
         match token {
@@ -333,6 +760,34 @@ impl From<Token> for TokenType {
                 kind: OperatorKind::Exp,
                 ..
             } => Self::ExpOperator,
+            Token::Operator {
+                kind: OperatorKind::Mod,
+                ..
+            } => Self::ModOperator,
+            Token::Operator {
+                kind: OperatorKind::BitAnd,
+                ..
+            } => Self::BitAndOperator,
+            Token::Operator {
+                kind: OperatorKind::BitOr,
+                ..
+            } => Self::BitOrOperator,
+            Token::Operator {
+                kind: OperatorKind::BitXor,
+                ..
+            } => Self::BitXorOperator,
+            Token::Operator {
+                kind: OperatorKind::BitNot,
+                ..
+            } => Self::BitNotOperator,
+            Token::Operator {
+                kind: OperatorKind::Shl,
+                ..
+            } => Self::ShlOperator,
+            Token::Operator {
+                kind: OperatorKind::Shr,
+                ..
+            } => Self::ShrOperator,
             Token::Operator {
                 kind: OperatorKind::Paresq,
                 ..
@@ -361,6 +816,38 @@ impl From<Token> for TokenType {
                 kind: PunctuationKind::EndBlock,
                 ..
             } => Self::EndBlockPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::Dot,
+                ..
+            } => Self::DotPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::Colon,
+                ..
+            } => Self::ColonPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::AddAssign,
+                ..
+            } => Self::AddAssignPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::SubAssign,
+                ..
+            } => Self::SubAssignPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::MultAssign,
+                ..
+            } => Self::MultAssignPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::DivAssign,
+                ..
+            } => Self::DivAssignPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::Increment,
+                ..
+            } => Self::IncrementPunctuation,
+            Token::Punctuation {
+                kind: PunctuationKind::Decrement,
+                ..
+            } => Self::DecrementPunctuation,
             Token::Keyword {
                 kind: KeywordKind::If,
                 ..
@@ -413,6 +900,46 @@ impl From<Token> for TokenType {
                 kind: KeywordKind::Void,
                 ..
             } => Self::VoidKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Return,
+                ..
+            } => Self::ReturnKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Const,
+                ..
+            } => Self::ConstKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Break,
+                ..
+            } => Self::BreakKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Continue,
+                ..
+            } => Self::ContinueKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Bool,
+                ..
+            } => Self::BoolKeyword,
+            Token::Keyword {
+                kind: KeywordKind::True,
+                ..
+            } => Self::TrueKeyword,
+            Token::Keyword {
+                kind: KeywordKind::False,
+                ..
+            } => Self::FalseKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Switch,
+                ..
+            } => Self::SwitchKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Case,
+                ..
+            } => Self::CaseKeyword,
+            Token::Keyword {
+                kind: KeywordKind::Default,
+                ..
+            } => Self::DefaultKeyword,
             Token::Eof => Self::Eof,
         }
     }
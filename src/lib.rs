@@ -1,15 +1,44 @@
+pub mod formatter;
+pub mod interpreter;
 pub mod lexer;
+pub mod preprocessor;
+pub mod semantic;
+pub mod source;
 pub mod syntactic;
 
+pub use formatter::*;
+pub use interpreter::*;
 pub use lexer::*;
+pub use preprocessor::*;
+pub use semantic::*;
+pub use source::*;
 pub use syntactic::*;
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+    };
 
     use super::*;
 
+    // Todo nó abaixo de `node` traz o seu intervalo
+    fn fully_spanned(node: &AstNode) -> bool {
+        node.children()
+            .iter()
+            .all(|child| child.span.is_some() && fully_spanned(child))
+    }
+
+    // Intervalos de todos os nós, em pré-ordem
+    fn spans_of(node: &Spanned<AstNode>) -> Vec<Option<Span>> {
+        let mut spans = vec![node.span];
+        for child in node.children() {
+            spans.extend(spans_of(child));
+        }
+        spans
+    }
+
     fn create_instance<'a>(
         contents: String,
         start_symbol: &str,
@@ -19,6 +48,31 @@ mod tests {
         Parser::new(contents, parse_table, symbol_table)
     }
 
+    fn span(start: (usize, usize), end: (usize, usize)) -> Span {
+        Span {
+            start: Position {
+                line: start.0,
+                column: start.1,
+            },
+            end: Position {
+                line: end.0,
+                column: end.1,
+            },
+        }
+    }
+
+    // Parser com uma única produção `S`, usado para levar ações semânticas ao caminho de erro
+    fn create_action_instance<'a>(
+        contents: String,
+        lookahead: TokenType,
+        production: Vec<Symbol>,
+        symbol_table: &'a mut SymbolTable,
+    ) -> Parser<'a> {
+        let mut parse_table = ParseTable::new("S");
+        parse_table.set_entry("S", lookahead, production);
+        Parser::new(contents, parse_table, symbol_table)
+    }
+
     #[test]
     fn test_unary_ast_tree() {
         let mut symbol_table = HashMap::new();
@@ -29,7 +83,8 @@ mod tests {
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
 
         let expected_ast = AstNode::UnaryOp {
-            expr: Box::new(AstNode::Number { value: "6".into() }),
+            op: OperatorKind::Sub,
+            expr: AstNode::Number { value: "6".into() }.into(),
         };
 
         assert_eq!(
@@ -45,11 +100,13 @@ mod tests {
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
 
         let expected_ast = AstNode::UnaryOp {
-            expr: Box::new(AstNode::BinaryOp {
+            op: OperatorKind::Sub,
+            expr: AstNode::BinaryOp {
                 op: OperatorKind::Mult,
-                left: Box::new(AstNode::Identifier { name: "x".into() }),
-                right: Box::new(AstNode::Number { value: "2".into() }),
-            }),
+                left: AstNode::Identifier { name: "x".into() }.into(),
+                right: AstNode::Number { value: "2".into() }.into(),
+            }
+            .into(),
         };
 
         assert_eq!(
@@ -59,6 +116,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_char_operands() {
+        // Um caractere é um fator como os outros: depois dele ainda podem vir
+        // `*`, `/` e `**`, e não só `+` e `-`
+        for (source, expected) in [
+            ("'a' * 2 ", "(* 'a' 2)"),
+            ("2 * 'a' ** 2 + 1 ", "(+ (* 2 (** 'a' 2)) 1)"),
+            ("'a' ** 2 * x ", "(* (** 'a' 2) x)"),
+            ("-'a' / 2 ", "(/ (- 'a') 2)"),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let result = create_instance(source.into(), "E", &mut symbol_table).parse();
+            assert_eq!(
+                result.map(|ast| ast.to_sexp()),
+                Ok(expected.to_string()),
+                "{}",
+                source
+            );
+        }
+
+        // Nenhuma célula da tabela é definida duas vezes: a gramática é LL(1)
+        assert!(
+            ParseTable::create_parse_table("inicio")
+                .conflicts()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_assigment_ast_tree() {
         let mut symbol_table = HashMap::new();
@@ -70,15 +155,18 @@ mod tests {
 
         let expected_ast = AstNode::Assignment {
             id: "x".to_string(),
-            expr: Box::new(AstNode::BinaryOp {
+            expr: AstNode::BinaryOp {
                 op: OperatorKind::Sub, // A raiz da expressão é a subtração
-                left: Box::new(AstNode::Number {
+                left: AstNode::Number {
                     value: "5".to_string(),
-                }),
-                right: Box::new(AstNode::Number {
+                }
+                .into(),
+                right: AstNode::Number {
                     value: "6".to_string(),
-                }),
-            }),
+                }
+                .into(),
+            }
+            .into(),
         };
 
         assert_eq!(
@@ -100,30 +188,40 @@ mod tests {
         let result = parser.parse();
 
         let expected_ast = AstNode::If {
-            cond: Box::new(AstNode::BinaryComp {
+            cond: AstNode::BinaryComp {
                 relop: RelopKind::GT,
-                left: Box::new(AstNode::Identifier {
+                left: AstNode::Identifier {
                     name: "x".to_string(),
-                }),
-                right: Box::new(AstNode::Identifier {
+                }
+                .into(),
+                right: AstNode::Identifier {
                     name: "y".to_string(),
-                }),
-            }),
-            then_block: Box::new(AstNode::Block {
+                }
+                .into(),
+            }
+            .into(),
+            then_block: AstNode::Block {
                 decls: vec![],
-                stmts: vec![AstNode::Assignment {
-                    id: "y".to_string(),
-                    expr: Box::new(AstNode::BinaryOp {
-                        op: OperatorKind::Mult,
-                        left: Box::new(AstNode::Identifier {
-                            name: "y".to_string(),
-                        }),
-                        right: Box::new(AstNode::Number {
-                            value: "2".to_string(),
-                        }),
-                    }),
-                }],
-            }),
+                stmts: vec![
+                    AstNode::Assignment {
+                        id: "y".to_string(),
+                        expr: AstNode::BinaryOp {
+                            op: OperatorKind::Mult,
+                            left: AstNode::Identifier {
+                                name: "y".to_string(),
+                            }
+                            .into(),
+                            right: AstNode::Number {
+                                value: "2".to_string(),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    }
+                    .into(),
+                ],
+            }
+            .into(),
             else_block: None,
         };
 
@@ -148,45 +246,64 @@ mod tests {
         let result = parser.parse();
 
         let expected_ast = AstNode::If {
-            cond: Box::new(AstNode::BinaryComp {
+            cond: AstNode::BinaryComp {
                 relop: RelopKind::GT,
-                left: Box::new(AstNode::Identifier {
+                left: AstNode::Identifier {
                     name: "x".to_string(),
-                }),
-                right: Box::new(AstNode::Identifier {
+                }
+                .into(),
+                right: AstNode::Identifier {
                     name: "y".to_string(),
-                }),
-            }),
-            then_block: Box::new(AstNode::Block {
-                decls: vec![],
-                stmts: vec![AstNode::Assignment {
-                    id: "y".to_string(),
-                    expr: Box::new(AstNode::BinaryOp {
-                        op: OperatorKind::Mult,
-                        left: Box::new(AstNode::Identifier {
-                            name: "y".to_string(),
-                        }),
-                        right: Box::new(AstNode::Number {
-                            value: "2".to_string(),
-                        }),
-                    }),
-                }],
-            }),
-            else_block: Some(Box::new(AstNode::Block {
+                }
+                .into(),
+            }
+            .into(),
+            then_block: AstNode::Block {
                 decls: vec![],
-                stmts: vec![AstNode::Assignment {
-                    id: "x".to_string(),
-                    expr: Box::new(AstNode::BinaryOp {
-                        op: OperatorKind::Exp,
-                        left: Box::new(AstNode::Number {
-                            value: "2".to_string(),
-                        }),
-                        right: Box::new(AstNode::Number {
-                            value: "4".to_string(),
-                        }),
-                    }),
-                }],
-            })),
+                stmts: vec![
+                    AstNode::Assignment {
+                        id: "y".to_string(),
+                        expr: AstNode::BinaryOp {
+                            op: OperatorKind::Mult,
+                            left: AstNode::Identifier {
+                                name: "y".to_string(),
+                            }
+                            .into(),
+                            right: AstNode::Number {
+                                value: "2".to_string(),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    }
+                    .into(),
+                ],
+            }
+            .into(),
+            else_block: Some(
+                AstNode::Block {
+                    decls: vec![],
+                    stmts: vec![
+                        AstNode::Assignment {
+                            id: "x".to_string(),
+                            expr: AstNode::BinaryOp {
+                                op: OperatorKind::Exp,
+                                left: AstNode::Number {
+                                    value: "2".to_string(),
+                                }
+                                .into(),
+                                right: AstNode::Number {
+                                    value: "4".to_string(),
+                                }
+                                .into(),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                }
+                .into(),
+            ),
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
@@ -207,12 +324,14 @@ mod tests {
 
         let expected_tree = AstNode::BinaryComp {
             relop: RelopKind::GT,
-            left: Box::new(AstNode::Identifier {
+            left: AstNode::Identifier {
                 name: "x".to_string(),
-            }),
-            right: Box::new(AstNode::Identifier {
+            }
+            .into(),
+            right: AstNode::Identifier {
                 name: "y".to_string(),
-            }),
+            }
+            .into(),
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
@@ -232,22 +351,33 @@ mod tests {
         let result = parser.parse();
 
         let expected_ast = AstNode::Block {
-            decls: vec![AstNode::VarDecl {
-                kind: Type::Int,
-                names: vec!["a".to_string(), "b".to_string()],
-            }],
-            stmts: vec![AstNode::Assignment {
-                id: "y".to_string(),
-                expr: Box::new(AstNode::BinaryOp {
-                    op: OperatorKind::Mult,
-                    left: Box::new(AstNode::Identifier {
-                        name: "y".to_string(),
-                    }),
-                    right: Box::new(AstNode::Number {
-                        value: "2".to_string(),
-                    }),
-                }),
-            }],
+            decls: vec![
+                AstNode::VarDecl {
+                    kind: Type::Int,
+                    names: vec!["a".to_string(), "b".to_string()],
+                    inits: vec![None, None],
+                    constant: false,
+                }
+                .into(),
+            ],
+            stmts: vec![
+                AstNode::Assignment {
+                    id: "y".to_string(),
+                    expr: AstNode::BinaryOp {
+                        op: OperatorKind::Mult,
+                        left: AstNode::Identifier {
+                            name: "y".to_string(),
+                        }
+                        .into(),
+                        right: AstNode::Number {
+                            value: "2".to_string(),
+                        }
+                        .into(),
+                    }
+                    .into(),
+                }
+                .into(),
+            ],
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
@@ -268,72 +398,114 @@ mod tests {
             &mut symbol_table,
         );
 
-        let result = parser.parse();
+        let result = parser.parse_spanned();
 
         let expected_ast = AstNode::If {
-            cond: Box::new(AstNode::BinaryComp {
+            cond: AstNode::BinaryComp {
                 relop: RelopKind::GT,
-                left: Box::new(AstNode::Identifier {
+                left: AstNode::Identifier {
                     name: "x".to_string(),
-                }),
-                right: Box::new(AstNode::Identifier {
+                }
+                .into(),
+                right: AstNode::Identifier {
                     name: "y".to_string(),
-                }),
-            }),
-            then_block: Box::new(AstNode::Block {
+                }
+                .into(),
+            }
+            .into(),
+            then_block: AstNode::Block {
                 decls: vec![],
-                stmts: vec![AstNode::Assignment {
-                    id: "y".to_string(),
-                    expr: Box::new(AstNode::BinaryOp {
-                        op: OperatorKind::Mult,
-                        left: Box::new(AstNode::Identifier {
-                            name: "y".to_string(),
-                        }),
-                        right: Box::new(AstNode::Number {
-                            value: "2".to_string(),
-                        }),
-                    }),
-                }],
-            }),
-            else_block: Some(Box::new(AstNode::If {
-                cond: Box::new(AstNode::BinaryComp {
-                    relop: RelopKind::EQ,
-                    left: Box::new(AstNode::Identifier {
-                        name: "c".to_string(),
-                    }),
-                    right: Box::new(AstNode::Literal { value: 'a' }),
-                }),
-                then_block: Box::new(AstNode::Block {
-                    decls: vec![],
-                    stmts: vec![AstNode::Assignment {
-                        id: "x".to_string(),
-                        expr: Box::new(AstNode::BinaryOp {
-                            op: OperatorKind::Exp,
-                            left: Box::new(AstNode::Number {
+                stmts: vec![
+                    AstNode::Assignment {
+                        id: "y".to_string(),
+                        expr: AstNode::BinaryOp {
+                            op: OperatorKind::Mult,
+                            left: AstNode::Identifier {
+                                name: "y".to_string(),
+                            }
+                            .into(),
+                            right: AstNode::Number {
                                 value: "2".to_string(),
-                            }),
-                            right: Box::new(AstNode::Number {
-                                value: "4".to_string(),
-                            }),
-                        }),
-                    }],
-                }),
-                else_block: Some(Box::new(AstNode::Block {
-                    decls: vec![AstNode::VarDecl {
-                        kind: Type::Int,
-                        names: vec!["a".to_string(), "b".to_string()],
-                    }],
-                    stmts: vec![],
-                })),
-            })),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    }
+                    .into(),
+                ],
+            }
+            .into(),
+            else_block: Some(
+                AstNode::If {
+                    cond: AstNode::BinaryComp {
+                        relop: RelopKind::EQ,
+                        left: AstNode::Identifier {
+                            name: "c".to_string(),
+                        }
+                        .into(),
+                        right: AstNode::Literal { value: 'a' }.into(),
+                    }
+                    .into(),
+                    then_block: AstNode::Block {
+                        decls: vec![],
+                        stmts: vec![
+                            AstNode::Assignment {
+                                id: "x".to_string(),
+                                expr: AstNode::BinaryOp {
+                                    op: OperatorKind::Exp,
+                                    left: AstNode::Number {
+                                        value: "2".to_string(),
+                                    }
+                                    .into(),
+                                    right: AstNode::Number {
+                                        value: "4".to_string(),
+                                    }
+                                    .into(),
+                                }
+                                .into(),
+                            }
+                            .into(),
+                        ],
+                    }
+                    .into(),
+                    else_block: Some(
+                        AstNode::Block {
+                            decls: vec![
+                                AstNode::VarDecl {
+                                    kind: Type::Int,
+                                    names: vec!["a".to_string(), "b".to_string()],
+                                    inits: vec![None, None],
+                                    constant: false,
+                                }
+                                .into(),
+                            ],
+                            stmts: vec![],
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+            ),
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
 
+        let ast = result.unwrap();
+
+        assert_eq!(ast.node, expected_ast, "A arvore gerada não corresponde");
+
+        // if ... elsif ... else ...
+        assert_eq!(ast.span, Some(span((1, 1), (1, 69))));
+        // Condição do primeiro if
+        assert_eq!(ast.children()[0].span, Some(span((1, 4), (1, 7))));
+        // O elsif começa na própria palavra-chave
+        let elsif = ast.children()[2];
+        assert_eq!(elsif.span, Some(span((1, 25), (1, 69))));
+        assert_eq!(elsif.children()[1].span, Some(span((1, 42), (1, 54))));
+        // int a, b;
         assert_eq!(
-            result.unwrap(),
-            expected_ast,
-            "A arvore gerada não corresponde"
+            elsif.children()[2].children()[0].span,
+            Some(span((1, 59), (1, 68)))
         );
     }
 
@@ -349,27 +521,34 @@ mod tests {
         let result = parser.parse();
 
         let expected_ast = AstNode::While {
-            cond: Box::new(AstNode::BinaryComp {
+            cond: AstNode::BinaryComp {
                 relop: RelopKind::GT,
-                left: Box::new(AstNode::Identifier {
+                left: AstNode::Identifier {
                     name: "x".to_string(),
-                }),
-                right: Box::new(AstNode::Identifier {
+                }
+                .into(),
+                right: AstNode::Identifier {
                     name: "y".to_string(),
-                }),
-            }),
-            body: Box::new(AstNode::Assignment {
+                }
+                .into(),
+            }
+            .into(),
+            body: AstNode::Assignment {
                 id: "y".to_string(),
-                expr: Box::new(AstNode::BinaryOp {
+                expr: AstNode::BinaryOp {
                     op: OperatorKind::Mult,
-                    left: Box::new(AstNode::Identifier {
+                    left: AstNode::Identifier {
                         name: "y".to_string(),
-                    }),
-                    right: Box::new(AstNode::Number {
+                    }
+                    .into(),
+                    right: AstNode::Number {
                         value: "2".to_string(),
-                    }),
-                }),
-            }),
+                    }
+                    .into(),
+                }
+                .into(),
+            }
+            .into(),
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
@@ -393,27 +572,34 @@ mod tests {
         let result = parser.parse();
 
         let expected_ast = AstNode::DoWhile {
-            body: Box::new(AstNode::Assignment {
+            body: AstNode::Assignment {
                 id: "y".to_string(),
-                expr: Box::new(AstNode::BinaryOp {
+                expr: AstNode::BinaryOp {
                     op: OperatorKind::Mult,
-                    left: Box::new(AstNode::Identifier {
+                    left: AstNode::Identifier {
                         name: "y".to_string(),
-                    }),
-                    right: Box::new(AstNode::Number {
+                    }
+                    .into(),
+                    right: AstNode::Number {
                         value: "2".to_string(),
-                    }),
-                }),
-            }),
-            cond: Box::new(AstNode::BinaryComp {
+                    }
+                    .into(),
+                }
+                .into(),
+            }
+            .into(),
+            cond: AstNode::BinaryComp {
                 relop: RelopKind::GT,
-                left: Box::new(AstNode::Identifier {
+                left: AstNode::Identifier {
                     name: "x".to_string(),
-                }),
-                right: Box::new(AstNode::Identifier {
+                }
+                .into(),
+                right: AstNode::Identifier {
                     name: "y".to_string(),
-                }),
-            }),
+                }
+                .into(),
+            }
+            .into(),
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
@@ -438,29 +624,42 @@ mod tests {
 
         let expected_ast = AstNode::For {
             id: "x".to_string(),
-            start: "0".parse().unwrap(),
-            end: "10".parse().unwrap(),
-            step: Box::new(AstNode::BinaryOp {
+            start: AstNode::Number {
+                value: "0".to_string(),
+            }
+            .into(),
+            end: AstNode::Number {
+                value: "10".to_string(),
+            }
+            .into(),
+            step: AstNode::BinaryOp {
                 op: OperatorKind::Sum,
-                left: Box::new(AstNode::Identifier {
+                left: AstNode::Identifier {
                     name: "x".to_string(),
-                }),
-                right: Box::new(AstNode::Number {
+                }
+                .into(),
+                right: AstNode::Number {
                     value: "1".to_string(),
-                }),
-            }),
-            body: Box::new(AstNode::Assignment {
+                }
+                .into(),
+            }
+            .into(),
+            body: AstNode::Assignment {
                 id: "y".to_string(),
-                expr: Box::new(AstNode::BinaryOp {
+                expr: AstNode::BinaryOp {
                     op: OperatorKind::Div,
-                    left: Box::new(AstNode::Identifier {
+                    left: AstNode::Identifier {
                         name: "y".to_string(),
-                    }),
-                    right: Box::new(AstNode::Number {
+                    }
+                    .into(),
+                    right: AstNode::Number {
                         value: "2".to_string(),
-                    }),
-                }),
-            }),
+                    }
+                    .into(),
+                }
+                .into(),
+            }
+            .into(),
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
@@ -511,135 +710,3051 @@ mod tests {
             &mut symbol_table,
         );
 
-        let result = parser.parse();
+        let result = parser.parse_spanned();
 
         let expected_ast = AstNode::Program {
+            records: vec![],
+            globals: vec![],
+            functions: vec![],
             kind: Type::Int,
-            body: Box::new(AstNode::Block {
+            body: AstNode::Block {
                 decls: vec![
                     AstNode::VarDecl {
                         kind: Type::Char,
                         names: vec!["c".to_string()],
-                    },
+                        inits: vec![None],
+                        constant: false,
+                    }
+                    .into(),
                     AstNode::VarDecl {
                         kind: Type::Int,
                         names: vec!["x".to_string()],
-                    },
+                        inits: vec![None],
+                        constant: false,
+                    }
+                    .into(),
                     AstNode::VarDecl {
                         kind: Type::Float,
                         names: vec!["y".to_string()],
-                    },
+                        inits: vec![None],
+                        constant: false,
+                    }
+                    .into(),
                 ],
                 stmts: vec![
                     AstNode::Assignment {
                         id: "c".to_string(),
-                        expr: Box::new(AstNode::Literal { value: 'a' }),
-                    },
+                        expr: AstNode::Literal { value: 'a' }.into(),
+                    }
+                    .into(),
                     AstNode::Assignment {
                         id: "x".to_string(),
-                        expr: Box::new(AstNode::Number {
+                        expr: AstNode::Number {
                             value: "67".to_string(),
-                        }),
-                    },
+                        }
+                        .into(),
+                    }
+                    .into(),
                     AstNode::Assignment {
                         id: "y".to_string(),
-                        expr: Box::new(AstNode::Number {
+                        expr: AstNode::Number {
                             value: "54.90E-22".to_string(),
-                        }),
-                    },
+                        }
+                        .into(),
+                    }
+                    .into(),
                     AstNode::If {
-                        cond: Box::new(AstNode::BinaryComp {
+                        cond: AstNode::BinaryComp {
                             relop: RelopKind::GT,
-                            left: Box::new(AstNode::Identifier {
+                            left: AstNode::Identifier {
                                 name: "x".to_string(),
-                            }),
-                            right: Box::new(AstNode::Identifier {
+                            }
+                            .into(),
+                            right: AstNode::Identifier {
                                 name: "y".to_string(),
-                            }),
-                        }),
-                        then_block: Box::new(AstNode::Block {
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        then_block: AstNode::Block {
                             decls: vec![],
-                            stmts: vec![AstNode::Assignment {
-                                id: "y".to_string(),
-                                expr: Box::new(AstNode::BinaryOp {
-                                    op: OperatorKind::Mult,
-                                    left: Box::new(AstNode::Identifier {
-                                        name: "y".to_string(),
-                                    }),
-                                    right: Box::new(AstNode::Number {
-                                        value: "2".to_string(),
-                                    }),
-                                }),
-                            }],
-                        }),
-                        else_block: Some(Box::new(AstNode::If {
-                            cond: Box::new(AstNode::BinaryComp {
-                                relop: RelopKind::EQ,
-                                left: Box::new(AstNode::Identifier {
-                                    name: "c".to_string(),
-                                }),
-                                right: Box::new(AstNode::Literal { value: 'a' }),
-                            }),
-                            then_block: Box::new(AstNode::Block {
-                                decls: vec![],
-                                stmts: vec![AstNode::Assignment {
-                                    id: "x".to_string(),
-                                    expr: Box::new(AstNode::BinaryOp {
-                                        op: OperatorKind::Sum,
-                                        left: Box::new(AstNode::Identifier {
-                                            name: "x".to_string(),
-                                        }),
-                                        right: Box::new(AstNode::Number {
+                            stmts: vec![
+                                AstNode::Assignment {
+                                    id: "y".to_string(),
+                                    expr: AstNode::BinaryOp {
+                                        op: OperatorKind::Mult,
+                                        left: AstNode::Identifier {
+                                            name: "y".to_string(),
+                                        }
+                                        .into(),
+                                        right: AstNode::Number {
                                             value: "2".to_string(),
-                                        }),
-                                    }),
-                                }],
-                            }),
-                            else_block: Some(Box::new(AstNode::Block {
-                                decls: vec![],
-                                stmts: vec![AstNode::Assignment {
-                                    id: "c".to_string(),
-                                    expr: Box::new(AstNode::Literal { value: 'b' }),
-                                }],
-                            })),
-                        })),
-                    },
+                                        }
+                                        .into(),
+                                    }
+                                    .into(),
+                                }
+                                .into(),
+                            ],
+                        }
+                        .into(),
+                        else_block: Some(
+                            AstNode::If {
+                                cond: AstNode::BinaryComp {
+                                    relop: RelopKind::EQ,
+                                    left: AstNode::Identifier {
+                                        name: "c".to_string(),
+                                    }
+                                    .into(),
+                                    right: AstNode::Literal { value: 'a' }.into(),
+                                }
+                                .into(),
+                                then_block: AstNode::Block {
+                                    decls: vec![],
+                                    stmts: vec![
+                                        AstNode::Assignment {
+                                            id: "x".to_string(),
+                                            expr: AstNode::BinaryOp {
+                                                op: OperatorKind::Sum,
+                                                left: AstNode::Identifier {
+                                                    name: "x".to_string(),
+                                                }
+                                                .into(),
+                                                right: AstNode::Number {
+                                                    value: "2".to_string(),
+                                                }
+                                                .into(),
+                                            }
+                                            .into(),
+                                        }
+                                        .into(),
+                                    ],
+                                }
+                                .into(),
+                                else_block: Some(
+                                    AstNode::Block {
+                                        decls: vec![],
+                                        stmts: vec![
+                                            AstNode::Assignment {
+                                                id: "c".to_string(),
+                                                expr: AstNode::Literal { value: 'b' }.into(),
+                                            }
+                                            .into(),
+                                        ],
+                                    }
+                                    .into(),
+                                ),
+                            }
+                            .into(),
+                        ),
+                    }
+                    .into(),
                     AstNode::DoWhile {
-                        body: Box::new(AstNode::Block {
+                        body: AstNode::Block {
                             decls: vec![],
-                            stmts: vec![AstNode::Assignment {
-                                id: "x".to_string(),
-                                expr: Box::new(AstNode::BinaryOp {
-                                    op: OperatorKind::Sum,
-                                    left: Box::new(AstNode::Identifier {
-                                        name: "x".to_string(),
-                                    }),
-                                    right: Box::new(AstNode::Number {
-                                        value: "1".to_string(),
-                                    }),
-                                }),
-                            }],
-                        }),
-                        cond: Box::new(AstNode::BinaryComp {
+                            stmts: vec![
+                                AstNode::Assignment {
+                                    id: "x".to_string(),
+                                    expr: AstNode::BinaryOp {
+                                        op: OperatorKind::Sum,
+                                        left: AstNode::Identifier {
+                                            name: "x".to_string(),
+                                        }
+                                        .into(),
+                                        right: AstNode::Number {
+                                            value: "1".to_string(),
+                                        }
+                                        .into(),
+                                    }
+                                    .into(),
+                                }
+                                .into(),
+                            ],
+                        }
+                        .into(),
+                        cond: AstNode::BinaryComp {
                             relop: RelopKind::LT,
-                            left: Box::new(AstNode::Identifier {
+                            left: AstNode::Identifier {
                                 name: "x".to_string(),
-                            }),
-                            right: Box::new(AstNode::Number {
+                            }
+                            .into(),
+                            right: AstNode::Number {
                                 value: "100".to_string(),
-                            }),
-                        }),
-                    },
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    }
+                    .into(),
                 ],
-            }),
+            }
+            .into(),
         };
 
         assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
 
+        let ast = result.unwrap();
+
+        assert_eq!(ast.node, expected_ast, "A arvore gerada não corresponde");
+
+        assert_eq!(ast.span, Some(span((1, 1), (30, 14))));
+
+        let body = ast.children()[0];
+        assert_eq!(body.span, Some(span((1, 12), (30, 14))));
+        // char c;
+        assert_eq!(body.children()[0].span, Some(span((2, 17), (2, 24))));
+        // y := 54.90E-22;
+        assert_eq!(body.children()[5].span, Some(span((8, 17), (8, 32))));
         assert_eq!(
-            result.unwrap(),
-            expected_ast,
-            "A arvore gerada não corresponde"
+            body.children()[5].children()[0].span,
+            Some(span((8, 22), (8, 31)))
+        );
+
+        let if_node = body.children()[6];
+        assert_eq!(if_node.span, Some(span((10, 17), (16, 18))));
+        assert_eq!(if_node.children()[0].span, Some(span((10, 20), (10, 23))));
+        assert_eq!(if_node.children()[2].span, Some(span((12, 18), (16, 18))));
+
+        // O comentário entre os comandos não faz parte de nenhum nó
+        let do_while = body.children()[7];
+        assert_eq!(do_while.span, Some(span((26, 17), (28, 34))));
+        assert_eq!(do_while.children()[1].span, Some(span((28, 25), (28, 32))));
+    }
+
+    #[test]
+    fn test_create_decl_internal_error() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_action_instance(
+            "x y ".into(),
+            TokenType::Id,
+            vec![
+                Symbol::Terminal(TokenType::Id),
+                Symbol::Terminal(TokenType::Id),
+                Symbol::Action(ActionKind::CreateDecl),
+            ],
+            &mut symbol_table,
+        );
+
+        match parser.parse() {
+            Err(ParserError::Internal {
+                action,
+                span,
+                ast_stack,
+                ..
+            }) => {
+                assert_eq!(action, ActionKind::CreateDecl);
+                assert_eq!(span.start, Position { line: 1, column: 5 });
+                assert_eq!(
+                    ast_stack,
+                    vec![
+                        AstNode::Identifier {
+                            name: "x".to_string()
+                        }
+                        .into(),
+                        AstNode::Identifier {
+                            name: "y".to_string()
+                        }
+                        .into(),
+                    ]
+                );
+            }
+            other => panic!("Era esperado um erro interno, recebido {:?}", other),
+        }
+
+        let mut parser = create_action_instance(
+            "int 5 ".into(),
+            TokenType::IntKeyword,
+            vec![
+                Symbol::Terminal(TokenType::IntKeyword),
+                Symbol::Terminal(TokenType::Number),
+                Symbol::Action(ActionKind::MakeList),
+                Symbol::Action(ActionKind::AppendList),
+                Symbol::Action(ActionKind::CreateDecl),
+            ],
+            &mut symbol_table,
+        );
+
+        match parser.parse() {
+            Err(ParserError::Internal {
+                action, ast_stack, ..
+            }) => {
+                assert_eq!(action, ActionKind::CreateDecl);
+                assert_eq!(
+                    ast_stack,
+                    vec![
+                        AstNode::TypeWrapper(Type::Int).into(),
+                        AstNode::List(vec![
+                            AstNode::Number {
+                                value: "5".to_string()
+                            }
+                            .into()
+                        ])
+                        .into(),
+                    ]
+                );
+            }
+            other => panic!("Era esperado um erro interno, recebido {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_create_cond_internal_error() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_action_instance(
+            "x y z ".into(),
+            TokenType::Id,
+            vec![
+                Symbol::Terminal(TokenType::Id),
+                Symbol::Terminal(TokenType::Id),
+                Symbol::Terminal(TokenType::Id),
+                Symbol::Action(ActionKind::CreateCond),
+            ],
+            &mut symbol_table,
+        );
+
+        match parser.parse() {
+            Err(ParserError::Internal {
+                action,
+                span,
+                ast_stack,
+                ..
+            }) => {
+                assert_eq!(action, ActionKind::CreateCond);
+                assert_eq!(span.start, Position { line: 1, column: 7 });
+                assert_eq!(ast_stack.len(), 3, "A pilha deveria ser preservada");
+                assert_eq!(
+                    ast_stack[1].node,
+                    AstNode::Identifier {
+                        name: "y".to_string()
+                    }
+                );
+            }
+            other => panic!("Era esperado um erro interno, recebido {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_create_for_internal_error() {
+        let mut symbol_table = HashMap::new();
+
+        // Nodos insuficientes
+        let mut parser = create_action_instance(
+            "x ".into(),
+            TokenType::Id,
+            vec![
+                Symbol::Terminal(TokenType::Id),
+                Symbol::Action(ActionKind::CreateFor),
+            ],
+            &mut symbol_table,
         );
+
+        match parser.parse() {
+            Err(ParserError::Internal {
+                action, ast_stack, ..
+            }) => {
+                assert_eq!(action, ActionKind::CreateFor);
+                assert_eq!(
+                    ast_stack,
+                    vec![
+                        AstNode::Identifier {
+                            name: "x".to_string()
+                        }
+                        .into()
+                    ]
+                );
+            }
+            other => panic!("Era esperado um erro interno, recebido {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_actions_with_empty_ast_stack() {
+        let actions = vec![
+            ActionKind::Math(OperatorKind::Sum),
+            ActionKind::CreateUnaryOp(OperatorKind::Sub),
+            ActionKind::Assign,
+            ActionKind::CreateBlock,
+            ActionKind::CreateIf,
+            ActionKind::CreateIfElse,
+            ActionKind::CreateWhile,
+            ActionKind::CreateDoWhile,
+            ActionKind::CreateFor,
+            ActionKind::AppendList,
+            ActionKind::CreateDecl,
+            ActionKind::MarkConst,
+            ActionKind::CreateCond,
+            ActionKind::CreateProgram,
+        ];
+
+        for kind in actions {
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_action_instance(
+                " ".into(),
+                TokenType::Eof,
+                vec![Symbol::Action(kind.clone())],
+                &mut symbol_table,
+            );
+
+            match parser.parse() {
+                Err(ParserError::Internal {
+                    action, ast_stack, ..
+                }) => {
+                    assert_eq!(action, kind);
+                    assert!(ast_stack.is_empty());
+                }
+                other => panic!(
+                    "Era esperado um erro interno para {:?}, recebido {:?}",
+                    kind, other
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_print_ast_snapshot() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(
+            include_str!("../data.txt").into(),
+            "inicio",
+            &mut symbol_table,
+        );
+
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
+
+        let expected = "\
+Program: int
+└── Block
+    ├── VarDecl: char c
+    ├── VarDecl: int x
+    ├── VarDecl: float y
+    ├── Assignment: c
+    │   └── Literal: 'a'
+    ├── Assignment: x
+    │   └── Number: 67
+    ├── Assignment: y
+    │   └── Number: 54.90E-22
+    ├── If
+    │   ├── cond: BinaryComp: >
+    │   │   ├── Identifier: x
+    │   │   └── Identifier: y
+    │   ├── then: Block
+    │   │   └── Assignment: y
+    │   │       └── BinaryOp: *
+    │   │           ├── Identifier: y
+    │   │           └── Number: 2
+    │   ├── elsif
+    │   │   ├── cond: BinaryComp: ==
+    │   │   │   ├── Identifier: c
+    │   │   │   └── Literal: 'a'
+    │   │   └── then: Block
+    │   │       └── Assignment: x
+    │   │           └── BinaryOp: +
+    │   │               ├── Identifier: x
+    │   │               └── Number: 2
+    │   └── else: Block
+    │       └── Assignment: c
+    │           └── Literal: 'b'
+    └── DoWhile
+        ├── body: Block
+        │   └── Assignment: x
+        │       └── BinaryOp: +
+        │           ├── Identifier: x
+        │           └── Number: 1
+        └── cond: BinaryComp: <
+            ├── Identifier: x
+            └── Number: 100";
+
+        let ast = result.unwrap();
+
+        assert_eq!(ast.format_ast(PrintStyle::Tree), expected);
+        assert_eq!(
+            ast.format_ast(PrintStyle::SExpr),
+            "(program int (block (var char c) (var int x) (var float y) (:= c 'a') (:= x 67) \
+             (:= y 54.90E-22) (if (> x y) (block (:= y (* y 2))) (if (== c 'a') \
+             (block (:= x (+ x 2))) (block (:= c 'b')))) (do (block (:= x (+ x 1))) (< x 100))))"
+        );
+    }
+
+    #[test]
+    fn test_print_ast_loops() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(
+            "for(i;0;10;i+1) [int a, b; while(a<b)do a := -a;] ".into(),
+            "cmd_for",
+            &mut symbol_table,
+        );
+
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
+
+        let expected = "\
+For: i
+├── start: Number: 0
+├── end: Number: 10
+├── step: BinaryOp: +
+│   ├── Identifier: i
+│   └── Number: 1
+└── body: Block
+    ├── VarDecl: int a, b
+    └── While
+        ├── cond: BinaryComp: <
+        │   ├── Identifier: a
+        │   └── Identifier: b
+        └── body: Assignment: a
+            └── UnaryOp: -
+                └── Identifier: a";
+
+        let ast = result.unwrap();
+
+        assert_eq!(ast.format_ast(PrintStyle::Tree), expected);
+        assert_eq!(
+            ast.format_ast(PrintStyle::SExpr),
+            "(for i 0 10 (+ i 1) (block (var int a b) (while (< a b) (:= a (- a)))))"
+        );
+    }
+
+    fn parse_source(contents: &str, start_symbol: &str) -> AstNode {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(contents.into(), start_symbol, &mut symbol_table);
+
+        let result = parser.parse();
+        assert!(
+            result.is_ok(),
+            "O parser retornou erro: {:?}\n{}",
+            result.err(),
+            contents
+        );
+        result.unwrap()
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let sources = [
+            include_str!("../data.txt"),
+            "void main() [
+                int a, b;
+                float f;
+                for (a; 0; 10; a + 1) [
+                    while (a < b) do a := a + 1;
+                    b := -(a - b) ** 2 * -a;
+                ]
+                if (a == b) then a := 1; else [ b := 2; ]
+                [ char c; c := 'z'; ]
+                do b := b - 1; while (b >= 0);
+            ] ",
+            "int main() [
+                x := (a + b) * -(c - d) ** 2 / (e ** f) ** g - (h - i);
+                y := a - (b - c) - d / (e * f);
+                z := 2 ** (3 ** 4) + - -x;
+                if (x + 1 != (y)) then x := 0;
+                do [ ] while ((a) <= b * c);
+            ] ",
+        ];
+
+        for source in sources {
+            let ast = parse_source(source, "inicio");
+            let formatted = format_program(&ast);
+
+            assert_eq!(
+                parse_source(&formatted, "inicio"),
+                ast,
+                "A árvore do código formatado não corresponde:\n{}",
+                formatted
+            );
+            // Formatar de novo não muda nada
+            assert_eq!(
+                format_program(&parse_source(&formatted, "inicio")),
+                formatted
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_program_snapshot() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(
+            include_str!("../data.txt").into(),
+            "inicio",
+            &mut symbol_table,
+        )
+        .with_comments();
+
+        let result = parser.parse();
+
+        assert!(result.is_ok(), "O parser retornou erro: {:?}", result.err());
+
+        let ast = result.unwrap();
+        let formatted = format_program_with_comments(&ast, parser.comments());
+
+        let expected = "\
+int main() [
+    char c;
+    int x;
+    float y;
+
+    c := 'a';
+    x := 67;
+    y := 54.90E-22;
+    if (x > y) then [
+        y := y * 2;
+    ] elsif (c == 'a') then [
+        x := x + 2;
+    ] else [
+        c := 'b';
+    ]
+    {%comentario%}
+    {%
+      comentario
+      de
+      multiplas
+      linhas
+    %}
+    do [
+        x := x + 1;
+    ] while (x < 100);
+]
+";
+
+        assert_eq!(formatted, expected);
+        // Os comentários continuam sendo ignorados pela análise
+        assert_eq!(parse_source(&formatted, "inicio"), ast);
+    }
+
+    #[test]
+    fn test_format_comments_placement() {
+        let source = "{% antes %} int main() [
+            int x; {% depois da declaração %}
+            x := 1;
+            [ x := 2; {% fim do bloco %} ]
+        ] {% fim %} ";
+
+        let mut symbol_table = HashMap::new();
+        let mut parser =
+            create_instance(source.into(), "inicio", &mut symbol_table).with_comments();
+
+        let ast = parser.parse().unwrap();
+
+        assert_eq!(parser.comments().len(), 4);
+        assert_eq!(parser.comments()[0].text, " antes ");
+        assert_eq!(parser.comments()[0].span, span((1, 1), (1, 12)));
+
+        assert_eq!(
+            format_program_with_comments(&ast, parser.comments()),
+            "\
+{% antes %}
+int main() [
+    int x; {% depois da declaração %}
+
+    x := 1;
+    [
+        x := 2; {% fim do bloco %}
+    ]
+] {% fim %}
+"
+        );
+
+        let format = |source: &str| {
+            let mut symbol_table = HashMap::new();
+            let mut parser =
+                create_instance(source.into(), "inicio", &mut symbol_table).with_comments();
+            let ast = parser.parse().unwrap();
+            format_program_with_comments(&ast, parser.comments())
+        };
+
+        // Comentários entre o `]` e o `else` ou `elsif` ficam fora dos blocos
+        let source = "int main() [
+    int x; {% fim de linha %}
+    if (x > 1) then [ x := 1; ] {% antes do elsif %} elsif (x < 0) then [
+        x := 2;
+    ]
+    {% antes do else %}
+    else [ x := 3; ]
+]
+";
+        let formatted = format(source);
+        assert_eq!(
+            formatted,
+            "\
+int main() [
+    int x; {% fim de linha %}
+
+    if (x > 1) then [
+        x := 1;
+    ] {% antes do elsif %}
+    elsif (x < 0) then [
+        x := 2;
+    ]
+    {% antes do else %}
+    else [
+        x := 3;
+    ]
+]
+"
+        );
+        assert_eq!(format(&formatted), formatted);
+
+        // Um comentário de várias linhas num bloco aninhado é reindentado por
+        // inteiro, com o recuo relativo e as linhas em branco preservados
+        let source = "int main() [
+  int x;
+  while (x < 1) do [
+      {% primeira
+         recuada
+
+      depois da linha em branco
+      %}
+      x := 1;
+  ]
+]
+";
+        let formatted = format(source);
+        assert_eq!(
+            formatted,
+            "\
+int main() [
+    int x;
+
+    while (x < 1) do [
+        {% primeira
+           recuada
+
+        depois da linha em branco
+        %}
+        x := 1;
+    ]
+]
+"
+        );
+        assert_eq!(format(&formatted), formatted);
+    }
+
+    #[test]
+    fn test_visitor_collects_identifiers() {
+        // Coleta os identificadores usados, na ordem em que aparecem
+        #[derive(Default)]
+        struct UsedNames {
+            names: Vec<String>,
+            assignments: usize,
+        }
+
+        impl Visitor for UsedNames {
+            fn visit_identifier(&mut self, name: &str) {
+                self.names.push(name.to_string());
+            }
+
+            fn visit_assignment(&mut self, id: &str, expr: &Spanned<AstNode>) {
+                self.assignments += 1;
+                self.names.push(id.to_string());
+                self.visit_node(expr);
+            }
+        }
+
+        let ast = parse_source(include_str!("../data.txt"), "inicio");
+        let mut visitor = UsedNames::default();
+        visitor.visit_node(&ast);
+
+        assert_eq!(visitor.assignments, 7);
+        assert_eq!(
+            visitor.names,
+            vec![
+                "c", "x", "y", "x", "y", "y", "y", "c", "x", "x", "c", "x", "x", "x"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_folder_rewrites_tree() {
+        // Calcula expressões formadas apenas por números inteiros
+        struct ConstantFolder;
+
+        impl Folder for ConstantFolder {
+            fn fold_node(&mut self, node: AstNode) -> AstNode {
+                let node = walk_fold(self, node);
+
+                let AstNode::BinaryOp { op, left, right } = &node else {
+                    return node;
+                };
+                let (AstNode::Number { value: left }, AstNode::Number { value: right }) =
+                    (&left.node, &right.node)
+                else {
+                    return node;
+                };
+                let (Ok(left), Ok(right)) = (left.parse::<i64>(), right.parse::<i64>()) else {
+                    return node;
+                };
+
+                let value = match op {
+                    OperatorKind::Sum => left + right,
+                    OperatorKind::Sub => left - right,
+                    OperatorKind::Mult => left * right,
+                    _ => return node,
+                };
+
+                AstNode::Number {
+                    value: value.to_string(),
+                }
+            }
+        }
+
+        let ast = parse_source(
+            "int main() [
+                x := 2 * 3 + 4;
+                while (x < 10 - 1) do x := x + (1 + 1);
+            ] ",
+            "inicio",
+        );
+        let expected = parse_source(
+            "int main() [
+                x := 10;
+                while (x < 9) do x := x + 2;
+            ] ",
+            "inicio",
+        );
+
+        assert_eq!(ConstantFolder.fold_node(ast.clone()), expected);
+
+        // Sem sobrescrever nada, o Folder devolve a mesma árvore
+        struct Identity;
+        impl Folder for Identity {}
+
+        assert_eq!(Identity.fold_node(ast.clone()), ast);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ast_json_round_trip() {
+        let ast = parse_source(include_str!("../data.txt"), "inicio");
+        let json = ast.to_json().unwrap();
+
+        assert!(
+            json.starts_with(
+                "{\n  \"Program\": {\n    \"records\": [],\n    \"globals\": [],\n    \"functions\": [],\n    \"kind\": \"Int\","
+            )
+        );
+        assert_eq!(AstNode::from_json(&json).unwrap(), ast);
+
+        let token = Token::Relop {
+            kind: RelopKind::LE,
+            line: 3,
+            column: 7,
+        };
+        let json = serde_json::to_string(&token).unwrap();
+
+        assert_eq!(json, r#"{"Relop":{"kind":"LE","line":3,"column":7}}"#);
+        assert_eq!(serde_json::from_str::<Token>(&json).unwrap(), token);
+    }
+
+    #[test]
+    fn test_ast_to_dot() {
+        let ast = parse_source("int main() [ x := -(a + 1); ] ", "inicio");
+
+        let expected = r#"digraph AST {
+    ordering=out;
+    node [fontname="monospace"];
+    n0 [label="Program: int", shape=box];
+    n1 [label="Block", shape=box];
+    n2 [label="Assignment: x", shape=box];
+    n3 [label="UnaryOp: -", shape=box];
+    n4 [label="BinaryOp: +", shape=box];
+    n5 [label="Identifier: a", shape=box];
+    n4 -> n5;
+    n6 [label="Number: 1", shape=box];
+    n4 -> n6;
+    n3 -> n4;
+    n2 -> n3;
+    n1 -> n2;
+    n0 -> n1;
+}
+"#;
+
+        assert_eq!(ast.to_dot(), expected);
+        assert!(
+            AstNode::Literal { value: '"' }
+                .to_dot()
+                .contains(r#"[label="Literal: '\"'", shape=box]"#)
+        );
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let source = include_str!("../data.txt");
+
+        let mut symbol_table = HashMap::new();
+        let mut parser =
+            create_instance(source.into(), "inicio", &mut symbol_table).with_parse_tree();
+        parser.parse().unwrap();
+
+        let tree = parser.parse_tree().unwrap();
+
+        // As folhas são exatamente os tokens do léxico
+        let mut symbol_table = HashMap::new();
+        let mut lexer = Lexer::new(source.into(), &mut symbol_table);
+        let mut expected = vec![];
+        loop {
+            match lexer.get_next_token().unwrap() {
+                Token::Eof => break,
+                token => expected.push(token),
+            }
+        }
+        assert_eq!(tree.tokens(), expected.iter().collect::<Vec<_>>());
+
+        let ParseTree::NonTerminal { name, children } = &tree else {
+            panic!("Raiz deveria ser um não terminal: {:?}", tree);
+        };
+        assert_eq!(name, "inicio");
+        assert_eq!(children.len(), 2);
+
+        // O restante do programa fica em <global>, que termina criando o programa
+        let Some(ParseTree::NonTerminal { name, children }) = children.last() else {
+            panic!("Esperado o não terminal <global>: {:?}", children);
+        };
+        assert_eq!(name, "global");
+        assert_eq!(children.len(), 5);
+        assert_eq!(
+            children.last(),
+            Some(&ParseTree::Action(ActionKind::CreateProgram))
+        );
+
+        // Sem `with_parse_tree` nada é registrado
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(source.into(), "inicio", &mut symbol_table);
+        parser.parse().unwrap();
+        assert_eq!(parser.parse_tree(), None);
+    }
+
+    #[test]
+    fn test_parse_tree_to_dot() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance("a ;".into(), "E", &mut symbol_table).with_parse_tree();
+        parser.parse().unwrap();
+
+        let expected = r#"digraph ParseTree {
+    ordering=out;
+    node [fontname="monospace"];
+    n0 [label="E", shape=ellipse];
+    n1 [label="O", shape=ellipse];
+    n2 [label="X", shape=ellipse];
+    n3 [label="A", shape=ellipse];
+    n4 [label="D", shape=ellipse];
+    n5 [label="S", shape=ellipse];
+    n6 [label="T", shape=ellipse];
+    n7 [label="F", shape=ellipse];
+    n8 [label="U", shape=ellipse];
+    n9 [label="a", shape=box];
+    n8 -> n9;
+    n10 [label="U'", shape=ellipse];
+    n11 [label="ε", shape=plaintext];
+    n10 -> n11;
+    n8 -> n10;
+    n7 -> n8;
+    n12 [label="F'", shape=ellipse];
+    n13 [label="ε", shape=plaintext];
+    n12 -> n13;
+    n7 -> n12;
+    n6 -> n7;
+    n14 [label="T'", shape=ellipse];
+    n15 [label="ε", shape=plaintext];
+    n14 -> n15;
+    n6 -> n14;
+    n5 -> n6;
+    n16 [label="S'", shape=ellipse];
+    n17 [label="ε", shape=plaintext];
+    n16 -> n17;
+    n5 -> n16;
+    n4 -> n5;
+    n18 [label="D'", shape=ellipse];
+    n19 [label="ε", shape=plaintext];
+    n18 -> n19;
+    n4 -> n18;
+    n3 -> n4;
+    n20 [label="A'", shape=ellipse];
+    n21 [label="ε", shape=plaintext];
+    n20 -> n21;
+    n3 -> n20;
+    n2 -> n3;
+    n22 [label="X'", shape=ellipse];
+    n23 [label="ε", shape=plaintext];
+    n22 -> n23;
+    n2 -> n22;
+    n1 -> n2;
+    n24 [label="O'", shape=ellipse];
+    n25 [label="ε", shape=plaintext];
+    n24 -> n25;
+    n1 -> n24;
+    n0 -> n1;
+    n26 [label="R", shape=ellipse];
+    n27 [label="ε", shape=plaintext];
+    n26 -> n27;
+    n0 -> n26;
+}
+"#;
+
+        assert_eq!(parser.parse_tree().unwrap().to_dot(), expected);
+    }
+
+    #[test]
+    fn test_concrete_syntax_tree_is_lossless() {
+        let sources = [
+            include_str!("../data.txt"),
+            "{% início %}\n\tvoid main ( )[int a ,b;a:=-( a+b )**2;{%x%}]  \n\n{% fim sem fechar",
+            "int main() [\r\n  if (a!=b) then a := 'z'; else b := 1.5E3;\r\n] ",
+        ];
+
+        for source in sources {
+            let mut symbol_table = HashMap::new();
+            let mut parser =
+                create_instance(source.into(), "inicio", &mut symbol_table).with_parse_tree();
+
+            let ast = parser.parse().unwrap();
+            let tree = parser.parse_tree().unwrap();
+
+            assert_eq!(tree.to_source(), source);
+            assert_eq!(tree.to_ast(), Ok(ast));
+        }
+    }
+
+    #[test]
+    fn test_concrete_syntax_tree_trivia() {
+        let source = "int main() [ {% a %}\n  x := 1; {% b %}\n\n  {% c %}\n  y := 2;\n]\n";
+
+        let mut symbol_table = HashMap::new();
+        let mut parser =
+            create_instance(source.into(), "inicio", &mut symbol_table).with_parse_tree();
+        parser.parse().unwrap();
+
+        let tree = parser.parse_tree().unwrap();
+
+        // Coleta (leading, texto, trailing) de cada token
+        fn collect(tree: &ParseTree, out: &mut Vec<(String, String, String)>) {
+            let join = |trivia: &[Trivia]| trivia.iter().map(|t| t.text()).collect::<String>();
+
+            match tree {
+                ParseTree::NonTerminal { children, .. } => {
+                    children.iter().for_each(|child| collect(child, out));
+                }
+                ParseTree::Terminal {
+                    text,
+                    leading_trivia,
+                    trailing_trivia,
+                    ..
+                } => out.push((join(leading_trivia), text.clone(), join(trailing_trivia))),
+                _ => {}
+            }
+        }
+
+        let mut tokens = vec![];
+        collect(&tree, &mut tokens);
+        let tokens: Vec<_> = tokens
+            .iter()
+            .map(|(l, t, r)| (l.as_str(), t.as_str(), r.as_str()))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                ("", "int", " "),
+                ("", "main", ""),
+                ("", "(", ""),
+                ("", ")", " "),
+                ("", "[", " {% a %}\n"),
+                ("  ", "x", " "),
+                ("", ":=", " "),
+                ("", "1", ""),
+                ("", ";", " {% b %}\n"),
+                ("\n  {% c %}\n  ", "y", " "),
+                ("", ":=", " "),
+                ("", "2", ""),
+                ("", ";", "\n"),
+                ("", "]", "\n"),
+            ]
+        );
+
+        // Comentários são trivia do tipo `Comment`
+        let ParseTree::NonTerminal { children, .. } = &tree else {
+            panic!("Raiz deveria ser um não terminal");
+        };
+        let Some(ParseTree::NonTerminal { children, .. }) = children.last() else {
+            panic!("Esperado o não terminal <inicio'>");
+        };
+        let bloco = children.iter().find_map(|child| match child {
+            ParseTree::NonTerminal { name, children } if name == "bloco" => children.first(),
+            _ => None,
+        });
+        let Some(ParseTree::Terminal {
+            trailing_trivia, ..
+        }) = bloco
+        else {
+            panic!("Esperado o token '[': {:?}", bloco);
+        };
+        assert_eq!(
+            trailing_trivia,
+            &vec![
+                Trivia::Whitespace(" ".into()),
+                Trivia::Comment("{% a %}".into()),
+                Trivia::Whitespace("\n".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_trace_table() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance("a * 2 ".into(), "E", &mut symbol_table).with_trace();
+        parser.parse().unwrap();
+
+        let trace = parser.trace().unwrap();
+        let expected = r"                                                 Pilha | Entrada | Ação
+-------------------------------------------------------+---------+--------------------------------
+                                                 $ <E> | a * 2 $ | <E> -> <O> <R>
+                                             $ <R> <O> | a * 2 $ | <O> -> <X> <O'>
+                                        $ <R> <O'> <X> | a * 2 $ | <X> -> <A> <X'>
+                                   $ <R> <O'> <X'> <A> | a * 2 $ | <A> -> <D> <A'>
+                              $ <R> <O'> <X'> <A'> <D> | a * 2 $ | <D> -> <S> <D'>
+                         $ <R> <O'> <X'> <A'> <D'> <S> | a * 2 $ | <S> -> <T> <S'>
+                    $ <R> <O'> <X'> <A'> <D'> <S'> <T> | a * 2 $ | <T> -> <F> <T'>
+               $ <R> <O'> <X'> <A'> <D'> <S'> <T'> <F> | a * 2 $ | <F> -> <U> <F'>
+          $ <R> <O'> <X'> <A'> <D'> <S'> <T'> <F'> <U> | a * 2 $ | <U> -> id <U'>
+      $ <R> <O'> <X'> <A'> <D'> <S'> <T'> <F'> <U'> id | a * 2 $ | casa 'a'
+         $ <R> <O'> <X'> <A'> <D'> <S'> <T'> <F'> <U'> | * 2 $   | <U'> -> ε
+              $ <R> <O'> <X'> <A'> <D'> <S'> <T'> <F'> | * 2 $   | <F'> -> ε
+                   $ <R> <O'> <X'> <A'> <D'> <S'> <T'> | * 2 $   | <T'> -> * <F> {Math(*)} <T'>
+   $ <R> <O'> <X'> <A'> <D'> <S'> <T'> {Math(*)} <F> * | * 2 $   | casa '*'
+     $ <R> <O'> <X'> <A'> <D'> <S'> <T'> {Math(*)} <F> | 2 $     | <F> -> <U> <F'>
+$ <R> <O'> <X'> <A'> <D'> <S'> <T'> {Math(*)} <F'> <U> | 2 $     | <U> -> num
+$ <R> <O'> <X'> <A'> <D'> <S'> <T'> {Math(*)} <F'> num | 2 $     | casa '2'
+    $ <R> <O'> <X'> <A'> <D'> <S'> <T'> {Math(*)} <F'> | $       | <F'> -> ε
+         $ <R> <O'> <X'> <A'> <D'> <S'> <T'> {Math(*)} | $       | executa {Math(*)}  AST: (* a 2)
+                   $ <R> <O'> <X'> <A'> <D'> <S'> <T'> | $       | <T'> -> ε
+                        $ <R> <O'> <X'> <A'> <D'> <S'> | $       | <S'> -> ε
+                             $ <R> <O'> <X'> <A'> <D'> | $       | <D'> -> ε
+                                  $ <R> <O'> <X'> <A'> | $       | <A'> -> ε
+                                       $ <R> <O'> <X'> | $       | <X'> -> ε
+                                            $ <R> <O'> | $       | <O'> -> ε
+                                                 $ <R> | $       | <R> -> ε
+                                                     $ | $       | aceita
+";
+
+        assert_eq!(trace.to_table(), expected);
+        assert_eq!(trace.steps.len(), 27);
+        assert_eq!(
+            trace.steps[18].action,
+            TraceAction::Execute {
+                kind: ActionKind::Math(OperatorKind::Mult),
+                ast_stack: vec![
+                    AstNode::BinaryOp {
+                        op: OperatorKind::Mult,
+                        left: AstNode::Identifier { name: "a".into() }.into(),
+                        right: AstNode::Number { value: "2".into() }.into(),
+                    }
+                    .into()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_trace_csv_and_json() {
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance("a + ) ".into(), "E", &mut symbol_table).with_trace();
+
+        assert!(parser.parse().is_err());
+
+        let trace = parser.trace().unwrap();
+        let csv = trace.to_csv();
+        let json = trace.to_json();
+
+        assert_eq!(
+            csv.lines().take(2).collect::<Vec<_>>(),
+            vec!["pilha,entrada,acao,ast", "$ <E>,a + ) $,<E> -> <O> <R>,"]
+        );
+        // O último passo registra o erro que interrompeu a análise
+        assert_eq!(
+            csv.lines().last(),
+            Some(
+                "$ <R> <O'> <X'> <A'> <D'> <S'> {Math(+)} <T>,) $,\"erro: Erro de sintaxe. Não esperado <Operator, value=')', kind=Pardir, line=1, column=5>, com não terminal \"\"T\"\"\","
+            )
+        );
+        assert_eq!(json.lines().last(), Some("]"));
+        assert_eq!(
+            json.lines().nth(16),
+            Some(
+                "  {\"pilha\": \"$ <R> <O'> <X'> <A'> <D'> <S'> {Math(+)} <T>\", \"entrada\": \") $\", \"acao\": \"erro: Erro de sintaxe. Não esperado <Operator, value=')', kind=Pardir, line=1, column=5>, com não terminal \\\"T\\\"\", \"ast\": \"\"}"
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_table_export() {
+        let mut table = ParseTable::new("S");
+        let id = || Symbol::Terminal(TokenType::Id);
+        table.set_entry(
+            "S",
+            TokenType::Id,
+            vec![id(), Symbol::Action(ActionKind::Assign)],
+        );
+        table.set_entry(
+            "S",
+            TokenType::Id,
+            vec![id(), Symbol::Terminal(TokenType::SemiColonPunctuation)],
+        );
+        table.set_entry("S", TokenType::Eof, vec![Symbol::Epsilon]);
+
+        assert_eq!(table.conflicts(), vec![("S", TokenType::Id)]);
+
+        // Uma coluna por terminal, mais a dos não terminais
+        let csv = table.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("\"M[A, a]\",id,num,caractere,>,"));
+        assert!(lines[0].contains(",:=,\",\",;,"));
+
+        let row: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(row.len(), TokenType::ALL.len() + 1);
+        assert_eq!(row[0], "<S>");
+        assert_eq!(row[1], "CONFLITO: <S> -> id || <S> -> id ;");
+        assert_eq!(row[2], "");
+        assert_eq!(row.last(), Some(&"<S> -> ε"));
+
+        let markdown = table.to_markdown();
+        let row = markdown.lines().nth(2).unwrap();
+        assert!(row.starts_with("| `<S>` | **conflito:** `<S> -> id`<br>`<S> -> id ;` | — | — |"));
+        assert!(row.ends_with("| `<S> -> ε` |"));
+
+        let html = table.to_html();
+        assert!(html.contains(
+            "<tr><th>&lt;S&gt;</th><td class=\"conflict\">&lt;S&gt; -&gt; id<br>&lt;S&gt; -&gt; id ;</td><td class=\"empty\"></td>"
+        ));
+        assert!(html.contains("<th>&lt;=</th>"));
+        assert!(html.ends_with("<td>&lt;S&gt; -&gt; ε</td></tr>\n</table>\n"));
+    }
+
+    #[test]
+    fn test_functions_and_calls() {
+        let source = "int soma(int a, float b) [
+    return a + b;
+]
+
+void nada() [
+    imprime();
+]
+
+int main() [
+    int x;
+
+    x := soma(1, f(2) * 3) + g();
+    if (x > 1) then nada();
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            ast.to_sexp(),
+            "(program (fn int soma ((var int a) (var float b)) (block (return (+ a b)))) \
+             (fn void nada () (block (call imprime))) int \
+             (block (var int x) (:= x (+ (call soma 1 (* (call f 2) 3)) (call g))) (if (> x 1) (call nada))))"
+        );
+        assert_eq!(format_program(&ast), source);
+
+        assert!(fully_spanned(&ast));
+        assert_eq!(
+            ast.children()[1].span.map(|span| span.start),
+            Some(Position { line: 5, column: 1 })
+        );
+
+        // `main` continua obrigatória e fecha o programa
+        let mut symbol_table = HashMap::new();
+        let mut parser =
+            create_instance("int f() [ return 1; ] ".into(), "inicio", &mut symbol_table);
+        assert!(parser.parse().is_err());
+
+        // Funções e parâmetros de uma mesma função não se repetem; o erro aponta
+        // a segunda definição
+        for (source, message, column) in [
+            (
+                "int f() [ return 1; ]\nfloat f() [ return 2; ] int main() [ return f(); ] ",
+                "Função 'f' definida mais de uma vez",
+                1,
+            ),
+            (
+                "int f(int a,\nint a) [ return a; ] int main() [ return f(1, 2); ] ",
+                "Parâmetro 'a' repetido na função 'f'",
+                1,
+            ),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+
+            let error = check_program(&ast).unwrap_err();
+            assert_eq!(error.message, message, "{}", source);
+            assert_eq!(
+                error.span.map(|span| (span.start.line, span.start.column)),
+                Some((2, column)),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_arrays() {
+        let source = "void ordena(int v[10], int n) [
+    int i, j;
+    float t;
+
+    for (i; 0; 9; i + 1) for (j; 0; 9; j + 1) if (v[j] > v[j + 1]) then [
+        t := v[j];
+        v[j] := v[j + 1];
+        v[j + 1] := t;
+    ]
+]
+
+int main() [
+    int v[10];
+
+    v[0] := -v[2 * f(1)] ** 2;
+    ordena(v, 10);
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        let AstNode::Program {
+            functions, body, ..
+        } = &ast
+        else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        let AstNode::Function { params, .. } = &functions[0].node else {
+            panic!("Era esperada uma função: {:?}", functions[0]);
+        };
+        assert_eq!(
+            params[0].node,
+            AstNode::VarDecl {
+                kind: Type::Array {
+                    elem: Box::new(Type::Int),
+                    size: 10
+                },
+                names: vec!["v".into()],
+                inits: vec![None],
+                constant: false,
+            }
+        );
+        assert_eq!(
+            body.to_sexp(),
+            "(block (var int[10] v) (:= (index v 0) (- (** (index v (* 2 (call f 1))) 2))) (call ordena v 10))"
+        );
+        assert_eq!(format_program(&ast), source);
+        assert!(fully_spanned(&ast));
+
+        // O tamanho precisa ser um inteiro
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(
+            "int main() [ int v[1.5]; ] ".into(),
+            "inicio",
+            &mut symbol_table,
+        );
+        assert!(matches!(
+            parser.parse(),
+            Err(ParserError::Internal {
+                action: ActionKind::CreateArrayDecl,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_declaration_initializers() {
+        let source = "int main() [
+    const int n := 10;
+    int x := 5, y := x + 1, z;
+    char c;
+
+    z := n * y;
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        let AstNode::Program { body, .. } = &ast else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        let AstNode::Block { decls, .. } = &body.node else {
+            panic!("Era esperado um bloco: {:?}", body);
+        };
+        assert_eq!(
+            decls[1].node,
+            AstNode::VarDecl {
+                kind: Type::Int,
+                names: vec!["x".into(), "y".into(), "z".into()],
+                inits: vec![
+                    Some(AstNode::Number { value: "5".into() }.into()),
+                    Some(
+                        AstNode::BinaryOp {
+                            op: OperatorKind::Sum,
+                            left: AstNode::Identifier { name: "x".into() }.into(),
+                            right: AstNode::Number { value: "1".into() }.into(),
+                        }
+                        .into()
+                    ),
+                    None,
+                ],
+                constant: false,
+            }
+        );
+        assert_eq!(
+            body.to_sexp(),
+            "(block (const int (:= n 10)) (var int (:= x 5) (:= y (+ x 1)) z) (var char c) (:= z (* n y)))"
+        );
+        assert_eq!(
+            decls[0].format_ast(PrintStyle::Tree),
+            "VarDecl: const int n\n└── n: Number: 10"
+        );
+        assert_eq!(format_program(&ast), source);
+        assert!(fully_spanned(&ast));
+
+        // Constantes precisam de valor inicial, inclusive em listas
+        for source in [
+            "int main() [ const int a := 1, b; ] ",
+            "int main() [ const int v[10]; ] ",
+        ] {
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(source.into(), "inicio", &mut symbol_table);
+            match parser.parse() {
+                Err(ParserError::Syntax { message, .. }) => {
+                    assert!(message.contains("sem valor inicial"), "{}", message)
+                }
+                other => panic!("Era esperado um erro sintático: {:?}", other),
+            }
+        }
+
+        // Redeclarar no mesmo escopo apagaria o `const`, mas um bloco interno
+        // ainda pode esconder o nome
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(
+            "int main() [ const int x := 1;\nint x; x := 2; ] ".into(),
+            "inicio",
+            &mut symbol_table,
+        )
+        .parse()
+        .unwrap();
+        let error = check_program(&ast).unwrap_err();
+        assert_eq!(error.message, "Variável 'x' já declarada neste escopo");
+        assert_eq!(
+            error.span.map(|span| (span.start.line, span.start.column)),
+            Some((2, 1))
+        );
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(
+            "int main() [ const int x := 1; if (x > 0) then [ int x; x := 2; ] ] ".into(),
+            "inicio",
+            &mut symbol_table,
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(check_program(&ast), Ok(()));
+    }
+
+    fn run_program(source: &str) -> Result<Option<Value>, RuntimeError> {
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+        evaluate(&ast)
+    }
+
+    #[test]
+    fn test_for_loop_semantics() {
+        // Número de voltas de cada cabeçalho: intervalo semiaberto [início, fim),
+        // limites avaliados uma vez e passo como o próximo valor da variável
+        for (header, body, expected) in [
+            ("for (i; 0; 10; i + 1)", "", 10),
+            ("for (i; 0; 0; i + 1)", "", 0),
+            ("for (i; 5; 0; i + 1)", "", 0),
+            ("for (i; -3; 3; i + 1)", "", 6),
+            ("for (i; 0; 10; i + 3)", "", 4),
+            ("for (i; 1; 100; i * 2)", "", 7),
+            ("for (i; 0; 2.5; i + 1)", "", 3),
+            ("for (i; n - 7; n; i + 1)", "", 7),
+            ("for (i; 0; n; i + 1)", "n := n + 1;", 7),
+            ("for (i; 0; 10; i + 1)", "i := i + 1;", 5),
+        ] {
+            let source = format!(
+                "int main() [
+    int i, count, n := 7;
+
+    count := 0;
+    {header} [ count := count + 1; {body} ]
+    return count;
+] "
+            );
+
+            assert_eq!(
+                run_program(&source),
+                Ok(Some(Value::Int(expected))),
+                "{} {}",
+                header,
+                body
+            );
+        }
+
+        // A variável termina com o primeiro valor que não passou no teste
+        assert_eq!(
+            run_program("int main() [ int i; for (i; 0; 10; i + 4) [] return i; ] "),
+            Ok(Some(Value::Int(12)))
+        );
+        assert_eq!(
+            run_program("int main() [ const int i := 0; for (i; 0; 1; i + 1) []] "),
+            Err(RuntimeError::ConstantAssignment("i".into()))
+        );
+        assert_eq!(
+            run_program("int main() [ int i; for (i; 0; 1; i) []] "),
+            Err(RuntimeError::IterationLimit)
+        );
+    }
+
+    #[test]
+    fn test_for_expression_bounds() {
+        // Limites e passo são expressões quaisquer, inclusive negativas, reais
+        // ou maiores que u32
+        for (source, expected) in [
+            (
+                "for(x;0;99999999999;x+1) y := y / 2; ",
+                "(for x 0 99999999999 (+ x 1) (:= y (/ y 2)))",
+            ),
+            (
+                "for(x;0.5;10;x+1) y := y / 2; ",
+                "(for x 0.5 10 (+ x 1) (:= y (/ y 2)))",
+            ),
+            (
+                "for(i;-n;f(n) * 2;i - -1) y := i; ",
+                "(for i (- n) (* (call f n) 2) (- i (- 1)) (:= y i))",
+            ),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source.into(), "cmd_for", &mut symbol_table)
+                .parse()
+                .unwrap();
+
+            assert_eq!(ast.to_sexp(), expected);
+            assert!(fully_spanned(&ast));
+        }
+    }
+
+    #[test]
+    fn test_reference_evaluation() {
+        let source = "int fatorial(int n) [
+    if (n <= 1) then return 1;
+    return n * fatorial(n - 1);
+]
+
+void ordena(int v[5], int n) [
+    int i, j, t;
+
+    for (i; 0; n - 1; i + 1) for (j; 0; n - 1 - i; j + 1) if (v[j] > v[j + 1]) then [
+        t := v[j];
+        v[j] := v[j + 1];
+        v[j + 1] := t;
+    ]
+]
+
+int main() [
+    int v[5];
+    int i, soma := 0;
+    float media;
+    char c := 'a';
+
+    v[0] := 4;
+    v[1] := -2;
+    v[2] := fatorial(3);
+    v[3] := 0;
+    v[4] := c - 'a' + 1;
+    ordena(v, 5);
+    i := 0;
+    while (i < 5) do [
+        soma := soma * 10 + v[i] + 2;
+        i := i + 1;
+    ]
+    media := soma / 2.0;
+    do i := i - 1; while (v[i] > 1);
+    if (media > 1000) then return soma + i; else return 0;
+]
+";
+        // v ordenado: -2 0 1 4 6, então soma = 2368, media = 1184 e i para em 2
+        assert_eq!(run_program(source), Ok(Some(Value::Int(2368 + 2))));
+        // Números reais continuam aceitos com ou sem `.`
+        assert_eq!(
+            run_program("float main() [ return 1E3 + 0.5; ] "),
+            Ok(Some(Value::Float(1000.5)))
+        );
+
+        for (source, expected) in [
+            (
+                "int main() [ int x; x := 1 / (x - x); ] ",
+                RuntimeError::UninitializedVariable("x".into()),
+            ),
+            (
+                "int main() [ int x := 1; x := 1 / (x - x); ] ",
+                RuntimeError::DivisionByZero,
+            ),
+            (
+                "int main() [ int v[3]; v[3] := 1; ] ",
+                RuntimeError::IndexOutOfBounds {
+                    name: "v".into(),
+                    index: 3,
+                    size: 3,
+                },
+            ),
+            (
+                "int main() [ const int x := 1; x := 2; ] ",
+                RuntimeError::ConstantAssignment("x".into()),
+            ),
+            (
+                "int main() [ int x; x := f(1); ] ",
+                RuntimeError::UndefinedFunction("f".into()),
+            ),
+            (
+                "int main() [ while (1 < 2) do []] ",
+                RuntimeError::IterationLimit,
+            ),
+            (
+                "int main() [ return 99999999999999999999; ] ",
+                RuntimeError::TypeMismatch("Número inválido: 99999999999999999999".into()),
+            ),
+            (
+                "int f() [ return 1; ] int f() [ return 2; ] int main() [ return f(); ] ",
+                RuntimeError::RedefinedFunction("f".into()),
+            ),
+        ] {
+            assert_eq!(run_program(source), Err(expected), "{}", source);
+        }
+
+        // A recursão para com erro quando a pilha passa do limite, que é medido
+        // em bytes e cabe na pilha de 2MB das threads de teste
+        let conta = |n: i64| {
+            run_program(&format!(
+                "int conta(int n) [ if (n == 0) then return 0; return 1 + conta(n - 1); ] int main() [ return conta({}); ] ",
+                n
+            ))
+        };
+        assert_eq!(conta(10), Ok(Some(Value::Int(10))));
+        assert_eq!(conta(100_000), Err(RuntimeError::StackOverflow));
+        assert_eq!(
+            run_program("int f() [ return f(); ] int main() [ return f(); ] "),
+            Err(RuntimeError::StackOverflow)
+        );
+
+        // Vetores grandes demais falham antes de alocar os elementos
+        for source in [
+            "int main() [ int v[4000000000]; return 0; ] ",
+            "int main() [ char v[4000000000]; return 0; ] ",
+        ] {
+            assert_eq!(
+                run_program(source),
+                Err(RuntimeError::OutOfMemory("v".into())),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_break_and_continue() {
+        let source = "int main() [
+    int i, soma := 0;
+
+    for (i; 0; 100; i + 1) [
+        if (i == 7) then break;
+        if (i / 2 * 2 == i) then continue;
+        soma := soma + i;
+    ]
+    while (1 == 1) do [
+        soma := soma * 10;
+        break;
+    ]
+    do [
+        i := i - 1;
+        if (i > 3) then continue;
+        break;
+    ] while (i > 0);
+    return soma + i;
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        let AstNode::Program { body, .. } = &ast else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        let AstNode::Block { stmts, .. } = &body.node else {
+            panic!("Era esperado um bloco: {:?}", body);
+        };
+        assert_eq!(
+            stmts[1].to_sexp(),
+            "(while (== 1 1) (block (:= soma (* soma 10)) (break)))"
+        );
+        assert_eq!(format_program(&ast), source);
+        assert!(fully_spanned(&ast));
+        // 1 + 3 + 5, vezes 10, mais o i em que o do-while parou
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(93))));
+
+        // Fora do corpo de um laço, inclusive no corpo de uma função
+        for (source, message, column) in [
+            ("int main() [ break; ] ", "'break' fora de um laço", 14),
+            (
+                "int main() [ if (1 < 2) then continue; ] ",
+                "'continue' fora de um laço",
+                30,
+            ),
+            (
+                "int f() [ continue; ] int main() [ while (1 < 2) do f(); ] ",
+                "'continue' fora de um laço",
+                11,
+            ),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(source.into(), "inicio", &mut symbol_table);
+            match parser.parse() {
+                Err(ParserError::Syntax { message: m, span }) => {
+                    assert_eq!(m, message);
+                    assert_eq!(span.start.column, column, "{}", source);
+                }
+                other => panic!("Era esperado um erro sintático: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_types() {
+        let source = "tipo Ponto [
+    char rotulo;
+    int x;
+    int y;
+    float peso;
+]
+
+tipo Linha [
+    tipo Ponto a;
+    tipo Ponto b;
+    int marcas[3];
+]
+
+int comprimento(tipo Linha l) [
+    return l.b.x - l.a.x + l.b.y - l.a.y;
+]
+
+tipo Ponto meio(tipo Ponto p, tipo Ponto q) [
+    tipo Ponto m;
+
+    m.x := (p.x + q.x) / 2;
+    m.y := (p.y + q.y) / 2;
+    return m;
+]
+
+int main() [
+    tipo Linha l;
+    tipo Ponto ps[2];
+    tipo Ponto c;
+
+    l.a.x := 1;
+    l.b.x := 7;
+    l.b.y := 9;
+    l.marcas[1] := 5;
+    ps[1] := l.b;
+    ps[1].x := 100;
+    c := meio(l.a, l.b);
+    return comprimento(l) * 1000 + c.x * 100 + l.marcas[1] * 10 + l.b.x - ps[1].x / 100;
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        let AstNode::Program { records, body, .. } = &ast else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        // Layout como em C: o float alinha em 8 e o tamanho fecha no maior alinhamento
+        assert_eq!(
+            records[0].to_sexp(),
+            "(record Ponto (char rotulo 0) (int x 4) (int y 8) (float peso 16))"
+        );
+        assert_eq!(
+            records[1].to_sexp(),
+            "(record Linha (tipo Ponto a 0) (tipo Ponto b 24) (int[3] marcas 48))"
+        );
+        let AstNode::Record { kind } = &records[1].node else {
+            panic!("Era esperado um registro: {:?}", records[1]);
+        };
+        assert_eq!((kind.size(), kind.align()), (Some(64), 8));
+        assert_eq!(kind.field("b").map(|field| field.offset), Some(24));
+
+        let AstNode::Block { stmts, .. } = &body.node else {
+            panic!("Era esperado um bloco: {:?}", body);
+        };
+        assert_eq!(stmts[3].to_sexp(), "(:= (index (. l marcas) 1) 5)");
+        assert_eq!(stmts[5].to_sexp(), "(:= (. (index ps 1) x) 100)");
+
+        assert_eq!(format_program(&ast), source);
+        assert!(fully_spanned(&ast));
+        assert_eq!(check_program(&ast), Ok(()));
+        // Registros são copiados: mudar `ps[1]` não altera `l.b`
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(15456))));
+
+        // Declarações de registro inválidas são rejeitadas na análise
+        for (source, message) in [
+            (
+                "int main() [ tipo Ponto p; ] ",
+                "Tipo 'Ponto' não declarado",
+            ),
+            (
+                "tipo P [ int x; float x; ] int main() [ ] ",
+                "Campo 'x' repetido no registro 'P'",
+            ),
+            (
+                "tipo P [ int x := 1; ] int main() [ ] ",
+                "Campo 'x' do registro 'P' não pode ter valor inicial",
+            ),
+            (
+                "tipo P [ int x; ] tipo P [ int y; ] int main() [ ] ",
+                "Tipo 'P' já declarado",
+            ),
+            (
+                "tipo P [ tipo P proximo; ] int main() [ ] ",
+                "Tipo 'P' não declarado",
+            ),
+            (
+                "tipo P [ char a[4000000000]; char b[4000000000]; ] int main() [ ] ",
+                "Registro 'P' grande demais",
+            ),
+            (
+                "tipo P [ int v[4000000000]; ] int main() [ ] ",
+                "Registro 'P' grande demais",
+            ),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(source.into(), "inicio", &mut symbol_table);
+            match parser.parse() {
+                Err(ParserError::Syntax { message: m, .. }) => assert_eq!(m, message),
+                other => panic!("Era esperado um erro sintático: {:?}", other),
+            }
+        }
+
+        // Erros de tipo, com a posição do nó que falhou na segunda linha
+        for (stmt, message, column) in [
+            ("i := p.z;", "tipo P não tem o campo 'z'", 6),
+            ("i := i.x;", "'i' não é um registro, é do tipo int", 6),
+            ("i := p[0];", "'p' não é um vetor, é do tipo P", 6),
+            ("i := p + 1;", "Operando do tipo P em 'p + 1'", 6),
+            ("i := p;", "Valor do tipo P onde se espera int", 6),
+            ("p.x := p;", "Valor do tipo P onde se espera int", 8),
+            ("q.x := 1;", "Atribuição à constante 'q'", 1),
+            (
+                "int v[4000000000];",
+                "Variável 'v' do tipo int[4000000000] grande demais",
+                1,
+            ),
+            (
+                "tipo P w[2000000000];",
+                "Variável 'w' do tipo P[2000000000] grande demais",
+                1,
+            ),
+            (
+                "const int c := 0; for (c; 0; 3; c + 1) [ ]",
+                "Atribuição à constante 'c'",
+                19,
+            ),
+            (
+                "int v[2]; int w[3]; v := w;",
+                "Valor do tipo int[3] onde se espera int[2]",
+                26,
+            ),
+        ] {
+            let source = format!(
+                "tipo P [ int x; ] int main() [ int i; tipo P p; const tipo P q := p;\n{} ] ",
+                stmt
+            );
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source.clone(), "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+
+            let error = check_program(&ast).unwrap_err();
+            assert_eq!(error.message, message, "{}", source);
+            assert_eq!(
+                error.span.map(|span| (span.start.line, span.start.column)),
+                Some((2, column)),
+                "{}",
+                source
+            );
+        }
+
+        // Vetores de tamanhos diferentes só se encontram na passagem de parâmetro,
+        // que é por referência
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(
+            "void f(int a[10]) [ ] int main() [ int v[5]; f(v); ] ".into(),
+            "inicio",
+            &mut symbol_table,
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(check_program(&ast), Ok(()));
+    }
+
+    #[test]
+    fn test_bool_expressions() {
+        let source = "bool menor(int a, int b) [
+    return a < b;
+]
+
+int main() [
+    int x := 3, y := 5, n;
+    bool b := x + 1 < y * 2, c := false;
+
+    c := menor(y, x) == false;
+    if (b) then n := 1;
+    while (c != (n >= 10)) do [
+        n := n + 1;
+    ]
+    if (true) then n := n * 10;
+    return n;
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        let AstNode::Program { body, .. } = &ast else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        let AstNode::Block { decls, stmts } = &body.node else {
+            panic!("Era esperado um bloco: {:?}", body);
+        };
+        // A comparação fica abaixo da soma e do produto
+        assert_eq!(
+            decls[1].to_sexp(),
+            "(var bool (:= b (< (+ x 1) (* y 2))) (:= c false))"
+        );
+        assert_eq!(stmts[0].to_sexp(), "(:= c (== (call menor y x) false))");
+
+        assert_eq!(format_program(&ast), source);
+        assert!(fully_spanned(&ast));
+        assert_eq!(check_program(&ast), Ok(()));
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(100))));
+
+        // Comparações não se encadeiam
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(
+            "int main() [ bool b := 1 < 2 < 3; ] ".into(),
+            "inicio",
+            &mut symbol_table,
+        );
+        assert!(matches!(parser.parse(), Err(ParserError::Syntax { .. })));
+
+        // Erros de tipo, com a posição do nó que falhou na segunda linha
+        for (stmt, message, column) in [
+            (
+                "if (x) then x := 1;",
+                "Condição do tipo int, era esperado bool",
+                5,
+            ),
+            (
+                "while (x + 1) do x := 1;",
+                "Condição do tipo int, era esperado bool",
+                8,
+            ),
+            ("x := b;", "Valor do tipo bool onde se espera int", 6),
+            ("b := x;", "Valor do tipo int onde se espera bool", 6),
+            ("b := b + 1;", "Operando do tipo bool em 'b + 1'", 6),
+            ("b := b < true;", "Operando do tipo bool em 'b < true'", 6),
+        ] {
+            let source = format!("int main() [ int x; bool b;\n{} ] ", stmt);
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source.clone(), "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+
+            let error = check_program(&ast).unwrap_err();
+            assert_eq!(error.message, message, "{}", source);
+            assert_eq!(
+                error.span.map(|span| (span.start.line, span.start.column)),
+                Some((2, column)),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_switch_statement() {
+        let source = "int comando(char c, int x) [
+    switch (c) [
+        case 'a': x := x + 2;
+        case 'd': x := x * 2;
+        case 'z': [
+            x := 0;
+        ]
+        default: x := x - 1;
+    ]
+    return x;
+]
+
+int main() [
+    int i, x := 1;
+
+    for (i; 0; 6; i + 1) [
+        switch (i) [
+            case 0: x := comando('a', x);
+            case -1: x := 0;
+            case 2: continue;
+            case 4: break;
+            default: x := comando('d', x);
+        ]
+        x := x + 100;
+    ]
+    return x;
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        let AstNode::Program { functions, .. } = &ast else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        let AstNode::Function { body, .. } = &functions[0].node else {
+            panic!("Era esperada uma função: {:?}", functions[0]);
+        };
+        assert_eq!(
+            body.children()[0].to_sexp(),
+            "(switch c (case 'a' (:= x (+ x 2))) (case 'd' (:= x (* x 2))) \
+             (case 'z' (block (:= x 0))) (default (:= x (- x 1))))"
+        );
+
+        assert_eq!(format_program(&ast), source);
+        assert!(fully_spanned(&ast));
+        assert_eq!(check_program(&ast), Ok(()));
+        // Sem passagem entre os ramos: 'a' soma 2 e só; o `break` sai do `for`
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(712))));
+
+        // O `default` é o último ramo
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(
+            "int main() [ switch (1) [ default: return 1; case 1: return 2; ] ] ".into(),
+            "inicio",
+            &mut symbol_table,
+        );
+        assert!(matches!(parser.parse(), Err(ParserError::Syntax { .. })));
+
+        // Erros de tipo, com a posição do nó que falhou na segunda linha
+        for (stmt, message, column) in [
+            (
+                "switch (x) [ case 1: x := 1; case 01: x := 2; ]",
+                "Rótulo 01 repetido no switch",
+                35,
+            ),
+            (
+                "switch (c) [ case 'a': x := 1; case 'a': x := 2; ]",
+                "Rótulo 'a' repetido no switch",
+                37,
+            ),
+            (
+                "switch (x) [ case 'a': x := 1; ]",
+                "Rótulo 'a' do tipo char num switch sobre int",
+                19,
+            ),
+            (
+                "switch (c) [ case 1.5: x := 1; ]",
+                "Rótulo 1.5 do tipo float num switch sobre char",
+                19,
+            ),
+            (
+                "switch (x * 0.5) [ default: x := 1; ]",
+                "switch sobre o tipo float, era esperado int ou char",
+                9,
+            ),
+        ] {
+            let source = format!("int main() [ int x; char c;\n{} ] ", stmt);
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source.clone(), "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+
+            let error = check_program(&ast).unwrap_err();
+            assert_eq!(error.message, message, "{}", source);
+            assert_eq!(
+                error.span.map(|span| (span.start.line, span.start.column)),
+                Some((2, column)),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let source = "tipo P [ int x; int v[3]; ]
+
+int main() [
+    int i, s := 1;
+    tipo P p;
+
+    for (i; 0; 3; i + 1) [
+        s *= 2;
+        p.v[i] += i * 10;
+        p.x++;
+    ]
+    s -= 1 + 2;
+    s /= p.x;
+    i--;
+    p.v[1]--;
+    return s * 100 + p.v[1] + i;
+] ";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        // `x op= e` e `x++` viram atribuições comuns, com o alvo repetido no valor
+        let AstNode::Program { body, .. } = &ast else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        let AstNode::Block { stmts, .. } = &body.node else {
+            panic!("Era esperado um bloco: {:?}", body);
+        };
+        assert_eq!(
+            stmts[0].children()[3].to_sexp(),
+            "(block (:= s (* s 2)) (:= (index (. p v) i) (+ (index (. p v) i) (* i 10))) \
+             (:= (. p x) (+ (. p x) 1)))"
+        );
+        assert_eq!(stmts[1].to_sexp(), "(:= s (- s (+ 1 2)))");
+        assert_eq!(stmts[3].to_sexp(), "(:= i (- i 1))");
+
+        assert!(fully_spanned(&ast));
+        // O `1` implícito de `i--` fica com o intervalo do `--;`
+        let decrement = ast.children()[1].children()[5].children()[0].children()[1];
+        assert_eq!(
+            decrement.span,
+            Some(Span {
+                start: Position {
+                    line: 14,
+                    column: 6
+                },
+                end: Position {
+                    line: 14,
+                    column: 9
+                },
+            })
+        );
+        assert_eq!(check_program(&ast), Ok(()));
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(111))));
+
+        // A forma canônica não tem açúcar sintático
+        assert!(format_program(&ast).contains("    s := s - (1 + 2);\n    s := s / p.x;\n"));
+
+        // O alvo é avaliado duas vezes, então não pode chamar funções: em
+        // `v[f()] += 5`, `f` rodaria duas vezes
+        let mut symbol_table = HashMap::new();
+        let mut parser = create_instance(
+            "int g := 0; int f() [ g += 1; return 1; ] int main() [ int v[3]; v[f()] += 5; return g; ] "
+                .into(),
+            "inicio",
+            &mut symbol_table,
+        );
+        match parser.parse() {
+            Err(ParserError::Syntax { message, span }) => {
+                assert_eq!(
+                    message,
+                    "Alvo de atribuição composta não pode ter chamadas de função"
+                );
+                assert_eq!(
+                    span.start,
+                    Position {
+                        line: 1,
+                        column: 66
+                    }
+                );
+            }
+            other => panic!("Era esperado um erro sintático: {:?}", other),
+        }
+
+        // Só variáveis, elementos e campos recebem atribuições compostas
+        for source in [
+            "int main() [ f() += 1; ] ",
+            "int main() [ x ++ 1; ] ",
+            "int main() [ 1 += x; ] ",
+        ] {
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(source.into(), "inicio", &mut symbol_table);
+            assert!(
+                matches!(parser.parse(), Err(ParserError::Syntax { .. })),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence_levels() {
+        // Cada nível contra o seguinte, a associatividade de cada um e os unários
+        for (source, sexp) in [
+            ("a == b | c", "(== a (| b c))"),
+            ("a | b ^ c", "(| a (^ b c))"),
+            ("a ^ b & c", "(^ a (& b c))"),
+            ("a & b << c", "(& a (<< b c))"),
+            ("a >> b - c", "(>> a (- b c))"),
+            ("a + b % c", "(+ a (% b c))"),
+            ("a * -b", "(* a (- b))"),
+            ("~a % b", "(% (~ a) b)"),
+            ("-a ** b", "(- (** a b))"),
+            ("a | b | c", "(| (| a b) c)"),
+            ("a ^ b ^ c", "(^ (^ a b) c)"),
+            ("a & b & c", "(& (& a b) c)"),
+            ("a << b >> c", "(>> (<< a b) c)"),
+            ("a - b + c", "(+ (- a b) c)"),
+            ("a % b / c", "(/ (% a b) c)"),
+            ("a ** b ** c", "(** a (** b c))"),
+            ("a ** -b ** c", "(** a (- (** b c)))"),
+            ("(a ** b) ** c", "(** (** a b) c)"),
+            ("(a | b) & ~(c ^ d)", "(& (| a b) (~ (^ c d)))"),
+            ("- -a", "(- (- a))"),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(format!("{} ", source), "E", &mut symbol_table);
+            let ast = parser.parse().unwrap();
+
+            assert_eq!(ast.to_sexp(), sexp, "{}", source);
+            // O formatador usa só os parênteses necessários
+            assert_eq!(format_expression(&ast), source);
+        }
+
+        for (expr, value) in [
+            ("1 | 2 ^ 3", Value::Int(1)),
+            ("6 ^ 3 & 5", Value::Int(7)),
+            ("12 & 1 << 2", Value::Int(4)),
+            ("1 << 2 + 1", Value::Int(8)),
+            ("-16 >> 2", Value::Int(-4)),
+            ("7+10%4", Value::Int(9)),
+            ("-7 % 3", Value::Int(-1)),
+            ("~5 * 2", Value::Int(-12)),
+            ("'a' & ~32", Value::Int(65)),
+            ("-2 ** 2", Value::Int(-4)),
+            ("2 ** 3 ** 2", Value::Int(512)),
+            ("2 ** -1", Value::Float(0.5)),
+            ("7.5 % 2", Value::Float(1.5)),
+        ] {
+            let kind = if matches!(value, Value::Float(_)) {
+                "float"
+            } else {
+                "int"
+            };
+            let source = format!("{} main() [ return {}; ] ", kind, expr);
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source, "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+            assert_eq!(check_program(&ast), Ok(()), "{}", expr);
+            assert_eq!(evaluate(&ast), Ok(Some(value)), "{}", expr);
+        }
+
+        for (expr, error) in [
+            ("1 << 64", RuntimeError::InvalidShift(64)),
+            ("1 >> -1", RuntimeError::InvalidShift(-1)),
+            ("5 % 0", RuntimeError::DivisionByZero),
+        ] {
+            let source = format!("int main() [ return {}; ] ", expr);
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source, "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+            assert_eq!(evaluate(&ast), Err(error), "{}", expr);
+        }
+
+        // Operadores bit a bit só aceitam inteiros e caracteres
+        for (stmt, message) in [
+            (
+                "x := 1.5 & x;",
+                "Operando do tipo float em '1.5 & x', era esperado int",
+            ),
+            (
+                "x := ~b;",
+                "Operando do tipo bool em '~b', era esperado int",
+            ),
+            (
+                "x := x << 1.0;",
+                "Operando do tipo float em 'x << 1.0', era esperado int",
+            ),
+            ("x := b % 2;", "Operando do tipo bool em 'b % 2'"),
+        ] {
+            let source = format!("int main() [ int x; bool b; {} ] ", stmt);
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source, "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+            assert_eq!(
+                check_program(&ast).map_err(|error| error.message),
+                Err(message.to_string()),
+                "{}",
+                stmt
+            );
+        }
+
+        // Um inteiro que não cabe em 64 bits não vira real na análise
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(
+            "int main() [ int x := 99999999999999999999; ] ".into(),
+            "inicio",
+            &mut symbol_table,
+        )
+        .parse()
+        .unwrap();
+        let error = check_program(&ast).unwrap_err();
+        assert_eq!(
+            error.message,
+            "Inteiro 99999999999999999999 não cabe em 64 bits"
+        );
+        assert_eq!(
+            error.span.map(|span| (span.start.line, span.start.column)),
+            Some((1, 23))
+        );
+    }
+
+    #[test]
+    fn test_global_declarations() {
+        let source = "tipo Ponto [
+    int x;
+    int y;
+]
+
+const int LIMITE := 10;
+int contador := 0, passo := 2;
+tipo Ponto origem;
+float taxa;
+
+int proximo() [
+    contador := contador + passo;
+    return contador;
+]
+
+int main() [
+    int passo := 100, volta := 0;
+
+    origem.x := LIMITE;
+    while (proximo() < LIMITE) do [
+        volta := volta + 1;
+        taxa := 0.5;
+    ]
+    return contador + origem.x + passo + volta;
+]
+";
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+
+        let AstNode::Program { globals, .. } = &ast else {
+            panic!("Era esperado um programa: {:?}", ast);
+        };
+        assert_eq!(
+            globals
+                .iter()
+                .map(|global| global.to_sexp())
+                .collect::<Vec<_>>(),
+            vec![
+                "(const int (:= LIMITE 10))",
+                "(var int (:= contador 0) (:= passo 2))",
+                "(var tipo Ponto origem)",
+                "(var float taxa)",
+            ]
+        );
+
+        assert_eq!(format_program(&ast), source);
+        assert!(fully_spanned(&ast));
+        assert_eq!(check_program(&ast), Ok(()));
+        // `proximo` usa o `passo` global; o local de `main` só vale lá dentro
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(124))));
+
+        // Uma global escondida por uma local continua global
+        for (name, scope) in [
+            ("LIMITE", Some(Scope::Global)),
+            ("passo", Some(Scope::Global)),
+            ("origem", Some(Scope::Global)),
+            ("volta", Some(Scope::Local)),
+            ("x", None),
+            ("proximo", None),
+        ] {
+            assert_eq!(symbol_table[name].scope, scope, "{}", name);
+        }
+
+        // Registros vêm antes das globais, que vêm antes das funções
+        for source in [
+            "int f() [ return 1; ] int g; int main() [ return 0; ] ",
+            "int g; tipo P [ int x; ] int main() [ return 0; ] ",
+            "int g int main() [ return 0; ] ",
+        ] {
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(source.into(), "inicio", &mut symbol_table);
+            assert!(
+                matches!(parser.parse(), Err(ParserError::Syntax { .. })),
+                "{}",
+                source
+            );
+        }
+
+        for (source, message) in [
+            (
+                "const int N := 1; void f() [ N := 2; ] int main() [ return N; ] ",
+                "Atribuição à constante 'N'",
+            ),
+            (
+                "int a := b; int b; int main() [ return a; ] ",
+                "Variável 'b' não declarada",
+            ),
+            (
+                "int g; int g; int main() [ return 0; ] ",
+                "Variável 'g' já declarada neste escopo",
+            ),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let ast = create_instance(source.into(), "inicio", &mut symbol_table)
+                .parse()
+                .unwrap();
+            assert_eq!(
+                check_program(&ast).map_err(|error| error.message),
+                Err(message.to_string()),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_program_imports() {
+        let files = |files: &[(&str, &str)]| {
+            let files: HashMap<PathBuf, String> = files
+                .iter()
+                .map(|(path, text)| (PathBuf::from(path), text.to_string()))
+                .collect();
+            move |path: &Path| {
+                files.get(path).cloned().ok_or(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "arquivo inexistente",
+                ))
+            }
+        };
+        let library = [
+            (
+                "prog/lib/ponto.txt",
+                "tipo Ponto [\n    int x;\n]\n\nconst int LIMITE := 4;\ntipo Ponto origem;\n",
+            ),
+            (
+                "prog/lib/util.txt",
+                "importa \"ponto.txt\"\nint dobro(int n) [\n    return n * 2;\n]\n",
+            ),
+        ];
+
+        let main = "importa \"lib/ponto.txt\"\n  importa \"./lib/util.txt\"  \n\nint main() [\n    origem.x := LIMITE;\n    return dobro(origem.x);\n]\n";
+        let (contents, source_map) = load_program_with(
+            "prog/main.txt",
+            files(&[library[0], library[1], ("prog/main.txt", main)]),
+        )
+        .unwrap();
+
+        // `ponto.txt` é importado duas vezes, mas entra uma só
+        assert_eq!(
+            source_map.files(),
+            ["prog/main.txt", "prog/lib/ponto.txt", "prog/lib/util.txt"].map(PathBuf::from)
+        );
+
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(contents, "inicio", &mut symbol_table)
+            .parse()
+            .unwrap();
+        assert_eq!(check_program(&ast), Ok(()));
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(8))));
+
+        // Registros, globais, funções e o corpo de `main`, cada um no seu arquivo
+        let origins: Vec<String> = ast
+            .children()
+            .iter()
+            .filter_map(|child| Some(source_map.locate(child.span?.start).to_string()))
+            .collect();
+        assert_eq!(
+            origins,
+            vec![
+                "prog/lib/ponto.txt: linha 1, coluna 6",
+                "prog/lib/ponto.txt: linha 5, coluna 1",
+                "prog/lib/ponto.txt: linha 6, coluna 1",
+                "prog/lib/util.txt: linha 2, coluna 1",
+                "prog/main.txt: linha 4, coluna 12",
+            ]
+        );
+
+        // Erros léxicos, sintáticos e de tipos apontam para o arquivo importado
+        for (util, message) in [
+            (
+                "int dobro(int n) [\n    return 'ab';\n]\n",
+                "Erro léxico: 'a\n Tipo: UnclosedChar\n arquivo: prog/lib/util.txt\n linha: 2\n coluna: 12",
+            ),
+            (
+                "int dobro(int n) [\n    return n * 2\n]\n",
+                "Erro de sintaxe. Esperado SemiColonPunctuation, encontrado <Punctuation, value=']', kind=EndBlock, line=3, column=1> (prog/lib/util.txt: linha 3, coluna 1)",
+            ),
+            (
+                "int dobro(int n) [\n    return origem;\n]\n",
+                "Valor do tipo Ponto onde se espera int (prog/lib/util.txt: linha 2, coluna 12)",
+            ),
+        ] {
+            let (contents, source_map) = load_program_with(
+                "prog/main.txt",
+                files(&[
+                    library[0],
+                    ("prog/lib/util.txt", util),
+                    ("prog/main.txt", main),
+                ]),
+            )
+            .unwrap();
+
+            let mut symbol_table = HashMap::new();
+            let described = match create_instance(contents, "inicio", &mut symbol_table).parse() {
+                Ok(ast) => source_map.describe_type_error(&check_program(&ast).unwrap_err()),
+                Err(error) => source_map.describe_parser_error(&error),
+            };
+            assert_eq!(described, message);
+        }
+
+        let cycle = load_program_with(
+            "a.txt",
+            files(&[
+                ("a.txt", "importa \"sub/b.txt\"\n"),
+                ("sub/b.txt", "importa \"../a.txt\"\n"),
+            ]),
+        );
+        assert_eq!(
+            cycle.map(|_| ()).map_err(|error| error.to_string()),
+            Err("Importação circular: a.txt -> sub/b.txt -> a.txt".to_string())
+        );
+
+        assert_eq!(
+            load_program_with("a.txt", files(&[("a.txt", "int x;\nimporta \"b.txt\n")]))
+                .map(|_| ()),
+            Err(ImportError::Directive {
+                path: "a.txt".into(),
+                line: 2
+            })
+        );
+        assert_eq!(
+            load_program_with("a.txt", files(&[("a.txt", "importa \"b.txt\"\n")])).map(|_| ()),
+            Err(ImportError::Io {
+                path: "b.txt".into(),
+                message: "arquivo inexistente".into()
+            })
+        );
+
+        // Um `importa` dentro de um comentário de várias linhas continua no texto;
+        // depois que o comentário fecha, volta a valer
+        let commented = "{% antigo:\nimporta \"b.txt\"\n%} importa \"c.txt\"\nimporta \"d.txt\"\n";
+        let (contents, source_map) = load_program_with(
+            "a.txt",
+            files(&[("a.txt", commented), ("d.txt", "int d;\n")]),
+        )
+        .unwrap();
+        assert_eq!(
+            contents,
+            "{% antigo:\nimporta \"b.txt\"\n%} importa \"c.txt\"\nint d;\n"
+        );
+        assert_eq!(source_map.files(), ["a.txt", "d.txt"].map(PathBuf::from));
+    }
+
+    #[test]
+    fn test_preprocessor_macros() {
+        let source = "#define N 10
+  #define LIMITE N * 2
+#define VAZIO
+int main() [
+    int soma := 0 VAZIO;
+    while (soma < LIMITE) do [
+        soma := soma + N;
+    ]
+    return soma;
+]
+";
+        let (contents, mut preprocessor) = Preprocessor::new(source).unwrap();
+        // As diretivas viram linhas vazias
+        assert!(contents.starts_with("\n\n\nint main() ["));
+
+        // Os tokens expandidos ficam na posição de onde a macro foi usada
+        let mut symbol_table = HashMap::new();
+        let mut lexer = Lexer::new(contents.clone(), &mut symbol_table);
+        let mut tokens = Vec::new();
+        loop {
+            match preprocessor.next_token(&mut lexer).unwrap() {
+                Token::Eof => break,
+                token => tokens.push(token),
+            }
+        }
+        assert_eq!(
+            tokens[13..18]
+                .iter()
+                .map(|token| (token.lexeme(), token.position().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "<".to_string(),
+                    Position {
+                        line: 6,
+                        column: 17
+                    }
+                ),
+                (
+                    "10".to_string(),
+                    Position {
+                        line: 6,
+                        column: 19
+                    }
+                ),
+                (
+                    "*".to_string(),
+                    Position {
+                        line: 6,
+                        column: 19
+                    }
+                ),
+                (
+                    "2".to_string(),
+                    Position {
+                        line: 6,
+                        column: 19
+                    }
+                ),
+                (
+                    ")".to_string(),
+                    Position {
+                        line: 6,
+                        column: 25
+                    }
+                ),
+            ]
+        );
+
+        let (_, preprocessor) = Preprocessor::new(source).unwrap();
+        let mut symbol_table = HashMap::new();
+        let ast = create_instance(contents, "inicio", &mut symbol_table)
+            .with_preprocessor(preprocessor)
+            .parse()
+            .unwrap();
+        assert_eq!(
+            ast.to_sexp(),
+            "(program int (block (var int (:= soma 0)) (while (< soma (* 10 2)) (block (:= soma (+ soma 10)))) (return soma)))"
+        );
+        assert_eq!(evaluate(&ast), Ok(Some(Value::Int(20))));
+
+        for (source, start, expected) in [
+            // A macro não se expande dentro da própria expansão
+            ("#define x x + 1\nx * 2 ", "E", Ok("(+ x (* 1 2))")),
+            // Antes do `#define` o nome é um identificador comum
+            (
+                "int main() [\n    int N := 1;\n#define N 2\n    return N;\n]\n",
+                "inicio",
+                Ok("(program int (block (var int (:= N 1)) (return 2)))"),
+            ),
+            // Dentro de um comentário `#` não é diretiva, nem mesmo um `#define`
+            (
+                "{% # nota\n#define N 2\n%}\nint main() [\n    return N;\n]\n",
+                "inicio",
+                Ok("(program int (block (return N)))"),
+            ),
+            // O erro aponta para onde a macro foi usada
+            (
+                "#define N 10\nint main() [\n    return 1 N;\n]\n",
+                "inicio",
+                Err(span((3, 14), (3, 15))),
+            ),
+        ] {
+            let (contents, preprocessor) = Preprocessor::new(source).unwrap();
+            let mut symbol_table = HashMap::new();
+            let result = create_instance(contents, start, &mut symbol_table)
+                .with_preprocessor(preprocessor)
+                .parse();
+            match (result, expected) {
+                (Ok(ast), Ok(sexp)) => assert_eq!(ast.to_sexp(), sexp, "{}", source),
+                (Err(ParserError::Syntax { span, .. }), Err(expected)) => {
+                    assert_eq!(span, expected, "{}", source)
+                }
+                (result, _) => panic!("Resultado inesperado para {:?}: {:?}", source, result),
+            }
+        }
+
+        assert_eq!(
+            Preprocessor::new("int x;\n  #include \"lib.txt\"\n").map(|_| ()),
+            Err("Erro de pré-processamento: #include \"lib.txt\"\n Tipo: UnknownDirective\n linha: 2\n coluna: 3".to_string())
+        );
+        for (source, kind) in [
+            ("#define\n", PreprocessorError::MissingMacroName),
+            ("#define 2N 2\n", PreprocessorError::InvalidMacroName),
+            (
+                "#define N 1\n#define N 2\n",
+                PreprocessorError::RedefinedMacro,
+            ),
+            ("#define C 'ab'\n", PreprocessorError::InvalidMacroBody),
+        ] {
+            let error = Preprocessor::new(source).map(|_| ()).unwrap_err();
+            assert!(error.contains(&format!("Tipo: {:?}", kind)), "{}", error);
+        }
+    }
+
+    // Entradas analisadas por mais de um analisador, que precisam dar a mesma AST,
+    // os mesmos intervalos e os erros na mesma posição
+    const PARSER_CORPUS: &[(&str, &str)] = &[
+        ("E", "-6 "),
+        ("E", "-(x * 2) "),
+        ("E", "a - b - c * d / e % f "),
+        ("E", "2 ** 3 ** -a ** b * c "),
+        ("E", "-a ** b * ~c "),
+        ("E", "- -a "),
+        ("E", "(a) + ((b)) * (c - d) "),
+        ("E", "a | b ^ c & d << 1 >> 2 + 3 "),
+        ("E", "(a + 1) < b | c "),
+        ("E", "(a < b) == (c >= d) "),
+        ("E", "f() + g(1, h(x, y[i][j + 1]), p.x.y) "),
+        ("E", "v[f(1)].campo[0] "),
+        ("E", "true != false "),
+        ("E", "'a' + 1.5E3 "),
+        ("E", "a < b < c "),
+        ("E", "a + "),
+        ("E", "(a + b "),
+        ("E", "f(a b) "),
+        ("E", "f(,) "),
+        ("E", "v[1 "),
+        ("E", "p. "),
+        ("E", "* a "),
+        ("E", "a ** "),
+        ("E", "f()[0] "),
+        ("inicio", include_str!("../data.txt")),
+        (
+            "inicio",
+            "tipo Ponto [
+    int x, y;
+]
+
+const int N := 3 * (2 + 1);
+tipo Ponto origem;
+float taxas[4];
+
+void ordena(int v[10], int n) [
+    int i, j;
+    float t;
+
+    for (i; 0; n - 1; i + 1) for (j; 0; n - 1 - i; j + 1) if (v[j] > v[j + 1]) then [
+        t := v[j];
+        v[j] := v[j + 1];
+        v[j + 1] := t;
+    ]
+]
+
+int comando(char c, int x) [
+    switch (c) [
+        case 'a': x += 2;
+        case -1: x := 0;
+        default: [
+            x *= -x ** 2;
+        ]
+    ]
+    return x;
+]
+
+int main() [
+    int v[10];
+    int i := 0;
+    bool achou := false;
+
+    origem.x := N % 2;
+    while ((i < 10 & 1) == true) do [
+        v[i] := comando('a', i << 1);
+        i++;
+        if (v[i - 1] >= N) then break;
+        elsif (achou != true) then continue;
+        else achou := ~i == -1;
+    ]
+    do v[0]--; while (v[0] > -5);
+    ordena(v, 10);
+    return v[0] + origem.x;
+]
+",
+        ),
+        ("inicio", "int main() [ return (1 + 2; ] "),
+        ("inicio", "int main() [ x := 1 + ; ] "),
+        ("inicio", "int main() [ if (a < b < c) then x := 1; ] "),
+        ("inicio", "int main() [ return f(1, 2; ] "),
+        ("inicio", "int main() [ return v[1 2]; ] "),
+        ("inicio", "int main() [ x := 'ab'; ] "),
+        (
+            "inicio",
+            "tipo P [
+    int x[2];
+]
+tipo Q [
+    tipo P p;
+]
+
+int g, h := 2, k;
+const float PI := 3.14, E := 2.71;
+int v[3];
+
+void vazia() [
+]
+
+int soma(int a, int b, float c[2]) [
+    return a + b;
+]
+
+int main() [
+    tipo Q q;
+    for (i; 0; 10; i + 1) [
+        if (i == 2) then continue;
+        elsif (i == 3) then [
+            break;
+        ]
+        elsif (i > 8) then return 1;
+    ]
+    soma(1, 2, v);
+    vazia();
+    q.p.x[1] -= 2;
+    v[g]++;
+    switch (g) [
+        case 'x': g /= 2;
+        case 1: [
+            do while (h < 1) do h++; while (false);
+        ]
+    ]
+    switch (h) [
+        default: h := 0;
+    ]
+]
+",
+        ),
+        ("inicio", "int main() [ ] "),
+        ("inicio", "int main() [ break; ] "),
+        ("inicio", "int main() [ switch (x) [ case 1: continue; ] ] "),
+        ("inicio", "tipo P [ int x := 1; ] int main() [ ] "),
+        ("inicio", "tipo P [ ] int main() [ ] "),
+        ("inicio", "tipo Q q; int main() [ ] "),
+        ("inicio", "int main() [ if (a) x := 1; ] "),
+        ("inicio", "int main() [ int x y; ] "),
+        ("inicio", "int main() [ x := 1 ] "),
+        ("inicio", "int f(int a b) [ ] int main() [ ] "),
+        ("inicio", "void f() [ ] int g; int main() [ ] "),
+        ("inicio", "int main() [ p.x; ] "),
+        ("inicio", "int main() [ switch (x) [ case a: x := 1; ] ] "),
+        ("inicio", "int main() [ x := 1; int y; ] "),
+        ("inicio", "int main() [ int x; [ x := 1; ] ] "),
+        ("inicio", "int main() [ [ x := 1; ] ] "),
+        (
+            "inicio",
+            "int g := 0; int f() [ g += 1; return 1; ] int main() [ int v[3]; v[f()] += 5; return g; ] ",
+        ),
+        ("inicio", "int main() [ p.v[f(1)]++; ] "),
+        ("inicio", "int main() [ ] x "),
+        ("inicio", "int main() [ "),
+        ("inicio", "main() [ ] "),
+    ];
+
+    // AST e intervalos, ou a posição do erro; erros léxicos não têm posição
+    fn parse_outcome(
+        parser: &mut Parser,
+    ) -> Result<(AstNode, Vec<Option<Span>>), Option<Position>> {
+        let outcome = parser.parse_spanned().map(|ast| {
+            let spans = spans_of(&ast);
+            (ast.node, spans)
+        });
+        outcome.map_err(|error| match error {
+            ParserError::Syntax { span, .. } | ParserError::Internal { span, .. } => {
+                Some(span.start)
+            }
+            ParserError::Lexical(_) => None,
+        })
+    }
+
+    #[test]
+    fn test_pratt_expressions_match_table() {
+        let mut accepted = 0;
+        for (start_symbol, source) in PARSER_CORPUS {
+            let mut symbol_table = HashMap::new();
+            let table = parse_outcome(&mut create_instance(
+                source.to_string(),
+                start_symbol,
+                &mut symbol_table,
+            ));
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(source.to_string(), start_symbol, &mut symbol_table)
+                .with_pratt_expressions()
+                .with_parse_tree();
+            let pratt = parse_outcome(&mut parser);
+
+            assert_eq!(pratt, table, "{}", source);
+
+            // A árvore de derivação continua sem perdas e refaz a mesma AST
+            if let Ok((ast, _)) = pratt {
+                accepted += 1;
+                let tree = parser.parse_tree().unwrap();
+                assert_eq!(tree.to_source(), *source);
+                assert_eq!(tree.to_ast(), Ok(ast));
+            }
+        }
+        assert_eq!(accepted, 20);
+
+        // Os níveis da tabela de precedência, do menos ao mais prioritário
+        let levels: Vec<(u8, Vec<String>)> = BINARY_OPERATORS
+            .chunk_by(|a, b| a.1 == b.1)
+            .map(|level| {
+                (
+                    level[0].1,
+                    level
+                        .iter()
+                        .map(|(token, ..)| format!("{:?}", token))
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(levels.len(), 8);
+        assert!(levels.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(
+            levels
+                .iter()
+                .all(|(precedence, _)| *precedence != PREFIX_PRECEDENCE)
+        );
+    }
+
+    #[test]
+    fn test_expression_nesting_limit() {
+        // O analisador por precedência recursa a cada nível, então desiste com erro
+        // de sintaxe antes de estourar a pilha; a tabela não tem esse limite
+        let nested = |depth: usize| {
+            format!(
+                "int main() [ return {}1{}; ] ",
+                "(".repeat(depth),
+                ")".repeat(depth)
+            )
+        };
+        for depth in [63, 64, 500] {
+            for descent in [false, true] {
+                let mut symbol_table = HashMap::new();
+                let parser = create_instance(nested(depth), "inicio", &mut symbol_table);
+                let mut parser = match descent {
+                    false => parser.with_pratt_expressions(),
+                    true => parser.with_recursive_descent(),
+                };
+                match parser.parse() {
+                    Ok(_) => assert_eq!(depth, 63),
+                    Err(ParserError::Syntax { message, span }) => {
+                        assert_ne!(depth, 63);
+                        assert_eq!(message, "Expressão com mais de 64 níveis de aninhamento");
+                        assert_eq!(
+                            span.start,
+                            Position {
+                                line: 1,
+                                column: 85
+                            }
+                        );
+                    }
+                    other => panic!("Era esperado um erro sintático: {:?}", other),
+                }
+            }
+
+            let mut symbol_table = HashMap::new();
+            let mut parser = create_instance(nested(depth), "inicio", &mut symbol_table);
+            assert!(parser.parse().is_ok(), "{}", depth);
+        }
+    }
+
+    #[test]
+    fn test_recursive_descent_matches_table() {
+        let mut accepted = 0;
+        for (start_symbol, source) in PARSER_CORPUS {
+            let mut symbol_table = HashMap::new();
+            let table = parse_outcome(&mut create_instance(
+                source.to_string(),
+                start_symbol,
+                &mut symbol_table,
+            ));
+            let mut descent_symbol_table = HashMap::new();
+            let mut parser =
+                create_instance(source.to_string(), start_symbol, &mut descent_symbol_table)
+                    .with_recursive_descent()
+                    .with_parse_tree();
+            let descent = parse_outcome(&mut parser);
+
+            assert_eq!(descent, table, "{}", source);
+            assert!(parser.parse_tree().is_none());
+            drop(parser);
+            // Os escopos registrados pelas ações também são os mesmos
+            let scopes = |table: &SymbolTable| {
+                table
+                    .iter()
+                    .map(|(name, entry)| (name.clone(), entry.scope))
+                    .collect::<HashMap<_, _>>()
+            };
+            assert_eq!(
+                scopes(&descent_symbol_table),
+                scopes(&symbol_table),
+                "{}",
+                source
+            );
+            accepted += usize::from(descent.is_ok());
+        }
+        assert_eq!(accepted, 20);
+
+        // As mensagens dizem o que faltou em cada ponto
+        for (source, message) in [
+            (
+                "int main() [ x := 1 ] ",
+                "Erro de sintaxe. Esperado ';' no fim da atribuição, encontrado ']'",
+            ),
+            (
+                "int main() [ if (a) x := 1; ] ",
+                "Erro de sintaxe. Esperado 'then' depois da condição do if, encontrado 'x'",
+            ),
+            (
+                "int main() [ int x y; ] ",
+                "Erro de sintaxe. Esperado ':=', ',', ';' ou '[' depois do nome, encontrado 'y'",
+            ),
+            (
+                "int main() [ ",
+                "Erro de sintaxe. Esperado ']' no fim do bloco, encontrado o fim do arquivo",
+            ),
+        ] {
+            let mut symbol_table = HashMap::new();
+            let error = create_instance(source.to_string(), "inicio", &mut symbol_table)
+                .with_recursive_descent()
+                .parse()
+                .unwrap_err();
+            assert_eq!(error.message(), message);
+        }
     }
 }
@@ -0,0 +1,205 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{Lexer, Position, SymbolEntry, Token, lexer::comment_open_after};
+
+#[allow(unused)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PreprocessorError {
+    UnknownDirective,
+    MissingMacroName,
+    InvalidMacroName,
+    RedefinedMacro,
+    InvalidMacroBody,
+}
+
+// Macro sem parâmetros, com os tokens do corpo já separados pelo léxico
+struct Macro {
+    // Linha do `#define`; a macro só vale nas linhas seguintes
+    line: usize,
+    body: Vec<Token>,
+}
+
+// Pré-processador entre o léxico e o sintático. As linhas `#define NOME corpo`
+// fora de comentários `{% %}` saem do texto antes do léxico e cada `NOME` que
+// aparece depois delas é trocado pelos tokens do corpo, todos na posição de onde
+// a macro foi usada.
+pub struct Preprocessor {
+    macros: HashMap<String, Macro>,
+    // Tokens de uma expansão que ainda não foram entregues
+    pending: VecDeque<Token>,
+}
+
+impl Preprocessor {
+    // Separa as diretivas de `contents`, devolvendo o texto que vai para o léxico.
+    // Cada diretiva vira uma linha vazia, então linhas e colunas não mudam.
+    pub fn new(contents: &str) -> Result<(String, Self), String> {
+        let mut macros = HashMap::new();
+        let mut text = String::with_capacity(contents.len());
+
+        let mut in_comment = false;
+        for (index, line) in contents.split_inclusive('\n').enumerate() {
+            let directive = line.trim_start();
+            if in_comment || !directive.starts_with('#') {
+                in_comment = comment_open_after(line, in_comment);
+                text.push_str(line);
+                continue;
+            }
+            if line.ends_with('\n') {
+                text.push('\n');
+            }
+
+            let line_number = index + 1;
+            let column = line.len() - directive.len() + 1;
+            let error = |value: &str, kind| {
+                format!(
+                    "Erro de pré-processamento: {value}\n Tipo: {kind:?}\n linha: {line_number}\n coluna: {column}"
+                )
+            };
+
+            let Some(definition) = directive.strip_prefix("#define") else {
+                return Err(error(
+                    directive.trim_end(),
+                    PreprocessorError::UnknownDirective,
+                ));
+            };
+            if !definition.starts_with(char::is_whitespace) {
+                return Err(error(
+                    directive.trim_end(),
+                    PreprocessorError::MissingMacroName,
+                ));
+            }
+
+            let definition = definition.trim();
+            let (name, body) = definition
+                .split_once(char::is_whitespace)
+                .unwrap_or((definition, ""));
+            if name.is_empty() {
+                return Err(error(
+                    directive.trim_end(),
+                    PreprocessorError::MissingMacroName,
+                ));
+            }
+            if !is_identifier(name) {
+                return Err(error(name, PreprocessorError::InvalidMacroName));
+            }
+            if macros.contains_key(name) {
+                return Err(error(name, PreprocessorError::RedefinedMacro));
+            }
+
+            let body =
+                tokenize(body).map_err(|_| error(body, PreprocessorError::InvalidMacroBody))?;
+            macros.insert(
+                name.to_string(),
+                Macro {
+                    line: line_number,
+                    body,
+                },
+            );
+        }
+
+        Ok((
+            text,
+            Preprocessor {
+                macros,
+                pending: VecDeque::new(),
+            },
+        ))
+    }
+
+    // Próximo token já expandido, lido de `lexer` quando não há expansão pendente
+    pub fn next_token(&mut self, lexer: &mut Lexer) -> Result<Token, String> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Ok(token);
+            }
+
+            let token = lexer.get_next_token()?;
+            let Token::Id {
+                value,
+                line,
+                column,
+            } = &token
+            else {
+                return Ok(token);
+            };
+            if !self.is_defined(value, *line) {
+                return Ok(token);
+            }
+
+            let position = Position {
+                line: *line,
+                column: *column,
+            };
+            let mut expansion = Vec::new();
+            self.expand(value, position, &mut vec![], &mut expansion);
+
+            // Nomes que só aparecem em corpos de macros também entram na tabela
+            for token in &expansion {
+                if let Token::Id { value, .. } = token {
+                    lexer
+                        .symbol_table
+                        .entry(value.clone())
+                        .or_insert(SymbolEntry {
+                            kind: None,
+                            scope: None,
+                        });
+                }
+            }
+            // Uma macro vazia some, e o laço segue para o próximo token
+            self.pending.extend(expansion);
+        }
+    }
+
+    // `name` é uma macro definida antes de `line`
+    fn is_defined(&self, name: &str, line: usize) -> bool {
+        self.macros
+            .get(name)
+            .is_some_and(|definition| definition.line < line)
+    }
+
+    // Expande `name` e as macros do seu corpo. Como no C, uma macro não se
+    // expande de novo dentro da própria expansão, o nome fica como está.
+    fn expand<'a>(
+        &'a self,
+        name: &'a str,
+        position: Position,
+        active: &mut Vec<&'a str>,
+        expansion: &mut Vec<Token>,
+    ) {
+        active.push(name);
+        for token in &self.macros[name].body {
+            match token {
+                Token::Id { value, .. }
+                    if self.is_defined(value, position.line)
+                        && !active.contains(&value.as_str()) =>
+                {
+                    self.expand(value, position, active, expansion);
+                }
+                token => expansion.push(token.clone().with_position(position)),
+            }
+        }
+        active.pop();
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+// Tokens do corpo de uma macro, sem o `Eof`
+fn tokenize(body: &str) -> Result<Vec<Token>, String> {
+    let mut symbol_table = HashMap::new();
+    // O espaço final garante que o último token seja lido inteiro
+    let mut lexer = Lexer::new(format!("{} ", body), &mut symbol_table);
+    let mut tokens = Vec::new();
+
+    loop {
+        match lexer.get_next_token()? {
+            Token::Eof => return Ok(tokens),
+            token => tokens.push(token),
+        }
+    }
+}
@@ -0,0 +1,661 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Error, Formatter},
+};
+
+use crate::{AstNode, OperatorKind, RelopKind, Span, Spanned, Type, format_expression};
+
+// Erro de tipos, com o intervalo do nó quando a AST veio do parser
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeError {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self.span {
+            Some(span) => write!(f, "{} ({})", self.message, span.start),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Verifica os tipos de um programa, seguindo as regras do interpretador:
+//   - `int`, `float` e `char` se convertem entre si nas contas e atribuições,
+//     exceto real para caractere;
+//   - `~`, `&`, `|`, `^`, `<<` e `>>` só aceitam `int` e `char`, e dão `int`;
+//   - comparações dão `bool`, que só se compara por igualdade com outro `bool`
+//     e é o único tipo aceito nas condições;
+//   - vetores só recebem vetores do mesmo tipo de elemento, e registros só
+//     recebem registros do mesmo tipo;
+//   - só vetores são indexados, com índice inteiro, e só registros têm campos;
+//   - `switch` é sobre `int` ou `char`, com rótulos do mesmo tipo e sem repetição;
+//   - globais valem em todas as funções, abaixo dos escopos locais;
+//   - um nome só é declarado uma vez por escopo, inclusive entre as globais;
+//   - o tamanho de uma variável em bytes cabe em 32 bits;
+//   - chamadas respeitam a quantidade e os tipos dos parâmetros, e funções `void`
+//     não são usadas como valor.
+pub fn check_program(program: &AstNode) -> Result<(), TypeError> {
+    let AstNode::Program {
+        globals,
+        functions,
+        kind,
+        body,
+        ..
+    } = program
+    else {
+        return Err(error(
+            format!("Era esperado um programa, recebido {}", program.to_sexp()),
+            None,
+        ));
+    };
+
+    // Funções podem ser chamadas antes da sua definição; nomes de funções, e de
+    // parâmetros numa mesma função, não se repetem
+    let mut signatures = HashMap::new();
+    for function in functions {
+        let AstNode::Function {
+            kind, name, params, ..
+        } = &function.node
+        else {
+            continue;
+        };
+
+        let mut names = HashSet::new();
+        let mut param_kinds = vec![];
+        for param in params {
+            if let AstNode::VarDecl {
+                kind, names: ids, ..
+            } = &param.node
+            {
+                for id in ids {
+                    if !names.insert(id.as_str()) {
+                        return Err(error(
+                            format!("Parâmetro '{}' repetido na função '{}'", id, name),
+                            param.span,
+                        ));
+                    }
+                }
+                param_kinds.push(kind.clone());
+            }
+        }
+
+        if signatures
+            .insert(name.as_str(), (kind.clone(), param_kinds))
+            .is_some()
+        {
+            return Err(error(
+                format!("Função '{}' definida mais de uma vez", name),
+                function.span,
+            ));
+        }
+    }
+
+    let mut checker = TypeChecker {
+        functions: signatures,
+        globals: HashMap::new(),
+        scopes: vec![],
+        returns: kind.clone(),
+    };
+
+    // As globais são vistas por todas as funções e por `main`; cada uma só vê as
+    // declaradas antes dela
+    checker.scopes = vec![HashMap::new()];
+    for global in globals {
+        checker.statement(global)?;
+    }
+    checker.globals = checker.scopes.pop().unwrap_or_default();
+
+    for function in functions {
+        checker.function(function)?;
+    }
+
+    checker.returns = kind.clone();
+    checker.scopes = vec![HashMap::new()];
+    checker.statement(body)
+}
+
+fn error(message: String, span: Option<Span>) -> TypeError {
+    TypeError { message, span }
+}
+
+// Tipo nas mensagens, como "tipo int"; o `Display` de um registro (ou de um
+// vetor de registros) já traz a palavra, como em "tipo P"
+fn described(kind: &Type) -> String {
+    let mut base = kind;
+    while let Type::Array { elem, .. } = base {
+        base = elem;
+    }
+    match base {
+        Type::Record { .. } => kind.to_string(),
+        _ => format!("tipo {}", kind),
+    }
+}
+
+fn is_scalar(kind: &Type) -> bool {
+    matches!(kind, Type::Int | Type::Float | Type::Char)
+}
+
+// Se um valor do tipo `value` pode ser guardado numa variável do tipo `target`
+fn assignable(target: &Type, value: &Type) -> bool {
+    match (target, value) {
+        (Type::Char, Type::Float) => false,
+        (Type::Bool, Type::Bool) => true,
+        (target, value) if is_scalar(target) && is_scalar(value) => true,
+        (Type::Array { .. }, Type::Array { .. }) => target == value,
+        (Type::Record { name, .. }, Type::Record { name: other, .. }) => name == other,
+        _ => false,
+    }
+}
+
+// Valor de um rótulo de `switch`, para achar rótulos repetidos como `1` e `01`
+fn label_value(label: &AstNode) -> Option<i64> {
+    match label {
+        AstNode::Number { value } => value.parse().ok(),
+        AstNode::UnaryOp {
+            op: OperatorKind::Sub,
+            expr,
+        } => label_value(expr).map(i64::wrapping_neg),
+        AstNode::Literal { value } => Some(*value as i64),
+        _ => None,
+    }
+}
+
+// Variável atribuída por um acesso, como `p` em `p.v[0]`
+fn root(node: &AstNode) -> Option<&str> {
+    match node {
+        AstNode::Identifier { name } => Some(name),
+        AstNode::ArrayAccess { array: base, .. } | AstNode::FieldAccess { record: base, .. } => {
+            root(base)
+        }
+        _ => None,
+    }
+}
+
+struct Variable {
+    kind: Type,
+    constant: bool,
+}
+
+struct TypeChecker<'a> {
+    // Tipo de retorno e tipos dos parâmetros de cada função
+    functions: HashMap<&'a str, (Type, Vec<Type>)>,
+    // Declarações globais, vistas depois de todos os escopos da função
+    globals: HashMap<String, Variable>,
+    // Um escopo por bloco aberto na função atual
+    scopes: Vec<HashMap<String, Variable>>,
+    // Tipo de retorno da função atual
+    returns: Type,
+}
+
+impl TypeChecker<'_> {
+    fn function(&mut self, node: &Spanned<AstNode>) -> Result<(), TypeError> {
+        let AstNode::Function {
+            kind, params, body, ..
+        } = &node.node
+        else {
+            return Err(error(
+                format!("Era esperada uma função, recebido {}", node.to_sexp()),
+                node.span,
+            ));
+        };
+
+        // Os parâmetros ficam num escopo próprio, fora do bloco do corpo
+        self.returns = kind.clone();
+        self.scopes = vec![HashMap::new()];
+        for param in params {
+            if let AstNode::VarDecl { kind, names, .. } = &param.node {
+                for name in names {
+                    self.declare(name, kind.clone(), false, param.span)?;
+                }
+            }
+        }
+
+        self.statement(body)
+    }
+
+    fn statement(&mut self, node: &Spanned<AstNode>) -> Result<(), TypeError> {
+        let span = node.span;
+
+        match &node.node {
+            AstNode::Block { decls, stmts } => {
+                self.scopes.push(HashMap::new());
+                let result = decls
+                    .iter()
+                    .chain(stmts)
+                    .try_for_each(|node| self.statement(node));
+                self.scopes.pop();
+                result
+            }
+            AstNode::VarDecl {
+                kind,
+                names,
+                inits,
+                constant,
+            } => {
+                for (name, init) in names.iter().zip(inits) {
+                    if let Some(init) = init {
+                        let value = self.expression(init)?;
+                        self.expect_assignable(kind, &value, init.span)?;
+                    }
+                    self.declare(name, kind.clone(), *constant, span)?;
+                }
+                Ok(())
+            }
+            AstNode::Assignment { id, expr } => {
+                let variable = self.variable(id, span)?;
+                if variable.constant {
+                    return Err(error(format!("Atribuição à constante '{}'", id), span));
+                }
+                let kind = variable.kind.clone();
+                let value = self.expression(expr)?;
+                self.expect_assignable(&kind, &value, expr.span)
+            }
+            AstNode::ArrayAssignment { array, index, expr } => {
+                self.check_constant(array, span)?;
+                let kind = self.element(array, index)?;
+                let value = self.expression(expr)?;
+                self.expect_assignable(&kind, &value, expr.span)
+            }
+            AstNode::FieldAssignment {
+                record,
+                field,
+                expr,
+            } => {
+                self.check_constant(record, span)?;
+                let kind = self.field(record, field, span)?;
+                let value = self.expression(expr)?;
+                self.expect_assignable(&kind, &value, expr.span)
+            }
+            // O valor de uma chamada usada como comando é descartado
+            AstNode::Call { name, args } => self.call(name, args, span).map(|_| ()),
+            AstNode::Return { expr } => {
+                let value = self.expression(expr)?;
+                if self.returns == Type::Void {
+                    return Err(error(
+                        "Função void não pode devolver valor".to_string(),
+                        span,
+                    ));
+                }
+                let returns = self.returns.clone();
+                self.expect_assignable(&returns, &value, expr.span)
+            }
+            AstNode::Break | AstNode::Continue => Ok(()),
+            AstNode::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                self.condition(cond)?;
+                self.statement(then_block)?;
+                match else_block {
+                    Some(else_block) => self.statement(else_block),
+                    None => Ok(()),
+                }
+            }
+            AstNode::While { cond, body } => {
+                self.condition(cond)?;
+                self.statement(body)
+            }
+            AstNode::DoWhile { body, cond } => {
+                self.statement(body)?;
+                self.condition(cond)
+            }
+            AstNode::For {
+                id,
+                start,
+                end,
+                step,
+                body,
+            } => {
+                let variable = self.variable(id, span)?;
+                if variable.constant {
+                    return Err(error(format!("Atribuição à constante '{}'", id), span));
+                }
+                let kind = variable.kind.clone();
+                if !is_scalar(&kind) {
+                    return Err(error(
+                        format!("Variável de controle '{}' do {}", id, described(&kind)),
+                        span,
+                    ));
+                }
+                for bound in [start, end, step] {
+                    let value = self.expression(bound)?;
+                    self.expect_assignable(&kind, &value, bound.span)?;
+                }
+                self.statement(body)
+            }
+            AstNode::Switch {
+                expr,
+                cases,
+                default,
+            } => {
+                let kind = self.expression(expr)?;
+                if !matches!(kind, Type::Int | Type::Char) {
+                    return Err(error(
+                        format!(
+                            "switch sobre o {}, era esperado int ou char",
+                            described(&kind)
+                        ),
+                        expr.span,
+                    ));
+                }
+
+                let mut labels = HashSet::new();
+                for case in cases {
+                    let AstNode::Case { label, body } = &case.node else {
+                        return Err(error(
+                            format!("Era esperado um ramo, recebido {}", case.to_sexp()),
+                            case.span,
+                        ));
+                    };
+
+                    let label_kind = self.expression(label)?;
+                    if label_kind != kind {
+                        return Err(error(
+                            format!(
+                                "Rótulo {} do {} num switch sobre {}",
+                                format_expression(label),
+                                described(&label_kind),
+                                kind
+                            ),
+                            label.span,
+                        ));
+                    }
+                    if !labels.insert(label_value(label)) {
+                        return Err(error(
+                            format!("Rótulo {} repetido no switch", format_expression(label)),
+                            label.span,
+                        ));
+                    }
+
+                    self.statement(body)?;
+                }
+
+                match default {
+                    Some(default) => self.statement(default),
+                    None => Ok(()),
+                }
+            }
+            _ => self.expression(node).map(|_| ()),
+        }
+    }
+
+    // Elementos e campos de uma constante também não podem ser atribuídos
+    fn check_constant(&self, target: &AstNode, span: Option<Span>) -> Result<(), TypeError> {
+        match root(target) {
+            Some(name) if self.variable(name, span)?.constant => {
+                Err(error(format!("Atribuição à constante '{}'", name), span))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn condition(&mut self, cond: &Spanned<AstNode>) -> Result<(), TypeError> {
+        let kind = self.expression(cond)?;
+        if kind == Type::Bool {
+            Ok(())
+        } else {
+            Err(error(
+                format!("Condição do {}, era esperado bool", described(&kind)),
+                cond.span,
+            ))
+        }
+    }
+
+    fn expression(&mut self, node: &Spanned<AstNode>) -> Result<Type, TypeError> {
+        let span = node.span;
+
+        match &node.node {
+            // Só um literal com `.` ou expoente é real; um inteiro que não cabe em 64
+            // bits é erro, como na execução
+            AstNode::Number { value } => match value.parse::<i64>() {
+                Ok(_) => Ok(Type::Int),
+                Err(_) if value.contains(['.', 'E']) => Ok(Type::Float),
+                Err(_) => Err(error(
+                    format!("Inteiro {} não cabe em 64 bits", value),
+                    span,
+                )),
+            },
+            AstNode::Literal { .. } => Ok(Type::Char),
+            AstNode::Bool { .. } => Ok(Type::Bool),
+            AstNode::Identifier { name } => Ok(self.variable(name, span)?.kind.clone()),
+            AstNode::ArrayAccess { array, index } => self.element(array, index),
+            AstNode::FieldAccess { record, field } => self.field(record, field, span),
+            AstNode::Call { name, args } => match self.call(name, args, span)? {
+                Type::Void => Err(error(
+                    format!("Função '{}' é void e não devolve valor", name),
+                    span,
+                )),
+                kind => Ok(kind),
+            },
+            AstNode::UnaryOp {
+                op: OperatorKind::BitNot,
+                expr,
+            } => {
+                let kind = self.expression(expr)?;
+                self.integral(&kind, node, span)?;
+                Ok(Type::Int)
+            }
+            AstNode::UnaryOp { expr, .. } => {
+                let kind = self.expression(expr)?;
+                self.numeric(&kind, node, span)?;
+                Ok(if kind == Type::Float {
+                    Type::Float
+                } else {
+                    Type::Int
+                })
+            }
+            AstNode::BinaryOp { op, left, right } if op.is_bitwise() => {
+                let left = self.expression(left)?;
+                let right = self.expression(right)?;
+                self.integral(&left, node, span)?;
+                self.integral(&right, node, span)?;
+                Ok(Type::Int)
+            }
+            AstNode::BinaryOp { left, right, .. } => {
+                let left = self.expression(left)?;
+                let right = self.expression(right)?;
+                self.numeric(&left, node, span)?;
+                self.numeric(&right, node, span)?;
+                Ok(if left == Type::Float || right == Type::Float {
+                    Type::Float
+                } else {
+                    Type::Int
+                })
+            }
+            AstNode::BinaryComp { relop, left, right } => {
+                let left = self.expression(left)?;
+                let right = self.expression(right)?;
+
+                // Lógicos só se comparam entre si, por igualdade
+                let equality = matches!(relop, RelopKind::EQ | RelopKind::NE);
+                if !(equality && left == Type::Bool && right == Type::Bool) {
+                    self.numeric(&left, node, span)?;
+                    self.numeric(&right, node, span)?;
+                }
+                Ok(Type::Bool)
+            }
+            other => Err(error(
+                format!("Era esperada uma expressão, recebido {}", other.to_sexp()),
+                span,
+            )),
+        }
+    }
+
+    // Tipo dos elementos de `array`, indexado por um inteiro
+    fn element(
+        &mut self,
+        array: &Spanned<AstNode>,
+        index: &Spanned<AstNode>,
+    ) -> Result<Type, TypeError> {
+        let index_kind = self.expression(index)?;
+        if !matches!(index_kind, Type::Int | Type::Char) {
+            return Err(error(
+                format!(
+                    "Índice do {} no vetor '{}'",
+                    described(&index_kind),
+                    format_expression(array)
+                ),
+                index.span,
+            ));
+        }
+
+        match self.expression(array)? {
+            Type::Array { elem, .. } => Ok(*elem),
+            kind => Err(error(
+                format!(
+                    "'{}' não é um vetor, é do {}",
+                    format_expression(array),
+                    described(&kind)
+                ),
+                array.span,
+            )),
+        }
+    }
+
+    // Tipo do campo `field` do registro; `span` é o do acesso inteiro
+    fn field(
+        &mut self,
+        record: &Spanned<AstNode>,
+        field: &str,
+        span: Option<Span>,
+    ) -> Result<Type, TypeError> {
+        match self.expression(record)? {
+            kind @ Type::Record { .. } => match kind.field(field) {
+                Some(found) => Ok(found.kind.clone()),
+                None => Err(error(format!("{} não tem o campo '{}'", kind, field), span)),
+            },
+            kind => Err(error(
+                format!(
+                    "'{}' não é um registro, é do {}",
+                    format_expression(record),
+                    described(&kind)
+                ),
+                record.span,
+            )),
+        }
+    }
+
+    // Confere os argumentos e devolve o tipo de retorno
+    fn call(
+        &mut self,
+        name: &str,
+        args: &[Spanned<AstNode>],
+        span: Option<Span>,
+    ) -> Result<Type, TypeError> {
+        let Some((returns, params)) = self.functions.get(name).cloned() else {
+            return Err(error(format!("Função '{}' não definida", name), span));
+        };
+
+        if params.len() != args.len() {
+            return Err(error(
+                format!(
+                    "Função '{}' espera {} argumento(s), recebeu {}",
+                    name,
+                    params.len(),
+                    args.len()
+                ),
+                span,
+            ));
+        }
+
+        for (param, arg) in params.iter().zip(args) {
+            let value = self.expression(arg)?;
+            match (param, &value) {
+                // Vetores são passados por referência, como em C, então o
+                // tamanho do argumento não precisa ser o do parâmetro
+                (Type::Array { elem, .. }, Type::Array { elem: other, .. }) if elem == other => {}
+                _ => self.expect_assignable(param, &value, arg.span)?,
+            }
+        }
+
+        Ok(returns)
+    }
+
+    fn numeric(&self, kind: &Type, node: &AstNode, span: Option<Span>) -> Result<(), TypeError> {
+        if is_scalar(kind) {
+            Ok(())
+        } else {
+            Err(error(
+                format!(
+                    "Operando do {} em '{}'",
+                    described(kind),
+                    format_expression(node)
+                ),
+                span,
+            ))
+        }
+    }
+
+    // Operandos de `~`, `&`, `|`, `^`, `<<` e `>>`
+    fn integral(&self, kind: &Type, node: &AstNode, span: Option<Span>) -> Result<(), TypeError> {
+        if matches!(kind, Type::Int | Type::Char) {
+            Ok(())
+        } else {
+            Err(error(
+                format!(
+                    "Operando do {} em '{}', era esperado int",
+                    described(kind),
+                    format_expression(node)
+                ),
+                span,
+            ))
+        }
+    }
+
+    fn expect_assignable(
+        &self,
+        target: &Type,
+        value: &Type,
+        span: Option<Span>,
+    ) -> Result<(), TypeError> {
+        if assignable(target, value) {
+            Ok(())
+        } else {
+            Err(error(
+                format!("Valor do {} onde se espera {}", described(value), target),
+                span,
+            ))
+        }
+    }
+
+    // Uma declaração só pode esconder nomes de escopos mais externos, nunca
+    // repetir um do mesmo escopo (o que apagaria um `const`, por exemplo)
+    fn declare(
+        &mut self,
+        name: &str,
+        kind: Type,
+        constant: bool,
+        span: Option<Span>,
+    ) -> Result<(), TypeError> {
+        if kind.size().is_none() {
+            return Err(error(
+                format!("Variável '{}' do {} grande demais", name, described(&kind)),
+                span,
+            ));
+        }
+        if let Some(scope) = self.scopes.last_mut()
+            && scope
+                .insert(name.to_string(), Variable { kind, constant })
+                .is_some()
+        {
+            return Err(error(
+                format!("Variável '{}' já declarada neste escopo", name),
+                span,
+            ));
+        }
+        Ok(())
+    }
+
+    // Procura do escopo mais interno para o mais externo, e por fim nas globais
+    fn variable(&self, name: &str, span: Option<Span>) -> Result<&Variable, TypeError> {
+        self.scopes
+            .iter()
+            .rev()
+            .chain([&self.globals])
+            .find_map(|scope| scope.get(name))
+            .ok_or_else(|| error(format!("Variável '{}' não declarada", name), span))
+    }
+}
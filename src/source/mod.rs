@@ -0,0 +1,300 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Error, Formatter},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{ParserError, Position, TypeError, lexer::comment_open_after};
+
+// Erros ao juntar os arquivos de um programa
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    // Arquivo não pôde ser lido
+    Io { path: PathBuf, message: String },
+    // Linha começa com `importa "` mas não é uma diretiva completa
+    Directive { path: PathBuf, line: usize },
+    // Arquivos que se importam em ciclo, do primeiro de volta a ele mesmo
+    Cycle { chain: Vec<PathBuf> },
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::Io { path, message } => {
+                write!(f, "Não foi possível ler '{}': {}", path.display(), message)
+            }
+            Self::Directive { path, line } => write!(
+                f,
+                "Diretiva 'importa' malformada, era esperado importa \"arquivo\" ({}: linha {})",
+                path.display(),
+                line
+            ),
+            Self::Cycle { chain } => write!(
+                f,
+                "Importação circular: {}",
+                chain
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+        }
+    }
+}
+
+// Posição de um arquivo de origem, traduzida a partir do texto unificado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceLocation<'a> {
+    pub file: &'a Path,
+    pub position: Position,
+}
+
+impl Display for SourceLocation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}: {}", self.file.display(), self.position)
+    }
+}
+
+// Trecho contínuo de linhas de um mesmo arquivo dentro do texto unificado
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    // Primeira linha do trecho no texto unificado
+    line: usize,
+    file: usize,
+    // A mesma linha no arquivo de origem
+    file_line: usize,
+}
+
+// Traduz posições do texto unificado de volta para o arquivo e a linha de origem.
+// As diretivas ocupam linhas inteiras, então as colunas não mudam.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    files: Vec<PathBuf>,
+    segments: Vec<Segment>,
+}
+
+impl SourceMap {
+    // Arquivos na ordem em que foram lidos, começando pelo principal
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn locate(&self, position: Position) -> SourceLocation<'_> {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.line <= position.line);
+
+        match index.checked_sub(1).map(|index| &self.segments[index]) {
+            Some(segment) => SourceLocation {
+                file: &self.files[segment.file],
+                position: Position {
+                    line: segment.file_line + (position.line - segment.line),
+                    column: position.column,
+                },
+            },
+            None => SourceLocation {
+                file: self.files.first().map_or(Path::new(""), PathBuf::as_path),
+                position,
+            },
+        }
+    }
+
+    // Mensagem do erro do sintático com o arquivo e a linha de origem
+    pub fn describe_parser_error(&self, error: &ParserError) -> String {
+        match error {
+            // O léxico já escreve `linha: N` na mensagem, então só ela é trocada
+            ParserError::Lexical(message) => message
+                .lines()
+                .map(|line| {
+                    match line
+                        .trim_start()
+                        .strip_prefix("linha: ")
+                        .and_then(|line| line.parse().ok())
+                    {
+                        Some(line) => {
+                            let location = self.locate(Position { line, column: 1 });
+                            format!(
+                                " arquivo: {}\n linha: {}",
+                                location.file.display(),
+                                location.position.line
+                            )
+                        }
+                        None => line.to_string(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ParserError::Syntax { span, .. } | ParserError::Internal { span, .. } => format!(
+                "{} ({})",
+                self.relocate_tokens(&error.message()),
+                self.locate(span.start)
+            ),
+        }
+    }
+
+    // Troca o `line=N` dos tokens citados na mensagem pela linha no arquivo de origem
+    fn relocate_tokens(&self, message: &str) -> String {
+        let mut parts = message.split("line=");
+        let mut relocated = parts.next().unwrap_or_default().to_string();
+
+        for part in parts {
+            let digits = part
+                .find(|ch: char| !ch.is_ascii_digit())
+                .unwrap_or(part.len());
+            relocated.push_str("line=");
+            match part[..digits].parse() {
+                Ok(line) => {
+                    let location = self.locate(Position { line, column: 1 });
+                    relocated.push_str(&location.position.line.to_string());
+                    relocated.push_str(&part[digits..]);
+                }
+                Err(_) => relocated.push_str(part),
+            }
+        }
+
+        relocated
+    }
+
+    // Mensagem do erro de tipos com o arquivo e a linha de origem
+    pub fn describe_type_error(&self, error: &TypeError) -> String {
+        match error.span {
+            Some(span) => format!("{} ({})", error.message, self.locate(span.start)),
+            None => error.message.clone(),
+        }
+    }
+}
+
+// Lê o programa em `path` e os arquivos que ele importa, devolvendo o texto
+// unificado que vai para o `Parser` e o mapa de volta para cada arquivo
+pub fn load_program(path: impl AsRef<Path>) -> Result<(String, SourceMap), ImportError> {
+    load_program_with(path, |path| fs::read_to_string(path))
+}
+
+// Como `load_program`, mas lendo cada arquivo com `read`.
+// Uma linha `importa "arquivo"` fora de comentários `{% %}` é trocada pelo
+// conteúdo do arquivo, procurado a partir da pasta de quem importa; ele entra no lugar da diretiva, então deve
+// trazer declarações que caibam ali. Cada arquivo entra uma única vez e as
+// importações seguintes dele são ignoradas.
+pub fn load_program_with(
+    path: impl AsRef<Path>,
+    read: impl FnMut(&Path) -> io::Result<String>,
+) -> Result<(String, SourceMap), ImportError> {
+    let mut loader = Loader {
+        read,
+        contents: String::new(),
+        line: 1,
+        map: SourceMap::default(),
+        stack: Vec::new(),
+        loaded: HashSet::new(),
+    };
+    loader.include(normalize(path.as_ref()))?;
+
+    Ok((loader.contents, loader.map))
+}
+
+struct Loader<R> {
+    read: R,
+    contents: String,
+    // Próxima linha do texto unificado
+    line: usize,
+    map: SourceMap,
+    // Arquivos sendo lidos, do principal até o atual
+    stack: Vec<PathBuf>,
+    loaded: HashSet<PathBuf>,
+}
+
+impl<R: FnMut(&Path) -> io::Result<String>> Loader<R> {
+    fn include(&mut self, path: PathBuf) -> Result<(), ImportError> {
+        if let Some(index) = self.stack.iter().position(|open| *open == path) {
+            let mut chain = self.stack[index..].to_vec();
+            chain.push(path);
+            return Err(ImportError::Cycle { chain });
+        }
+        if !self.loaded.insert(path.clone()) {
+            return Ok(());
+        }
+
+        let text = (self.read)(&path).map_err(|error| ImportError::Io {
+            path: path.clone(),
+            message: error.to_string(),
+        })?;
+        let file = self.map.files.len();
+        self.map.files.push(path.clone());
+        self.stack.push(path.clone());
+
+        // Um novo trecho começa no início do arquivo e depois de cada importação
+        let mut resume = true;
+        let mut in_comment = false;
+        for (index, text_line) in text.lines().enumerate() {
+            if resume {
+                self.map.segments.push(Segment {
+                    line: self.line,
+                    file,
+                    file_line: index + 1,
+                });
+                resume = false;
+            }
+
+            let line_directive = if in_comment {
+                None
+            } else {
+                directive(text_line)
+            };
+            match line_directive {
+                Some(Some(name)) => {
+                    let parent = path.parent().unwrap_or(Path::new(""));
+                    self.include(normalize(&parent.join(name)))?;
+                    resume = true;
+                }
+                Some(None) => {
+                    return Err(ImportError::Directive {
+                        path,
+                        line: index + 1,
+                    });
+                }
+                None => {
+                    in_comment = comment_open_after(text_line, in_comment);
+                    self.contents.push_str(text_line);
+                    self.contents.push('\n');
+                    self.line += 1;
+                }
+            }
+        }
+
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+// `None` se a linha não é uma diretiva, `Some(None)` se está malformada
+fn directive(line: &str) -> Option<Option<&str>> {
+    let rest = line.trim().strip_prefix("importa")?.trim_start();
+    let name = rest.strip_prefix('"')?;
+
+    Some(
+        name.strip_suffix('"')
+            .filter(|name| !name.is_empty() && !name.contains('"')),
+    )
+}
+
+// Resolve `.` e `..` sem acessar o disco, para comparar caminhos na detecção de ciclos
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
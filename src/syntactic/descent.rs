@@ -0,0 +1,960 @@
+use crate::{ActionKind, AstNode, OperatorKind, ParserError, Position, Spanned, Token, TokenType};
+
+use super::{ParseTable, Parser};
+
+// Nó da AST junto com os intervalos dele
+type Parsed = Spanned<AstNode>;
+
+// FIRST de <tipo>
+const TYPES: [TokenType; 6] = [
+    TokenType::CharKeyword,
+    TokenType::IntKeyword,
+    TokenType::FloatKeyword,
+    TokenType::VoidKeyword,
+    TokenType::BoolKeyword,
+    TokenType::TypeKeyword,
+];
+
+// FIRST de <comando>, que é o mesmo de <cmd_ou_bloco>
+const COMMANDS: [TokenType; 10] = [
+    TokenType::Id,
+    TokenType::IfKeyword,
+    TokenType::WhileKeyword,
+    TokenType::DoKeyword,
+    TokenType::ForKeyword,
+    TokenType::ReturnKeyword,
+    TokenType::BreakKeyword,
+    TokenType::ContinueKeyword,
+    TokenType::SwitchKeyword,
+    TokenType::BeginBlockPunctuation,
+];
+
+// Analisador descendente recursivo, usado no lugar da tabela quando o parser é
+// criado `with_recursive_descent`. Cada não terminal da gramática vira uma
+// função que escolhe a produção pelo token atual, e os nós saem das mesmas
+// ações semânticas da tabela, executadas no mesmo ponto da entrada: a AST, os
+// intervalos e a posição dos erros são os mesmos, mas a mensagem diz o que era
+// esperado ali. As expressões ficam com o analisador por precedência.
+//
+// As funções dos não terminais com apóstrofo recebem `origin`, a posição de onde
+// começa o nó que elas continuam, que a tabela guarda junto das ações na pilha.
+// As listas, recursivas à direita na gramática, são lidas num laço e montadas no
+// fim, com a origem que cada `AppendList` teria na pilha.
+impl Parser<'_> {
+    pub(super) fn parse_descent(&mut self) -> Result<Parsed, ParserError> {
+        self.loops = 0;
+        let mut token = self.next_token()?;
+
+        let parsed = match self.parse_table.start_symbol.as_str() {
+            "inicio" => self.inicio(&mut token)?,
+            "bloco" => self.bloco(&mut token)?,
+            "comando" => self.comando(&mut token)?,
+            "E" | "cond" => self.descent_expression(&mut token)?,
+            symbol => {
+                return Err(ParserError::Syntax {
+                    message: format!(
+                        "Símbolo inicial {:?} não é aceito pelo analisador descendente recursivo",
+                        symbol
+                    ),
+                    span: self.current_span,
+                });
+            }
+        };
+
+        if token != Token::Eof {
+            return Err(self.unexpected(&token, "o fim do arquivo"));
+        }
+        Ok(parsed)
+    }
+
+    /**
+     <inicio> -> <tipo> <global>
+     <inicio> -> const <tipo> id <decl'> <globais>
+     <inicio> -> tipo id <inicio_tipo>
+    */
+    fn inicio(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::TypeKeyword => {
+                self.advance(token)?;
+                let name = self.terminal(token, TokenType::Id, "com o nome do registro")?;
+                self.inicio_tipo(token, name)
+            }
+            TokenType::ConstKeyword => self.const_global(token, start),
+            terminal if TYPES.contains(&terminal) => {
+                let kind = self.tipo(token)?;
+                self.global(token, kind)
+            }
+            _ => Err(self.unexpected(
+                token,
+                "um registro, uma declaração global, uma função ou main",
+            )),
+        }
+    }
+
+    /**
+     <inicio_tipo> -> [ <decls> ] <inicio>
+     <inicio_tipo> -> <global>
+    */
+    fn inicio_tipo(&mut self, token: &mut Token, name: Parsed) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::BeginBlockPunctuation => {
+                self.advance(token)?;
+                let decls = self.decls(token)?;
+                self.consume(token, TokenType::EndBlockPunctuation, "no fim dos campos")?;
+                let record = self.reduce(ActionKind::CreateRecord, start, vec![name, decls])?;
+                let program = self.inicio(token)?;
+                self.reduce(ActionKind::AddRecord, start, vec![record, program])
+            }
+            TokenType::MainKeyword | TokenType::Id => {
+                let kind = self.reduce(ActionKind::RecordType, start, vec![name])?;
+                self.global(token, kind)
+            }
+            _ => Err(self.unexpected(token, "'[' com os campos do registro, ou um nome")),
+        }
+    }
+
+    /**
+     <global> -> main ( ) <bloco>
+     <global> -> id <global'>
+    */
+    fn global(&mut self, token: &mut Token, kind: Parsed) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::MainKeyword => self.main(token, start, kind),
+            TokenType::Id => {
+                let name = self.leaf(token)?;
+                self.global_rest(token, start, kind, name)
+            }
+            _ => Err(self.unexpected(token, "main ou um nome depois do tipo")),
+        }
+    }
+
+    /**
+     <global'> -> ( <params> ) <bloco> <funcoes>
+     <global'> -> <decl'> <globais>
+    */
+    fn global_rest(
+        &mut self,
+        token: &mut Token,
+        origin: Position,
+        kind: Parsed,
+        name: Parsed,
+    ) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::LParenOperator => self.function(token, origin, kind, name),
+            TokenType::BeginBlockPunctuation
+            | TokenType::AssignPunctuation
+            | TokenType::CommaPunctuation
+            | TokenType::SemiColonPunctuation => {
+                let decl = self.decl_rest(token, start, kind, name)?;
+                let program = self.globais(token)?;
+                self.reduce(ActionKind::AddGlobal, origin, vec![decl, program])
+            }
+            _ => Err(self.unexpected(token, "'(' com os parâmetros, ou o resto da declaração")),
+        }
+    }
+
+    /**
+     <globais> -> <tipo> <global>
+     <globais> -> const <tipo> id <decl'> <globais>
+    */
+    fn globais(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::ConstKeyword => self.const_global(token, start),
+            terminal if TYPES.contains(&terminal) => {
+                let kind = self.tipo(token)?;
+                self.global(token, kind)
+            }
+            _ => Err(self.unexpected(token, "uma declaração global, uma função ou main")),
+        }
+    }
+
+    // const <tipo> id <decl'> {MarkConst} <globais> {AddGlobal}
+    fn const_global(&mut self, token: &mut Token, origin: Position) -> Result<Parsed, ParserError> {
+        let decl = self.const_decl(token, origin)?;
+        let program = self.globais(token)?;
+        self.reduce(ActionKind::AddGlobal, origin, vec![decl, program])
+    }
+
+    /**
+     <funcoes> -> <tipo> <inicio'>
+     <inicio'> -> main ( ) <bloco>
+     <inicio'> -> id ( <params> ) <bloco> <funcoes>
+    */
+    fn funcoes(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        if !TYPES.contains(&Self::peek(token)) {
+            return Err(self.unexpected(token, "outra função ou main"));
+        }
+        let kind = self.tipo(token)?;
+
+        match Self::peek(token) {
+            TokenType::MainKeyword => self.main(token, start, kind),
+            TokenType::Id => {
+                let name = self.leaf(token)?;
+                self.function(token, start, kind, name)
+            }
+            _ => Err(self.unexpected(token, "main ou o nome da função")),
+        }
+    }
+
+    // main ( ) <bloco> {CreateProgram}
+    fn main(
+        &mut self,
+        token: &mut Token,
+        origin: Position,
+        kind: Parsed,
+    ) -> Result<Parsed, ParserError> {
+        self.advance(token)?;
+        self.consume(token, TokenType::LParenOperator, "depois de main")?;
+        self.consume(
+            token,
+            TokenType::RParenOperator,
+            "em main(), que não tem parâmetros",
+        )?;
+        let body = self.bloco(token)?;
+        self.reduce(ActionKind::CreateProgram, origin, vec![kind, body])
+    }
+
+    // ( <params> ) <bloco> {CreateFunction} <funcoes> {AddFunction}
+    fn function(
+        &mut self,
+        token: &mut Token,
+        origin: Position,
+        kind: Parsed,
+        name: Parsed,
+    ) -> Result<Parsed, ParserError> {
+        self.consume(token, TokenType::LParenOperator, "depois do nome da função")?;
+        let params = self.params(token)?;
+        self.consume(token, TokenType::RParenOperator, "no fim dos parâmetros")?;
+        let body = self.bloco(token)?;
+        let function = self.reduce(
+            ActionKind::CreateFunction,
+            origin,
+            vec![kind, name, params, body],
+        )?;
+        let program = self.funcoes(token)?;
+        self.reduce(ActionKind::AddFunction, origin, vec![function, program])
+    }
+
+    /**
+     <params> -> <param> <params'> | ε
+     <params'> -> , <param> <params'> | ε
+    */
+    fn params(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::RParenOperator => return self.list(vec![], start),
+            terminal if TYPES.contains(&terminal) => {}
+            _ => return Err(self.unexpected(token, "um parâmetro ou ')'")),
+        }
+
+        let mut params = vec![(start, self.param(token)?)];
+        let mut origin = start;
+        while Self::peek(token) == TokenType::CommaPunctuation {
+            let next = self.current_span.start;
+            self.advance(token)?;
+            if !TYPES.contains(&Self::peek(token)) {
+                return Err(self.unexpected(token, "outro parâmetro depois de ','"));
+            }
+            params.push((origin, self.param(token)?));
+            origin = next;
+        }
+
+        self.list(params, origin)
+    }
+
+    /**
+     <param> -> <tipo> id <param'>
+     <param'> -> [ num ] | ε
+    */
+    fn param(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+        let kind = self.tipo(token)?;
+        let name = self.terminal(token, TokenType::Id, "com o nome do parâmetro")?;
+
+        match Self::peek(token) {
+            TokenType::BeginBlockPunctuation => {
+                let size = self.array_size(token)?;
+                self.reduce(ActionKind::CreateArrayDecl, start, vec![kind, name, size])
+            }
+            TokenType::CommaPunctuation | TokenType::RParenOperator => {
+                self.reduce(ActionKind::CreateParam, start, vec![kind, name])
+            }
+            _ => Err(self.unexpected(token, "'[', ',' ou ')' depois do parâmetro")),
+        }
+    }
+
+    /**
+     <tipo> -> float | int | char | bool | void | tipo id
+    */
+    fn tipo(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::TypeKeyword => {
+                self.advance(token)?;
+                let name = self.terminal(token, TokenType::Id, "com o nome do registro")?;
+                self.reduce(ActionKind::RecordType, start, vec![name])
+            }
+            terminal if TYPES.contains(&terminal) => self.leaf(token),
+            _ => Err(self.unexpected(token, "um tipo")),
+        }
+    }
+
+    /**
+     <bloco> -> [ <decls> <comandos> ]
+    */
+    fn bloco(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        self.consume(
+            token,
+            TokenType::BeginBlockPunctuation,
+            "no início do bloco",
+        )?;
+        let decls = self.decls(token)?;
+        let commands = self.comandos(token)?;
+        self.consume(token, TokenType::EndBlockPunctuation, "no fim do bloco")?;
+        self.reduce(ActionKind::CreateBlock, start, vec![decls, commands])
+    }
+
+    /**
+     <decls> -> <decl> <decls> | ε
+    */
+    fn decls(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let mut decls = vec![];
+        while TYPES.contains(&Self::peek(token)) || Self::peek(token) == TokenType::ConstKeyword {
+            let start = self.current_span.start;
+            decls.push((start, self.decl(token)?));
+        }
+
+        self.list(decls, self.current_span.start)
+    }
+
+    /**
+     <decl> -> <tipo> id <decl'>
+     <decl> -> const <tipo> id <decl'>
+    */
+    fn decl(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        if Self::peek(token) == TokenType::ConstKeyword {
+            return self.const_decl(token, start);
+        }
+        let kind = self.tipo(token)?;
+        let name = self.terminal(token, TokenType::Id, "com o nome da variável")?;
+        self.decl_rest(token, start, kind, name)
+    }
+
+    // const <tipo> id <decl'> {MarkConst}
+    fn const_decl(&mut self, token: &mut Token, origin: Position) -> Result<Parsed, ParserError> {
+        self.advance(token)?;
+        let kind = self.tipo(token)?;
+        let name = self.terminal(token, TokenType::Id, "com o nome da constante")?;
+        let decl = self.decl_rest(token, origin, kind, name)?;
+        self.reduce(ActionKind::MarkConst, origin, vec![decl])
+    }
+
+    /**
+     <decl'> -> [ num ] ;
+     <decl'> -> <valor_inicial> <lista_ids'> ;
+     <lista_ids'> -> , id <valor_inicial> <lista_ids'> | ε
+    */
+    fn decl_rest(
+        &mut self,
+        token: &mut Token,
+        origin: Position,
+        kind: Parsed,
+        name: Parsed,
+    ) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::BeginBlockPunctuation => {
+                let size = self.array_size(token)?;
+                self.consume(
+                    token,
+                    TokenType::SemiColonPunctuation,
+                    "no fim da declaração",
+                )?;
+                self.reduce(ActionKind::CreateArrayDecl, origin, vec![kind, name, size])
+            }
+            TokenType::AssignPunctuation
+            | TokenType::CommaPunctuation
+            | TokenType::SemiColonPunctuation => {
+                let first = self.valor_inicial(token, name)?;
+
+                let mut names = vec![];
+                let mut names_origin = start;
+                while Self::peek(token) == TokenType::CommaPunctuation {
+                    let next = self.current_span.start;
+                    self.advance(token)?;
+                    let name = self.terminal(token, TokenType::Id, "depois de ','")?;
+                    names.push((names_origin, self.valor_inicial(token, name)?));
+                    names_origin = next;
+                }
+                if Self::peek(token) != TokenType::SemiColonPunctuation {
+                    return Err(self.unexpected(token, "',' ou ';' na declaração"));
+                }
+
+                let rest = self.list(names, names_origin)?;
+                let names = self.reduce(ActionKind::AppendList, origin, vec![first, rest])?;
+                self.advance(token)?;
+                self.reduce(ActionKind::CreateDecl, origin, vec![kind, names])
+            }
+            _ => Err(self.unexpected(token, "':=', ',', ';' ou '[' depois do nome")),
+        }
+    }
+
+    /**
+     <valor_inicial> -> := <E> | ε
+    */
+    fn valor_inicial(&mut self, token: &mut Token, name: Parsed) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        if Self::peek(token) != TokenType::AssignPunctuation {
+            return Ok(name);
+        }
+        self.advance(token)?;
+        let value = self.descent_expression(token)?;
+        self.reduce(ActionKind::Assign, start, vec![name, value])
+    }
+
+    // [ num ], o tamanho de um vetor
+    fn array_size(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        self.advance(token)?;
+        let size = self.terminal(token, TokenType::Number, "com o tamanho do vetor")?;
+        self.consume(
+            token,
+            TokenType::EndBlockPunctuation,
+            "depois do tamanho do vetor",
+        )?;
+        Ok(size)
+    }
+
+    /**
+     <comandos> -> <comando> <comandos> | ε
+    */
+    fn comandos(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let mut commands = vec![];
+        while COMMANDS.contains(&Self::peek(token)) {
+            let start = self.current_span.start;
+            commands.push((start, self.comando(token)?));
+        }
+
+        self.list(commands, self.current_span.start)
+    }
+
+    /**
+     <comando> -> <cmd_atrib> | <cmd_if> | <cmd_while> | <cmd_do> | <cmd_for> | <cmd_switch> | <cmd_return> | <bloco>
+     <comando> -> break ; | continue ;
+    */
+    fn comando(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::Id => self.cmd_atrib(token),
+            TokenType::IfKeyword => self.cmd_if(token),
+            TokenType::WhileKeyword => self.cmd_while(token),
+            TokenType::DoKeyword => self.cmd_do(token),
+            TokenType::ForKeyword => self.cmd_for(token),
+            TokenType::SwitchKeyword => self.cmd_switch(token),
+            TokenType::ReturnKeyword => {
+                self.advance(token)?;
+                let value = self.descent_expression(token)?;
+                self.consume(
+                    token,
+                    TokenType::SemiColonPunctuation,
+                    "depois do valor de return",
+                )?;
+                self.reduce(ActionKind::CreateReturn, start, vec![value])
+            }
+            TokenType::BreakKeyword | TokenType::ContinueKeyword => {
+                let kind = match Self::peek(token) {
+                    TokenType::BreakKeyword => ActionKind::CreateBreak,
+                    _ => ActionKind::CreateContinue,
+                };
+                let keyword = self.advance(token)?;
+                self.consume(
+                    token,
+                    TokenType::SemiColonPunctuation,
+                    &format!("depois de {}", keyword.lexeme()),
+                )?;
+                self.reduce(kind, start, vec![])
+            }
+            TokenType::BeginBlockPunctuation => self.bloco(token),
+            _ => Err(self.unexpected(token, "um comando")),
+        }
+    }
+
+    /**
+     <cmd_ou_bloco> -> <comando> | <bloco>
+    */
+    fn cmd_ou_bloco(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        if !COMMANDS.contains(&Self::peek(token)) {
+            return Err(self.unexpected(token, "um comando ou um bloco"));
+        }
+        self.comando(token)
+    }
+
+    /**
+     <cmd_atrib> -> id <cmd_atrib'>
+     <cmd_atrib'> -> := <E> ;
+     <cmd_atrib'> -> <atrib_op>
+     <cmd_atrib'> -> ( <args> ) ;
+     <cmd_atrib'> -> <acesso> <acessos> <atrib_acesso>
+     <atrib_acesso> -> := <E> ; | <atrib_op>
+    */
+    fn cmd_atrib(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+        let name = self.leaf(token)?;
+
+        match Self::peek(token) {
+            TokenType::AssignPunctuation => {
+                self.advance(token)?;
+                let value = self.descent_expression(token)?;
+                self.consume(
+                    token,
+                    TokenType::SemiColonPunctuation,
+                    "no fim da atribuição",
+                )?;
+                self.reduce(ActionKind::Assign, start, vec![name, value])
+            }
+            terminal if ParseTable::ATRIB_OPS.contains(&terminal) => self.atrib_op(token, name),
+            TokenType::LParenOperator => {
+                self.advance(token)?;
+                let args = self.args(token)?;
+                self.consume(token, TokenType::RParenOperator, "no fim dos argumentos")?;
+                self.consume(token, TokenType::SemiColonPunctuation, "depois da chamada")?;
+                self.reduce(ActionKind::CreateCall, start, vec![name, args])
+            }
+            TokenType::BeginBlockPunctuation | TokenType::DotPunctuation => {
+                let mut target = name;
+                while matches!(
+                    Self::peek(token),
+                    TokenType::BeginBlockPunctuation | TokenType::DotPunctuation
+                ) {
+                    target = self.acesso(token, target)?;
+                }
+
+                let start = self.current_span.start;
+                match Self::peek(token) {
+                    TokenType::AssignPunctuation => {
+                        self.advance(token)?;
+                        let value = self.descent_expression(token)?;
+                        self.consume(
+                            token,
+                            TokenType::SemiColonPunctuation,
+                            "no fim da atribuição",
+                        )?;
+                        self.reduce(ActionKind::AssignAccess, start, vec![target, value])
+                    }
+                    terminal if ParseTable::ATRIB_OPS.contains(&terminal) => {
+                        self.atrib_op(token, target)
+                    }
+                    _ => Err(self.unexpected(token, "uma atribuição ao elemento ou campo")),
+                }
+            }
+            _ => Err(self.unexpected(token, "uma atribuição ou uma chamada")),
+        }
+    }
+
+    /**
+     <acesso> -> [ <E> ] | . id
+    */
+    fn acesso(&mut self, token: &mut Token, target: Parsed) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        if Self::peek(token) == TokenType::BeginBlockPunctuation {
+            self.advance(token)?;
+            let index = self.descent_expression(token)?;
+            self.consume(token, TokenType::EndBlockPunctuation, "depois do índice")?;
+            self.reduce(ActionKind::CreateArrayAccess, start, vec![target, index])
+        } else {
+            self.advance(token)?;
+            let field = self.terminal(token, TokenType::Id, "com o nome do campo")?;
+            self.reduce(ActionKind::CreateFieldAccess, start, vec![target, field])
+        }
+    }
+
+    /**
+     <atrib_op> -> += <E> ; | -= <E> ; | *= <E> ; | /= <E> ;
+     <atrib_op> -> ++ ; | -- ;
+    */
+    fn atrib_op(&mut self, token: &mut Token, target: Parsed) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+        let operator = self.advance(token)?;
+
+        let op = |kind| match kind {
+            TokenType::AddAssignPunctuation => OperatorKind::Sum,
+            TokenType::SubAssignPunctuation => OperatorKind::Sub,
+            TokenType::MultAssignPunctuation => OperatorKind::Mult,
+            TokenType::DivAssignPunctuation => OperatorKind::Div,
+            kind => unreachable!("Atribuição composta fora da tabela: {}", kind),
+        };
+        let (kind, nodes) = match Self::peek(&operator) {
+            TokenType::IncrementPunctuation => {
+                (ActionKind::AssignStep(OperatorKind::Sum), vec![target])
+            }
+            TokenType::DecrementPunctuation => {
+                (ActionKind::AssignStep(OperatorKind::Sub), vec![target])
+            }
+            kind => {
+                let value = self.descent_expression(token)?;
+                (ActionKind::AssignOp(op(kind)), vec![target, value])
+            }
+        };
+
+        self.consume(
+            token,
+            TokenType::SemiColonPunctuation,
+            &format!("depois de {}", operator.lexeme()),
+        )?;
+        self.reduce(kind, start, nodes)
+    }
+
+    /**
+     <args> -> <E> <args'> | ε
+     <args'> -> , <E> <args'> | ε
+    */
+    fn args(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        if Self::peek(token) == TokenType::RParenOperator {
+            return self.list(vec![], start);
+        }
+
+        let mut args = vec![(start, self.descent_expression(token)?)];
+        let mut origin = start;
+        while Self::peek(token) == TokenType::CommaPunctuation {
+            let next = self.current_span.start;
+            self.advance(token)?;
+            args.push((origin, self.descent_expression(token)?));
+            origin = next;
+        }
+
+        self.list(args, origin)
+    }
+
+    /**
+     <cmd_if> -> if ( <cond> ) then <cmd_ou_bloco> <cmd_if'>
+    */
+    fn cmd_if(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        self.advance(token)?;
+        let (cond, then) = self.branch(token, "if")?;
+        self.cmd_if_rest(token, start, cond, then)
+    }
+
+    /**
+     <cmd_if'> -> elsif ( <cond> ) then <cmd_ou_bloco> <cmd_if'>
+     <cmd_if'> -> else <cmd_ou_bloco>
+     <cmd_if'> -> ε
+    */
+    fn cmd_if_rest(
+        &mut self,
+        token: &mut Token,
+        origin: Position,
+        cond: Parsed,
+        then: Parsed,
+    ) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::ElsifKeyword => {
+                self.advance(token)?;
+                let (elsif_cond, elsif_then) = self.branch(token, "elsif")?;
+                let otherwise = self.cmd_if_rest(token, start, elsif_cond, elsif_then)?;
+                self.reduce(
+                    ActionKind::CreateIfElse,
+                    origin,
+                    vec![cond, then, otherwise],
+                )
+            }
+            TokenType::ElseKeyword => {
+                self.advance(token)?;
+                let otherwise = self.cmd_ou_bloco(token)?;
+                self.reduce(
+                    ActionKind::CreateIfElse,
+                    origin,
+                    vec![cond, then, otherwise],
+                )
+            }
+            _ => self.reduce(ActionKind::CreateIf, origin, vec![cond, then]),
+        }
+    }
+
+    // ( <cond> ) then <cmd_ou_bloco>, depois de `if` ou `elsif`
+    fn branch(
+        &mut self,
+        token: &mut Token,
+        keyword: &str,
+    ) -> Result<(Parsed, Parsed), ParserError> {
+        self.consume(
+            token,
+            TokenType::LParenOperator,
+            &format!("depois de {}", keyword),
+        )?;
+        let cond = self.descent_expression(token)?;
+        self.consume(token, TokenType::RParenOperator, "depois da condição")?;
+        self.consume(
+            token,
+            TokenType::ThenKeyword,
+            &format!("depois da condição do {}", keyword),
+        )?;
+        let then = self.cmd_ou_bloco(token)?;
+
+        Ok((cond, then))
+    }
+
+    /**
+     <cmd_while> -> while ( <cond> ) do <cmd_ou_bloco>
+    */
+    fn cmd_while(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        self.advance(token)?;
+        self.consume(token, TokenType::LParenOperator, "depois de while")?;
+        let cond = self.descent_expression(token)?;
+        self.consume(token, TokenType::RParenOperator, "depois da condição")?;
+        self.consume(token, TokenType::DoKeyword, "depois da condição do while")?;
+        let body = self.loop_body(token)?;
+        self.reduce(ActionKind::CreateWhile, start, vec![cond, body])
+    }
+
+    /**
+     <cmd_do> -> do <cmd_ou_bloco> while ( <cond> ) ;
+    */
+    fn cmd_do(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        self.advance(token)?;
+        let body = self.loop_body(token)?;
+        self.consume(token, TokenType::WhileKeyword, "depois do corpo do do")?;
+        self.consume(token, TokenType::LParenOperator, "depois de while")?;
+        let cond = self.descent_expression(token)?;
+        self.consume(token, TokenType::RParenOperator, "depois da condição")?;
+        self.consume(token, TokenType::SemiColonPunctuation, "no fim do do-while")?;
+        self.reduce(ActionKind::CreateDoWhile, start, vec![body, cond])
+    }
+
+    /**
+     <cmd_for> -> for ( id ; <E> ; <E> ; <E> ) <cmd_ou_bloco>
+    */
+    fn cmd_for(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        self.advance(token)?;
+        self.consume(token, TokenType::LParenOperator, "depois de for")?;
+        let var = self.terminal(token, TokenType::Id, "com a variável de controle")?;
+        self.consume(
+            token,
+            TokenType::SemiColonPunctuation,
+            "depois da variável de controle",
+        )?;
+        let from = self.descent_expression(token)?;
+        self.consume(
+            token,
+            TokenType::SemiColonPunctuation,
+            "depois do valor inicial",
+        )?;
+        let to = self.descent_expression(token)?;
+        self.consume(
+            token,
+            TokenType::SemiColonPunctuation,
+            "depois do valor final",
+        )?;
+        let step = self.descent_expression(token)?;
+        self.consume(token, TokenType::RParenOperator, "depois do passo")?;
+        let body = self.loop_body(token)?;
+        self.reduce(
+            ActionKind::CreateFor,
+            start,
+            vec![var, from, to, step, body],
+        )
+    }
+
+    // Corpo de um laço, onde `break` e `continue` são aceitos
+    fn loop_body(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        self.loops += 1;
+        let body = self.cmd_ou_bloco(token)?;
+        self.loops -= 1;
+
+        Ok(body)
+    }
+
+    /**
+     <cmd_switch> -> switch ( <E> ) [ <casos> <padrao> ]
+     <casos> -> case <rotulo> : <cmd_ou_bloco> <casos> | ε
+     <padrao> -> default : <cmd_ou_bloco> | ε
+    */
+    fn cmd_switch(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        self.advance(token)?;
+        self.consume(token, TokenType::LParenOperator, "depois de switch")?;
+        let value = self.descent_expression(token)?;
+        self.consume(
+            token,
+            TokenType::RParenOperator,
+            "depois do valor do switch",
+        )?;
+        self.consume(token, TokenType::BeginBlockPunctuation, "antes dos casos")?;
+
+        let mut cases = vec![];
+        while Self::peek(token) == TokenType::CaseKeyword {
+            let case_start = self.current_span.start;
+            self.advance(token)?;
+            let label = self.rotulo(token)?;
+            self.consume(token, TokenType::ColonPunctuation, "depois do rótulo")?;
+            let body = self.cmd_ou_bloco(token)?;
+            let case = self.reduce(ActionKind::CreateCase, case_start, vec![label, body])?;
+            cases.push((case_start, case));
+        }
+        let cases = self.list(cases, self.current_span.start)?;
+
+        let default_start = self.current_span.start;
+        let default = match Self::peek(token) {
+            TokenType::DefaultKeyword => {
+                self.advance(token)?;
+                self.consume(token, TokenType::ColonPunctuation, "depois de default")?;
+                let body = self.cmd_ou_bloco(token)?;
+                let list = self.reduce(ActionKind::MakeList, default_start, vec![])?;
+                self.reduce(ActionKind::AppendList, default_start, vec![body, list])?
+            }
+            TokenType::EndBlockPunctuation => {
+                self.reduce(ActionKind::MakeList, default_start, vec![])?
+            }
+            _ => return Err(self.unexpected(token, "case, default ou ']'")),
+        };
+
+        self.consume(token, TokenType::EndBlockPunctuation, "no fim do switch")?;
+        self.reduce(ActionKind::CreateSwitch, start, vec![value, cases, default])
+    }
+
+    /**
+     <rotulo> -> num | caractere | - num
+    */
+    fn rotulo(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let start = self.current_span.start;
+
+        match Self::peek(token) {
+            TokenType::Number | TokenType::CharValue => self.leaf(token),
+            TokenType::MinusOperator => {
+                self.advance(token)?;
+                let value = self.terminal(token, TokenType::Number, "depois de '-' no rótulo")?;
+                self.reduce(
+                    ActionKind::CreateUnaryOp(OperatorKind::Sub),
+                    start,
+                    vec![value],
+                )
+            }
+            _ => Err(self.unexpected(token, "um número ou caractere como rótulo")),
+        }
+    }
+
+    // <E> pelo analisador por precedência, sem a árvore de derivação
+    fn descent_expression(&mut self, token: &mut Token) -> Result<Parsed, ParserError> {
+        let parsed = self.pratt_expression(token);
+        self.expression_nodes.clear();
+        parsed
+    }
+
+    // Executa a ação da tabela sobre `nodes`, na ordem em que seriam empilhados
+    fn reduce(
+        &mut self,
+        kind: ActionKind,
+        origin: Position,
+        nodes: Vec<Parsed>,
+    ) -> Result<Parsed, ParserError> {
+        self.check_loop_control(&kind, origin)?;
+
+        let mut ast_stack = nodes;
+        Self::handle_action(
+            &kind,
+            &mut ast_stack,
+            &mut self.records,
+            self.current_span,
+            Some((origin, self.last_end)),
+        )?;
+        self.record_scope(&kind, ast_stack.last());
+
+        Ok(ast_stack.pop().expect("Toda ação empilha um nó"))
+    }
+
+    // Lista como as recursivas à direita: `MakeList` com a origem `empty` e um
+    // `AppendList` por item, do último ao primeiro, cada um com sua origem
+    fn list(
+        &mut self,
+        items: Vec<(Position, Parsed)>,
+        empty: Position,
+    ) -> Result<Parsed, ParserError> {
+        let mut list = self.reduce(ActionKind::MakeList, empty, vec![])?;
+        for (origin, item) in items.into_iter().rev() {
+            list = self.reduce(ActionKind::AppendList, origin, vec![item, list])?;
+        }
+
+        Ok(list)
+    }
+
+    // Terminal que vira nó folha, como id e num
+    fn terminal(
+        &mut self,
+        token: &mut Token,
+        expected: TokenType,
+        context: &str,
+    ) -> Result<Parsed, ParserError> {
+        if Self::peek(token) == expected {
+            self.leaf(token)
+        } else {
+            Err(self.unexpected(token, &format!("{} {}", expected, context)))
+        }
+    }
+
+    // Terminal sem nó, como a pontuação e as palavras-chave
+    fn consume(
+        &mut self,
+        token: &mut Token,
+        expected: TokenType,
+        context: &str,
+    ) -> Result<(), ParserError> {
+        if Self::peek(token) == expected {
+            self.advance(token)?;
+            Ok(())
+        } else {
+            Err(self.unexpected(token, &format!("'{}' {}", expected, context)))
+        }
+    }
+
+    fn unexpected(&self, token: &Token, expected: &str) -> ParserError {
+        let found = match token {
+            Token::Eof => "o fim do arquivo".to_string(),
+            token => format!("'{}'", token.lexeme()),
+        };
+
+        ParserError::Syntax {
+            message: format!(
+                "Erro de sintaxe. Esperado {}, encontrado {}",
+                expected, found
+            ),
+            span: self.current_span,
+        }
+    }
+
+    fn peek(token: &Token) -> TokenType {
+        token.clone().into()
+    }
+}
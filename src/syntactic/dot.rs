@@ -0,0 +1,49 @@
+use std::fmt::Write;
+
+// Monta um grafo no formato DOT do Graphviz. Os nós recebem identificadores
+// sequenciais e as arestas saem na ordem em que são adicionadas, que o atributo
+// `ordering=out` preserva no desenho.
+pub(crate) struct DotGraph {
+    out: String,
+    next_id: usize,
+}
+
+impl DotGraph {
+    pub(crate) fn new(name: &str) -> Self {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph {} {{", name);
+        out.push_str("    ordering=out;\n");
+        out.push_str("    node [fontname=\"monospace\"];\n");
+
+        DotGraph { out, next_id: 0 }
+    }
+
+    // Adiciona um nó com o rótulo e o formato (`box`, `ellipse`, ...) dados
+    pub(crate) fn node(&mut self, label: &str, shape: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let _ = writeln!(
+            self.out,
+            "    n{} [label=\"{}\", shape={}];",
+            id,
+            escape(label),
+            shape
+        );
+        id
+    }
+
+    pub(crate) fn edge(&mut self, from: usize, to: usize) {
+        let _ = writeln!(self.out, "    n{} -> n{};", from, to);
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+}
+
+// Escapa aspas e barras invertidas para uso dentro de uma string DOT
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,62 @@
+use std::fmt::{Display, Error, Formatter};
+
+use crate::{ActionKind, AstNode, Span, Spanned};
+
+// Erros do analisador sintático
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParserError {
+    // Erro repassado pelo analisador léxico
+    Lexical(String),
+    // Token atual não é aceito pela pilha de análise
+    Syntax {
+        message: String,
+        span: Span,
+    },
+    // Uma ação semântica não encontrou os nodos que esperava na pilha da AST.
+    // Indica inconsistência entre a tabela de análise e as ações, por isso
+    // guarda uma cópia da pilha no momento da falha.
+    Internal {
+        action: ActionKind,
+        message: String,
+        span: Span,
+        ast_stack: Vec<Spanned<AstNode>>,
+    },
+}
+
+impl ParserError {
+    // Mensagem do erro numa única linha, sem a posição nem a pilha da AST
+    pub fn message(&self) -> String {
+        let message = match self {
+            Self::Lexical(message)
+            | Self::Syntax { message, .. }
+            | Self::Internal { message, .. } => message,
+        };
+
+        message.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+impl From<String> for ParserError {
+    fn from(message: String) -> Self {
+        ParserError::Lexical(message)
+    }
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Self::Lexical(message) => write!(f, "{}", message),
+            Self::Syntax { message, span } => write!(f, "{} ({})", message, span.start),
+            Self::Internal {
+                action,
+                message,
+                span,
+                ast_stack,
+            } => write!(
+                f,
+                "Erro interno na ação {:?}: {} ({})\n Pilha AST: {:#?}",
+                action, message, span.start, ast_stack
+            ),
+        }
+    }
+}
@@ -1,21 +1,185 @@
-use std::{collections::HashMap, fs};
+use std::{collections::HashMap, env, process};
 
-use compiler::{ParseTable, Parser};
+use compiler::{
+    AstNode, Lexer, ParseTable, Parser, ParserError, Preprocessor, PrintStyle, Token,
+    check_program, load_program,
+};
+
+// Formatos aceitos por `--emit`, que imprimem apenas a AST codificada, o
+// registro da análise, a tabela LL(1) ou os tokens depois do pré-processador
+const EMIT_FORMATS: [&str; 11] = [
+    "ast-json",
+    "ast-sexp",
+    "ast-dot",
+    "parse-tree-dot",
+    "trace",
+    "trace-csv",
+    "trace-json",
+    "table-md",
+    "table-html",
+    "table-csv",
+    "tokens",
+];
 
 // Função principal de exemplo
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // `--sexp` imprime a AST como S-expression compacta em vez da árvore
+    let style = if args.iter().any(|arg| arg == "--sexp") {
+        PrintStyle::SExpr
+    } else {
+        PrintStyle::Tree
+    };
+
+    // `--emit <formato>` ou `--emit=<formato>`
+    let emit = args
+        .iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.as_str() {
+            "--emit" => Some(args.get(i + 1).map(String::as_str).unwrap_or("")),
+            _ => arg.strip_prefix("--emit="),
+        });
+
+    if let Some(format) = emit
+        && !EMIT_FORMATS.contains(&format)
+    {
+        eprintln!(
+            "✗ Formato desconhecido para --emit: '{}' (use {})",
+            format,
+            EMIT_FORMATS.join(" ou ")
+        );
+        process::exit(2);
+    }
+
     let mut symbol_table = HashMap::new();
     let parse_table = ParseTable::create_parse_table("inicio");
-    let contents = fs::read_to_string("data.txt").expect("Failed to open the file entry.");
-    let mut parser = Parser::new(contents, parse_table, &mut symbol_table);
 
-    match parser.parse() {
-        Ok(ast) => {
-            println!("✓ Análise sintática bem-sucedida!");
-            println!("\nÁrvore Sintática Abstrata:\n{:#?}", ast);
+    // A tabela não depende da entrada, então é impressa sem analisar nada
+    if let Some(format) = emit
+        && format.starts_with("table")
+    {
+        match format {
+            "table-md" => print!("{}", parse_table.to_markdown()),
+            "table-html" => print!("{}", parse_table.to_html()),
+            _ => print!("{}", parse_table.to_csv()),
         }
+        return;
+    }
+
+    // `data.txt` junto com os arquivos que ele importa
+    let (contents, source_map) = match load_program("data.txt") {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("✗ {}", error);
+            process::exit(1);
+        }
+    };
+    // `#define` sai do texto e as macros são expandidas entre o léxico e o sintático
+    let (contents, mut preprocessor) = match Preprocessor::new(&contents) {
+        Ok(preprocessed) => preprocessed,
         Err(error) => {
-            println!("✗ {}", error);
+            eprintln!(
+                "✗ {}",
+                source_map.describe_parser_error(&ParserError::from(error))
+            );
+            process::exit(1);
+        }
+    };
+
+    if emit == Some("tokens") {
+        let mut lexer = Lexer::new(contents, &mut symbol_table);
+        loop {
+            match preprocessor.next_token(&mut lexer) {
+                Ok(Token::Eof) => return,
+                Ok(token) => println!("{}", token),
+                Err(error) => {
+                    eprintln!(
+                        "✗ {}",
+                        source_map.describe_parser_error(&ParserError::from(error))
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
+    let mut parser =
+        Parser::new(contents, parse_table, &mut symbol_table).with_preprocessor(preprocessor);
+    // `--pratt` analisa as expressões por precedência de operadores, fora da tabela
+    if args.iter().any(|arg| arg == "--pratt") {
+        parser = parser.with_pratt_expressions();
+    }
+    // `--descendente` troca a tabela pelo analisador descendente recursivo
+    if args.iter().any(|arg| arg == "--descendente") {
+        parser = parser.with_recursive_descent();
+    }
+    match emit {
+        Some("parse-tree-dot") => parser = parser.with_parse_tree(),
+        Some(format) if format.starts_with("trace") => parser = parser.with_trace(),
+        _ => {}
+    }
+
+    match (parser.parse(), emit) {
+        (Ok(ast), Some(format)) => match emit_ast(&parser, &ast, format) {
+            Ok(encoded) => println!("{}", encoded),
+            Err(error) => {
+                eprintln!("✗ {}", error);
+                process::exit(1);
+            }
+        },
+        (Ok(ast), None) => {
+            println!("✓ Análise sintática bem-sucedida!");
+            println!("\nÁrvore Sintática Abstrata:");
+            ast.print_ast(style);
+
+            match check_program(&ast) {
+                Ok(()) => println!("\n✓ Verificação de tipos bem-sucedida!"),
+                Err(error) => println!("\n✗ {}", source_map.describe_type_error(&error)),
+            }
+        }
+        (Err(error), Some(format)) => {
+            // O registro mostra até onde a análise chegou
+            if let Some(trace) = emit_trace(&parser, format) {
+                print!("{}", trace);
+            }
+            eprintln!("✗ {}", source_map.describe_parser_error(&error));
+            process::exit(1);
+        }
+        (Err(error), None) => {
+            println!("✗ {}", source_map.describe_parser_error(&error));
         }
     };
 }
+
+fn emit_ast(parser: &Parser, ast: &AstNode, format: &str) -> Result<String, String> {
+    match format {
+        "trace" | "trace-csv" | "trace-json" => {
+            emit_trace(parser, format).ok_or("Registro da análise não disponível".into())
+        }
+        "ast-sexp" => Ok(ast.to_sexp()),
+        "ast-dot" => Ok(ast.to_dot()),
+        "parse-tree-dot" => parser
+            .parse_tree()
+            .map(|tree| tree.to_dot())
+            .ok_or("Árvore de derivação não registrada".into()),
+        #[cfg(feature = "serde")]
+        "ast-json" => ast.to_json().map_err(|error| error.to_string()),
+        #[cfg(not(feature = "serde"))]
+        "ast-json" => {
+            Err("--emit ast-json requer a feature 'serde' (cargo run --features serde)".into())
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn emit_trace(parser: &Parser, format: &str) -> Option<String> {
+    let trace = parser.trace()?;
+
+    match format {
+        "trace" => Some(trace.to_table()),
+        "trace-csv" => Some(trace.to_csv()),
+        "trace-json" => Some(trace.to_json()),
+        _ => None,
+    }
+}
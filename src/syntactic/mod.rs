@@ -1,21 +1,63 @@
 pub use crate::{
-    lexer::{KeywordKind, Lexer, OperatorKind, RelopKind, SymbolTable, Token, TokenType, Type},
+    lexer::{
+        Comment, KeywordKind, Lexer, OperatorKind, Position, RelopKind, Scope, Span, SymbolEntry,
+        SymbolTable, Token, TokenType, Type,
+    },
+    preprocessor::Preprocessor,
     syntactic::{
+        error::ParserError,
         parse_table::ParseTable,
+        parse_tree::{ParseTree, Trivia},
+        pratt::{Associativity, BINARY_OPERATORS, PREFIX_PRECEDENCE},
         symbol::{ActionKind, Symbol},
-        tree::AstNode,
+        trace::{ParseTrace, TraceAction, TraceStep},
+        tree::{
+            AstNode, Folder, PrintStyle, Spanned, Visitor, walk_block, walk_fold, walk_if,
+            walk_list, walk_node,
+        },
     },
 };
 
+use std::collections::HashMap;
+
+use parse_tree::{ParseTreeBuilder, PendingNode};
+
+mod descent;
+mod dot;
+mod error;
 mod parse_table;
+mod parse_tree;
+mod pratt;
 mod symbol;
+mod table_export;
+mod trace;
 mod tree;
 
 // Estrutura do Analisador sintático
 pub struct Parser<'a> {
-    stack: Vec<Symbol>,
+    // Cada símbolo guarda a posição de onde começa o nó que ele ajuda a construir
+    // e, se a árvore de derivação estiver sendo registrada, o nó que vai preencher
+    stack: Vec<(Symbol, Position, Option<usize>)>,
     parse_table: ParseTable,
     lexer: Lexer<'a>,
+    current_span: Span,
+    last_end: Position,
+    parse_tree: Option<ParseTreeBuilder>,
+    trace: Option<ParseTrace>,
+    // Tipos registro declarados até o momento, pelo nome
+    records: HashMap<String, Type>,
+    preprocessor: Option<Preprocessor>,
+    // Expande <E> com o analisador por precedência em vez da tabela
+    pratt: bool,
+    // Tokens e ações do <E> atual no analisador por precedência, na ordem em
+    // que a tabela os produziria, para a árvore de derivação
+    expression_nodes: Vec<PendingNode>,
+    // Expressões aninhadas em andamento no analisador por precedência
+    nesting: usize,
+    // Analisa com as funções descendentes recursivas em vez da tabela
+    descent: bool,
+    // Laços abertos na análise descendente, para validar `break` e `continue`
+    loops: usize,
 }
 
 // Funções para o analisador sintático
@@ -29,396 +71,1249 @@ impl<'a> Parser<'a> {
             lexer: Lexer::new(content, symbol_table),
             stack: Vec::new(),
             parse_table,
+            current_span: Span::default(),
+            last_end: Position::default(),
+            parse_tree: None,
+            trace: None,
+            records: HashMap::new(),
+            preprocessor: None,
+            pratt: false,
+            expression_nodes: Vec::new(),
+            nesting: 0,
+            descent: false,
+            loops: 0,
         }
     }
 
-    // Função principal do sintático
-    pub fn parse(&mut self) -> Result<AstNode, String> {
-        // Insere simbolo de parada
-        self.stack.push(Symbol::End);
-        // Insere simbolo inicial
-        self.stack
-            .push(Symbol::NonTerminal(self.parse_table.start_symbol.clone()));
+    // Analisa as expressões por precedência de operadores (Pratt), com os níveis
+    // de `BINARY_OPERATORS`, em vez das produções de <E>. A AST e os intervalos
+    // são os mesmos; na árvore de derivação, os tokens e as ações de cada <E>
+    // ficam direto abaixo dele, e o registro da análise só mostra os tokens
+    pub fn with_pratt_expressions(mut self) -> Self {
+        self.pratt = true;
+        self
+    }
+
+    // Analisa com um descendente recursivo escrito à mão, uma função por não
+    // terminal, em vez da tabela LL(1); da tabela só vem o símbolo inicial. A AST,
+    // os intervalos e a posição dos erros são os mesmos, e as mensagens de erro
+    // dizem o que era esperado em cada ponto. As expressões são analisadas por
+    // precedência; a árvore de derivação não é registrada e o registro da análise
+    // só mostra os tokens
+    pub fn with_recursive_descent(mut self) -> Self {
+        self.descent = true;
+        self
+    }
+
+    // Lê os tokens através do pré-processador, que expande as macros antes da
+    // análise; o texto dado a `new` deve ser o devolvido por `Preprocessor::new`
+    pub fn with_preprocessor(mut self, preprocessor: Preprocessor) -> Self {
+        self.preprocessor = Some(preprocessor);
+        self
+    }
 
-        let mut ast_stack: Vec<AstNode> = Vec::new();
+    // Faz o léxico preservar os comentários, disponíveis depois em `comments`
+    pub fn with_comments(mut self) -> Self {
+        self.lexer.keep_comments = true;
+        self
+    }
+
+    pub fn comments(&self) -> &[Comment] {
+        &self.lexer.comments
+    }
+
+    // Registra a árvore de derivação durante a análise, disponível depois em
+    // `parse_tree` como árvore sintática concreta, sem perda do código-fonte
+    pub fn with_parse_tree(mut self) -> Self {
+        self.parse_tree = Some(ParseTreeBuilder::default());
+        self
+    }
+
+    pub fn parse_tree(&self) -> Option<ParseTree> {
+        self.parse_tree
+            .as_ref()
+            .and_then(|builder| builder.build(&self.lexer.file_content))
+    }
+
+    // Registra cada passo da análise, disponível depois em `trace`
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(ParseTrace::default());
+        self
+    }
 
+    pub fn trace(&self) -> Option<&ParseTrace> {
+        self.trace.as_ref()
+    }
+
+    // Função principal do sintático. Cada filho da AST traz o seu intervalo de
+    // código-fonte; o da raiz vem de `parse_spanned`.
+    pub fn parse(&mut self) -> Result<AstNode, ParserError> {
+        self.parse_spanned().map(|ast| ast.node)
+    }
+
+    // Analisa a entrada devolvendo a raiz junto com o intervalo que ela cobre
+    pub fn parse_spanned(&mut self) -> Result<Spanned<AstNode>, ParserError> {
+        if let Some(trace) = self.trace.as_mut() {
+            *trace = ParseTrace::default();
+        }
+
+        let result = if self.descent {
+            self.parse_descent()
+        } else {
+            self.run()
+        };
+        if let Err(error) = &result {
+            let snapshot = self.trace_snapshot();
+            self.record_step(snapshot, || TraceAction::Error(error.clone()));
+        }
+
+        result
+    }
+
+    fn run(&mut self) -> Result<Spanned<AstNode>, ParserError> {
         // Obtém primeiro token
-        let mut current_token = self.lexer.get_next_token()?;
+        let mut current_token = self.next_token()?;
+        let start = self.current_span.start;
+        let root = self.parse_tree.as_mut().map(|builder| builder.reset());
+
+        // Insere simbolo de parada
+        self.stack.push((Symbol::End, start, None));
+        // Insere simbolo inicial
+        self.stack.push((
+            Symbol::NonTerminal(self.parse_table.start_symbol.clone()),
+            start,
+            root,
+        ));
+
+        let mut ast_stack: Vec<Spanned<AstNode>> = Vec::new();
 
         while !self.stack.is_empty() {
-            let x = self
+            let (x, origin, node) = self
                 .stack
                 .last()
                 .expect("Era esperado uma transição válida")
                 .clone();
+            let snapshot = self.trace_snapshot();
 
             match x {
                 Symbol::Terminal(_) | Symbol::End => {
-                    self.handle_terminal(&x, &mut current_token, &mut ast_stack)?;
+                    let matched = snapshot.as_ref().map(|_| current_token.clone());
+                    self.handle_terminal(&x, node, &mut current_token, &mut ast_stack)?;
+
+                    self.record_step(snapshot, || match matched {
+                        Some(token) if !x.is_end() => TraceAction::Match(token),
+                        _ => TraceAction::Accept,
+                    });
+                }
+                Symbol::NonTerminal(ref nt) if self.pratt && nt == "E" => {
+                    self.expression_nodes.clear();
+                    let ast = self.pratt_expression(&mut current_token)?;
+                    self.stack.pop();
+
+                    if let (Some(builder), Some(node)) = (self.parse_tree.as_mut(), node) {
+                        builder.set_children(node, nt, std::mem::take(&mut self.expression_nodes));
+                    }
+                    ast_stack.push(ast);
                 }
                 Symbol::NonTerminal(ref nt) => {
-                    self.handle_non_terminal(nt, &current_token)?;
+                    self.handle_non_terminal(nt, origin, node, &current_token)?;
+
+                    let production = self
+                        .parse_table
+                        .get_entry(nt, &current_token.clone().into())
+                        .cloned()
+                        .unwrap_or_default();
+                    self.record_step(snapshot, || TraceAction::Expand {
+                        non_terminal: nt.clone(),
+                        production,
+                    });
                 }
                 // Ações semânticas
                 Symbol::Action(kind) => {
-                    match kind {
-                        ActionKind::CreateProgram => {
-                            self.stack.pop(); // Remove a ação
-
-                            if ast_stack.len() >= 2 {
-                                let body = Box::new(ast_stack.pop().unwrap());
-                                let kind = ast_stack.pop().unwrap();
-
-                                let node = AstNode::Program {
-                                    kind: if let AstNode::TypeWrapper(value) = kind {
-                                        value
-                                    } else {
-                                        return Err("Era esperado um tipo.".into());
-                                    },
-                                    body,
-                                };
-
-                                ast_stack.push(node);
-                            } else {
-                                return Err(format!(
-                                    "Erro semântico: não foi possivel criar nó raiz 'programa' com {:#?}",
-                                    ast_stack
-                                ));
-                            }
-                        }
-                        ActionKind::Math(op_kind) => {
-                            self.stack.pop(); // Remove a ação da pilha de parsing
-
-                            // Precisamos de pelo menos 2 operandos na pilha AST (Esquerda e Direita)
-                            if ast_stack.len() >= 2 {
-                                let right = ast_stack.pop().unwrap();
-                                let left = ast_stack.pop().unwrap();
-
-                                let new_node = AstNode::BinaryOp {
-                                    op: op_kind,
-                                    left: Box::new(left),
-                                    right: Box::new(right),
-                                };
-                                ast_stack.push(new_node);
-                            } else {
-                                return Err(
-                                    "Erro semântico: operandos insuficientes para operação"
-                                        .to_string(),
-                                );
-                            }
+                    self.stack.pop(); // Remove a ação
+                    self.check_loop_control(&kind, origin)?;
+                    Self::handle_action(
+                        &kind,
+                        &mut ast_stack,
+                        &mut self.records,
+                        self.current_span,
+                        Some((origin, self.last_end)),
+                    )?;
+                    self.record_scope(&kind, ast_stack.last());
+
+                    self.record_step(snapshot, || TraceAction::Execute {
+                        kind,
+                        ast_stack: ast_stack.clone(),
+                    });
+                }
+                Symbol::Epsilon => {
+                    self.stack.pop();
+                }
+            }
+        }
+
+        match ast_stack.pop() {
+            Some(ast) => Ok(ast),
+            None => Err(ParserError::Syntax {
+                message: "Falha ao construir AST".to_string(),
+                span: self.current_span,
+            }),
+        }
+    }
+
+    // `break` e `continue` precisam de um laço ainda aberto, isto é, da ação que
+    // cria o laço mais abaixo na pilha de análise
+    fn check_loop_control(&self, kind: &ActionKind, origin: Position) -> Result<(), ParserError> {
+        let keyword = match kind {
+            ActionKind::CreateBreak => "break",
+            ActionKind::CreateContinue => "continue",
+            _ => return Ok(()),
+        };
+
+        let inside_loop = self.loops > 0
+            || self.stack.iter().any(|(symbol, ..)| {
+                matches!(
+                    symbol,
+                    Symbol::Action(
+                        ActionKind::CreateWhile | ActionKind::CreateDoWhile | ActionKind::CreateFor
+                    )
+                )
+            });
+
+        if inside_loop {
+            Ok(())
+        } else {
+            Err(ParserError::Syntax {
+                message: format!("'{}' fora de um laço", keyword),
+                span: Span {
+                    start: origin,
+                    end: self.last_end.max(origin),
+                },
+            })
+        }
+    }
+
+    // Marca no `SymbolTable` os nomes declarados pela ação: globais no
+    // `AddGlobal`, locais nos blocos e parâmetros
+    fn record_scope(&mut self, kind: &ActionKind, node: Option<&Spanned<AstNode>>) {
+        let (scope, decls): (Scope, Vec<&AstNode>) = match (kind, node.map(|node| &node.node)) {
+            (ActionKind::AddGlobal, Some(AstNode::Program { globals, .. })) => (
+                Scope::Global,
+                globals.iter().take(1).map(|decl| &decl.node).collect(),
+            ),
+            (ActionKind::CreateBlock, Some(AstNode::Block { decls, .. })) => {
+                (Scope::Local, decls.iter().map(|decl| &decl.node).collect())
+            }
+            (ActionKind::CreateParam, Some(param)) => (Scope::Local, vec![param]),
+            _ => return,
+        };
+
+        for decl in decls {
+            let AstNode::VarDecl { names, .. } = decl else {
+                continue;
+            };
+            for name in names {
+                let entry = self
+                    .lexer
+                    .symbol_table
+                    .entry(name.clone())
+                    .or_insert(SymbolEntry {
+                        kind: None,
+                        scope: None,
+                    });
+                if scope == Scope::Global || entry.scope.is_none() {
+                    entry.scope = Some(scope);
+                }
+            }
+        }
+    }
+
+    // Pilha de análise e índice do token atual, se o registro de passos estiver ativo
+    fn trace_snapshot(&self) -> Option<(Vec<Symbol>, usize)> {
+        self.trace.as_ref().map(|trace| {
+            (
+                self.stack
+                    .iter()
+                    .map(|(symbol, ..)| symbol.clone())
+                    .collect(),
+                trace.tokens.len().saturating_sub(1),
+            )
+        })
+    }
+
+    fn record_step(
+        &mut self,
+        snapshot: Option<(Vec<Symbol>, usize)>,
+        action: impl FnOnce() -> TraceAction,
+    ) {
+        if let (Some(trace), Some((stack, token))) = (self.trace.as_mut(), snapshot) {
+            trace.steps.push(TraceStep {
+                stack,
+                token,
+                action: action(),
+            });
+        }
+    }
+
+    // Lê o próximo token e guarda o intervalo que ele ocupa no código-fonte
+    fn next_token(&mut self) -> Result<Token, ParserError> {
+        let token = match self.preprocessor.as_mut() {
+            Some(preprocessor) => preprocessor.next_token(&mut self.lexer)?,
+            None => self.lexer.get_next_token()?,
+        };
+        if let Some(trace) = self.trace.as_mut() {
+            trace.tokens.push(token.clone());
+        }
+        let end = Position {
+            line: self.lexer.line,
+            column: self.lexer.column,
+        };
+
+        self.current_span = Span {
+            start: token.position().unwrap_or(end),
+            end,
+        };
+
+        Ok(token)
+    }
+
+    // Executa uma ação semântica sobre a pilha da AST
+    // `records` guarda os tipos registro já declarados e `span` é o intervalo
+    // informado nos erros. `extent` traz a origem da produção e o fim do último
+    // token casado: o nó criado vai do início do primeiro filho (ou da origem, se
+    // vier antes) até esse fim. Sem ele o nó fica sem intervalo.
+    fn handle_action(
+        kind: &ActionKind,
+        ast_stack: &mut Vec<Spanned<AstNode>>,
+        records: &mut HashMap<String, Type>,
+        span: Span,
+        extent: Option<(Position, Position)>,
+    ) -> Result<(), ParserError> {
+        // Início do primeiro nodo consumido, que pode não virar filho do nó criado
+        let mut first = None;
+
+        match kind {
+            ActionKind::CreateProgram => {
+                let [kind_node, body] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match kind_node.node {
+                    AstNode::TypeWrapper(program_kind) => ast_stack.push(
+                        AstNode::Program {
+                            records: vec![],
+                            globals: vec![],
+                            functions: vec![],
+                            kind: program_kind,
+                            body: Box::new(body),
                         }
-                        ActionKind::CreateBlock => {
-                            self.stack.pop(); // Remove ação
-
-                            if ast_stack.len() >= 2 {
-                                let stmts_node = ast_stack.pop().unwrap();
-                                let stmts = match stmts_node {
-                                    AstNode::List(vec) => vec,
-                                    _ => {
-                                        return Err(format!(
-                                            "Erro: Esperava-se uma lista de comandos, recebeu {:?}",
-                                            stmts_node
-                                        ));
-                                    }
-                                };
-
-                                let decls_node = ast_stack.pop().unwrap();
-                                let decls = match decls_node {
-                                    AstNode::List(vec) => vec,
-                                    _ => {
-                                        return Err(format!(
-                                            "Erro: Esperava-se uma lista de declarações, recebeu {:?}",
-                                            decls_node
-                                        ));
-                                    }
-                                };
-
-                                let node = AstNode::Block { decls, stmts };
-                                ast_stack.push(node);
-                            } else {
-                                return Err(format!(
-                                    "Erro: era esperado ao menos 2 nodos para criar um bloco, temos {:?}",
-                                    ast_stack
-                                ));
-                            }
+                        .into(),
+                    ),
+                    kind_node => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado um tipo. Recebido: {:?}", kind_node),
+                            ast_stack,
+                            vec![kind_node.into(), body],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateFunction => {
+                let [type_node, name_node, params_node, body] =
+                    Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match (type_node.node, name_node.node, params_node.node) {
+                    (
+                        AstNode::TypeWrapper(return_kind),
+                        AstNode::Identifier { name },
+                        AstNode::List(params),
+                    ) => ast_stack.push(
+                        AstNode::Function {
+                            kind: return_kind,
+                            name,
+                            params,
+                            body: Box::new(body),
                         }
-                        ActionKind::CreateDecl => {
-                            self.stack.pop();
-
-                            if ast_stack.len() >= 2 {
-                                let ids_nodes = ast_stack.pop().unwrap();
-
-                                let type_node = ast_stack.pop().unwrap();
-
-                                let var_type = match type_node {
-                                    AstNode::TypeWrapper(t) => t,
-                                    _ => panic!("Esperava-se um nó tipo, recebeu {:?}", type_node),
-                                };
-
-                                let names_vec: Vec<String> = match ids_nodes {
-                                    AstNode::List(nodes) => nodes
-                                        .into_iter()
-                                        .map(|node| match node {
-                                            AstNode::Identifier { name } => name,
-                                            _ => panic!("Item da lista de declaração não é um ID"),
-                                        })
-                                        .collect(),
-                                    _ => panic!(
-                                        "Esperava uma lista de IDs. Recebeu: {:?}",
-                                        ids_nodes
-                                    ),
-                                };
-
-                                let decl_node = AstNode::VarDecl {
-                                    kind: var_type,
-                                    names: names_vec,
-                                };
-
-                                ast_stack.push(decl_node);
-                            } else {
-                                return Err(format!(
-                                    "Era esperado ao menos 2 nodos, recebido: {:?}",
-                                    ast_stack
-                                ));
+                        .into(),
+                    ),
+                    (type_node, name_node, params_node) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se um tipo, um identificador e uma lista de parâmetros, recebeu {:?}, {:?} e {:?}",
+                                type_node, name_node, params_node
+                            ),
+                            ast_stack,
+                            vec![type_node.into(), name_node.into(), params_node.into(), body],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateParam => {
+                let [type_node, name_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match (type_node.node, name_node.node) {
+                    (AstNode::TypeWrapper(param_kind), AstNode::Identifier { name }) => ast_stack
+                        .push(
+                            AstNode::VarDecl {
+                                kind: param_kind,
+                                names: vec![name],
+                                inits: vec![None],
+                                constant: false,
                             }
-                        }
-                        ActionKind::MakeList => {
-                            self.stack.pop();
+                            .into(),
+                        ),
+                    (type_node, name_node) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se um tipo e um identificador, recebeu {:?} e {:?}",
+                                type_node, name_node
+                            ),
+                            ast_stack,
+                            vec![type_node.into(), name_node.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::AddFunction => {
+                let [function, program] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
 
-                            ast_stack.push(AstNode::List(vec![]));
-                        }
-                        ActionKind::AppendList => {
-                            self.stack.pop();
-
-                            if ast_stack.len() >= 2 {
-                                let mut list_node = ast_stack.pop().unwrap();
-                                let item_node = ast_stack.pop().unwrap();
-
-                                if let AstNode::List(ref mut vec) = list_node {
-                                    vec.insert(0, item_node);
-                                } else {
-                                    return Err(format!(
-                                        "Esperava-se uma lista no topo da lista. Recebido: {:?}",
-                                        list_node
-                                    ));
-                                }
-
-                                ast_stack.push(list_node);
-                            } else {
-                                return Err(format!(
-                                    "Era esperado ao menos 2 nodos, recebido: {:?}",
-                                    ast_stack
-                                ));
+                match program.node {
+                    AstNode::Program {
+                        records,
+                        globals,
+                        mut functions,
+                        kind: program_kind,
+                        body,
+                    } => {
+                        functions.insert(0, function);
+                        ast_stack.push(
+                            AstNode::Program {
+                                records,
+                                globals,
+                                functions,
+                                kind: program_kind,
+                                body,
                             }
-                        }
-                        ActionKind::Assign => {
-                            self.stack.pop();
-
-                            if ast_stack.len() >= 2 {
-                                let expr = ast_stack.pop().unwrap();
-                                let id = ast_stack.pop().unwrap();
-
-                                let id_name = match id {
-                                    AstNode::Identifier { name } => name,
-                                    _ => {
-                                        return Err(format!(
-                                            "Era esperado um identificador. Recebido {:?}",
-                                            id
-                                        ));
-                                    }
-                                };
-
-                                ast_stack.push(AstNode::Assignment {
-                                    id: id_name,
-                                    expr: Box::new(expr),
-                                });
-                            } else {
-                                return Err(format!(
-                                    "Era esperado ao menos dois nodos. Recebido: {:?}",
-                                    ast_stack
-                                ));
+                            .into(),
+                        );
+                    }
+                    program => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado o programa. Recebido: {:?}", program),
+                            ast_stack,
+                            vec![function, program.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::AddGlobal => {
+                let [decl, program] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match program.node {
+                    AstNode::Program {
+                        records,
+                        mut globals,
+                        functions,
+                        kind: program_kind,
+                        body,
+                    } => {
+                        globals.insert(0, decl);
+                        ast_stack.push(
+                            AstNode::Program {
+                                records,
+                                globals,
+                                functions,
+                                kind: program_kind,
+                                body,
                             }
-                        }
-                        ActionKind::CreateIf => {
-                            self.stack.pop();
-
-                            if ast_stack.len() >= 2 {
-                                let then_block = Box::new(ast_stack.pop().unwrap());
-                                let cond = Box::new(ast_stack.pop().unwrap());
-
-                                ast_stack.push(AstNode::If {
-                                    cond,
-                                    then_block,
-                                    else_block: None,
-                                });
-                            } else {
-                                return Err(format!(
-                                    "Era esperado ao menos 2 nodos. Recebido: {:?}",
-                                    ast_stack
-                                ));
+                            .into(),
+                        );
+                    }
+                    program => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado o programa. Recebido: {:?}", program),
+                            ast_stack,
+                            vec![decl, program.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateCall => {
+                let [name_node, args_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match (name_node.node, args_node.node) {
+                    (AstNode::Identifier { name }, AstNode::List(args)) => {
+                        ast_stack.push(AstNode::Call { name, args }.into())
+                    }
+                    (name_node, args_node) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se um identificador e uma lista de argumentos, recebeu {:?} e {:?}",
+                                name_node, args_node
+                            ),
+                            ast_stack,
+                            vec![name_node.into(), args_node.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateBreak => ast_stack.push(AstNode::Break.into()),
+            ActionKind::CreateContinue => ast_stack.push(AstNode::Continue.into()),
+            ActionKind::CreateReturn => {
+                let [expr] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::Return {
+                        expr: Box::new(expr),
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateArrayDecl => {
+                let [type_node, name_node, size_node] =
+                    Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                // O tamanho precisa ser um literal inteiro sem sinal
+                let size = match &size_node.node {
+                    AstNode::Number { value } => value.parse::<u32>().ok(),
+                    _ => None,
+                };
+
+                match (type_node.node, name_node.node, size) {
+                    (AstNode::TypeWrapper(elem), AstNode::Identifier { name }, Some(size)) => {
+                        ast_stack.push(
+                            AstNode::VarDecl {
+                                kind: Type::Array {
+                                    elem: Box::new(elem),
+                                    size,
+                                },
+                                names: vec![name],
+                                inits: vec![None],
+                                constant: false,
                             }
+                            .into(),
+                        )
+                    }
+                    (type_node, name_node, _) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se um tipo, um identificador e um tamanho inteiro, recebeu {:?}, {:?} e {:?}",
+                                type_node, name_node, size_node
+                            ),
+                            ast_stack,
+                            vec![type_node.into(), name_node.into(), size_node],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateArrayAccess => {
+                let [array, index] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::ArrayAccess {
+                        array: Box::new(array),
+                        index: Box::new(index),
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateFieldAccess => {
+                let [record, field_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match field_node.node {
+                    AstNode::Identifier { name } => ast_stack.push(
+                        AstNode::FieldAccess {
+                            record: Box::new(record),
+                            field: name,
                         }
-                        ActionKind::CreateIfElse => {
-                            self.stack.pop();
-                            if ast_stack.len() >= 3 {
-                                let else_block = Some(Box::new(ast_stack.pop().unwrap()));
-                                let then_block = Box::new(ast_stack.pop().unwrap());
-                                let cond = Box::new(ast_stack.pop().unwrap());
-
-                                ast_stack.push(AstNode::If {
-                                    cond,
-                                    then_block,
-                                    else_block,
-                                });
-                            } else {
-                                return Err(format!(
-                                    "Era esperado ao menos 3 nodos. Recebido {:?}",
-                                    ast_stack
-                                ));
-                            }
+                        .into(),
+                    ),
+                    field_node => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado um identificador. Recebido {:?}", field_node),
+                            ast_stack,
+                            vec![record, field_node.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::AssignAccess => {
+                let [target, expr] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match Self::assignment(target, expr) {
+                    Ok(node) => ast_stack.push(node.into()),
+                    Err(nodes) => {
+                        let [target, expr] = *nodes;
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Era esperado um acesso a elemento ou campo. Recebido {:?}",
+                                target
+                            ),
+                            ast_stack,
+                            vec![target, expr],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::AssignOp(op) | ActionKind::AssignStep(op) => {
+                // `x++` é `x += 1`
+                let (target, expr) = match kind {
+                    ActionKind::AssignStep(_) => {
+                        let [target] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+                        // O `1` implícito fica com o intervalo depois do alvo
+                        let one = Spanned {
+                            node: AstNode::Number {
+                                value: "1".to_string(),
+                            },
+                            span: target.span.zip(extent).map(|(target, (_, end))| Span {
+                                start: target.end,
+                                end,
+                            }),
+                        };
+                        (target, one)
+                    }
+                    _ => {
+                        let [target, expr] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+                        (target, expr)
+                    }
+                };
+
+                // O alvo entra de novo como operando do valor, `x op expr`, e por isso é
+                // avaliado duas vezes. Sem chamadas ele não tem efeitos colaterais e as
+                // duas avaliações dão o mesmo resultado; com uma chamada, como em
+                // `v[f()] += 1`, a função rodaria duas vezes.
+                if Self::has_call(&target.node) {
+                    return Err(ParserError::Syntax {
+                        message: "Alvo de atribuição composta não pode ter chamadas de função"
+                            .into(),
+                        span: target.span.unwrap_or(span),
+                    });
+                }
+                let value = Spanned {
+                    span: target.span.zip(expr.span).map(|(target, expr)| Span {
+                        start: target.start,
+                        end: expr.end,
+                    }),
+                    node: AstNode::BinaryOp {
+                        op: op.clone(),
+                        left: Box::new(target.clone()),
+                        right: Box::new(expr),
+                    },
+                };
+                match Self::assignment(target, value) {
+                    Ok(node) => ast_stack.push(node.into()),
+                    Err(nodes) => {
+                        let [target, value] = *nodes;
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Era esperada uma variável, elemento ou campo. Recebido {:?}",
+                                target
+                            ),
+                            ast_stack,
+                            vec![target, value],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::RecordType => {
+                let [name_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match name_node.node {
+                    AstNode::Identifier { name } => match records.get(&name) {
+                        Some(record) => ast_stack.push(AstNode::TypeWrapper(record.clone()).into()),
+                        None => {
+                            return Err(ParserError::Syntax {
+                                message: format!("Tipo '{}' não declarado", name),
+                                span,
+                            });
                         }
-                        ActionKind::CreateWhile => {
-                            self.stack.pop();
-                            if ast_stack.len() >= 2 {
-                                let body = Box::new(ast_stack.pop().unwrap());
-                                let cond = Box::new(ast_stack.pop().unwrap());
-
-                                ast_stack.push(AstNode::While { cond, body });
-                            } else {
-                                return Err(format!(
-                                    "Era esperado ao menos 2 nodos. Recebido: {:?}",
-                                    ast_stack
-                                ));
+                    },
+                    name_node => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado um identificador. Recebido {:?}", name_node),
+                            ast_stack,
+                            vec![name_node.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateRecord => {
+                let [name_node, decls_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                let (name, decls) = match (name_node.node, decls_node.node) {
+                    (AstNode::Identifier { name }, AstNode::List(decls)) => (name, decls),
+                    (name_node, decls_node) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se um identificador e uma lista de declarações, recebeu {:?} e {:?}",
+                                name_node, decls_node
+                            ),
+                            ast_stack,
+                            vec![name_node.into(), decls_node.into()],
+                            span,
+                        ));
+                    }
+                };
+
+                let record = Self::record_type(name, decls, records, span)?;
+                if let Type::Record { name, .. } = &record {
+                    records.insert(name.clone(), record.clone());
+                }
+                ast_stack.push(AstNode::Record { kind: record }.into());
+            }
+            ActionKind::AddRecord => {
+                let [record, program] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match program.node {
+                    AstNode::Program {
+                        mut records,
+                        globals,
+                        functions,
+                        kind: program_kind,
+                        body,
+                    } => {
+                        records.insert(0, record);
+                        ast_stack.push(
+                            AstNode::Program {
+                                records,
+                                globals,
+                                functions,
+                                kind: program_kind,
+                                body,
                             }
-                        }
-                        ActionKind::CreateDoWhile => {
-                            self.stack.pop();
-                            if ast_stack.len() >= 2 {
-                                let cond = Box::new(ast_stack.pop().unwrap());
-                                let body = Box::new(ast_stack.pop().unwrap());
-
-                                ast_stack.push(AstNode::DoWhile { body, cond });
-                            } else {
-                                return Err(format!(
-                                    "Era esperado ao menos 2 nodos. Recebido: {:?}",
-                                    ast_stack
-                                ));
+                            .into(),
+                        );
+                    }
+                    program => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado o programa. Recebido: {:?}", program),
+                            ast_stack,
+                            vec![record, program.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::Math(op_kind) => {
+                // Precisamos de 2 operandos na pilha AST (Esquerda e Direita)
+                let [left, right] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::BinaryOp {
+                        op: op_kind.clone(),
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateBlock => {
+                let [decls_node, stmts_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match (decls_node.node, stmts_node.node) {
+                    (AstNode::List(decls), AstNode::List(stmts)) => {
+                        ast_stack.push(AstNode::Block { decls, stmts }.into());
+                    }
+                    (decls_node, stmts_node) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se uma lista de declarações e uma de comandos, recebeu {:?} e {:?}",
+                                decls_node, stmts_node
+                            ),
+                            ast_stack,
+                            vec![decls_node.into(), stmts_node.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateDecl => {
+                let [type_node, ids_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match (type_node.node, ids_node.node) {
+                    // Cada item é um identificador sem valor inicial ou uma atribuição
+                    (AstNode::TypeWrapper(var_type), AstNode::List(nodes))
+                        if nodes.iter().all(|node| {
+                            matches!(
+                                node.node,
+                                AstNode::Identifier { .. } | AstNode::Assignment { .. }
+                            )
+                        }) =>
+                    {
+                        let (names, inits) = nodes
+                            .into_iter()
+                            .filter_map(|node| match node.node {
+                                AstNode::Identifier { name } => Some((name, None)),
+                                AstNode::Assignment { id, expr } => Some((id, Some(*expr))),
+                                _ => None,
+                            })
+                            .unzip();
+
+                        ast_stack.push(
+                            AstNode::VarDecl {
+                                kind: var_type,
+                                names,
+                                inits,
+                                constant: false,
                             }
+                            .into(),
+                        );
+                    }
+                    (type_node, ids_node) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se um nó tipo e uma lista de IDs, recebeu {:?} e {:?}",
+                                type_node, ids_node
+                            ),
+                            ast_stack,
+                            vec![type_node.into(), ids_node.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::MarkConst => {
+                let [decl] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match decl.node {
+                    AstNode::VarDecl {
+                        kind: var_type,
+                        names,
+                        inits,
+                        ..
+                    } => {
+                        // Uma constante não pode ficar sem valor, e vetores não têm valor inicial
+                        if let Some((name, _)) =
+                            names.iter().zip(&inits).find(|(_, init)| init.is_none())
+                        {
+                            return Err(ParserError::Syntax {
+                                message: format!(
+                                    "Constante '{}' declarada sem valor inicial",
+                                    name
+                                ),
+                                span,
+                            });
                         }
-                        ActionKind::CreateFor => {
-                            self.stack.pop();
-                            if ast_stack.len() >= 5 {
-                                let body = Box::new(ast_stack.pop().unwrap());
-                                let step = Box::new(ast_stack.pop().unwrap());
-                                let end_node = ast_stack.pop().unwrap();
-                                let start_node = ast_stack.pop().unwrap();
-                                let id_node = ast_stack.pop().unwrap();
-
-                                let end = match end_node {
-                                    AstNode::Number { value } => {
-                                        value.parse().expect("Esperado um número inteiro.")
-                                    }
-                                    _ => {
-                                        return Err(format!(
-                                            "Era esperado um número. Recebido: {:?}",
-                                            end_node
-                                        ));
-                                    }
-                                };
-
-                                let start = match start_node {
-                                    AstNode::Number { value } => {
-                                        value.parse().expect("Esperado um número inteiro.")
-                                    }
-                                    _ => {
-                                        return Err(format!(
-                                            "Era esperado um número. Recebido: {:?}",
-                                            start_node
-                                        ));
-                                    }
-                                };
-
-                                let id = match id_node {
-                                    AstNode::Identifier { name } => name,
-                                    _ => {
-                                        return Err(format!(
-                                            "Era esperado um identificador. Recebido: {:?}",
-                                            id_node
-                                        ));
-                                    }
-                                };
-
-                                ast_stack.push(AstNode::For {
-                                    id,
-                                    start,
-                                    end,
-                                    step,
-                                    body,
-                                });
+
+                        ast_stack.push(
+                            AstNode::VarDecl {
+                                kind: var_type,
+                                names,
+                                inits,
+                                constant: true,
                             }
+                            .into(),
+                        );
+                    }
+                    decl => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Esperava-se uma declaração, recebeu {:?}", decl),
+                            ast_stack,
+                            vec![decl.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::MakeList => {
+                ast_stack.push(AstNode::List(vec![]).into());
+            }
+            ActionKind::AppendList => {
+                let [item_node, list_node] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match list_node.node {
+                    AstNode::List(mut vec) => {
+                        vec.insert(0, item_node);
+                        ast_stack.push(AstNode::List(vec).into());
+                    }
+                    list_node => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Esperava-se uma lista no topo da pilha. Recebido: {:?}",
+                                list_node
+                            ),
+                            ast_stack,
+                            vec![item_node, list_node.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::Assign => {
+                let [id, expr] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match id.node {
+                    AstNode::Identifier { name } => ast_stack.push(
+                        AstNode::Assignment {
+                            id: name,
+                            expr: Box::new(expr),
                         }
-                        ActionKind::CreateCond => {
-                            self.stack.pop();
-                            if ast_stack.len() >= 3 {
-                                let right = Box::new(ast_stack.pop().unwrap());
-                                let relop_term = ast_stack.pop().unwrap();
-                                let left = Box::new(ast_stack.pop().unwrap());
-
-                                let relop = match relop_term {
-                                    AstNode::CondWrapper(r) => r,
-                                    _ => {
-                                        panic!("Era esperado um relop. Recebido: {:?}", relop_term)
-                                    }
-                                };
-
-                                ast_stack.push(AstNode::BinaryComp { relop, left, right });
-                            } else {
-                                return Err(
-                                    "Era esperado ao menos 3 nodos, para criar uma condição"
-                                        .to_string(),
-                                );
-                            }
+                        .into(),
+                    ),
+                    id => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado um identificador. Recebido {:?}", id),
+                            ast_stack,
+                            vec![id.into(), expr],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateIf => {
+                let [cond, then_block] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::If {
+                        cond: Box::new(cond),
+                        then_block: Box::new(then_block),
+                        else_block: None,
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateIfElse => {
+                let [cond, then_block, else_block] =
+                    Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::If {
+                        cond: Box::new(cond),
+                        then_block: Box::new(then_block),
+                        else_block: Some(Box::new(else_block)),
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateWhile => {
+                let [cond, body] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::While {
+                        cond: Box::new(cond),
+                        body: Box::new(body),
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateDoWhile => {
+                let [body, cond] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::DoWhile {
+                        body: Box::new(body),
+                        cond: Box::new(cond),
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateFor => {
+                let [id_node, start, end, step, body] =
+                    Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match id_node.node {
+                    AstNode::Identifier { name } => ast_stack.push(
+                        AstNode::For {
+                            id: name,
+                            start: Box::new(start),
+                            end: Box::new(end),
+                            step: Box::new(step),
+                            body: Box::new(body),
                         }
-                        ActionKind::CreateUnaryOp => {
-                            self.stack.pop();
-                            if !ast_stack.is_empty() {
-                                let expr = Box::new(ast_stack.pop().unwrap());
-
-                                ast_stack.push(AstNode::UnaryOp { expr });
-                            } else {
-                                return Err(
-                                    "Era esperado ao menos 1 nodo, para criar um operador unário"
-                                        .to_string(),
-                                );
+                        .into(),
+                    ),
+                    id_node => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado um identificador. Recebido {:?}", id_node),
+                            ast_stack,
+                            vec![id_node.into(), start, end, step, body],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateSwitch => {
+                let [expr, cases, default] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match (cases.node, default.node) {
+                    (AstNode::List(cases), AstNode::List(mut default)) if default.len() <= 1 => {
+                        ast_stack.push(
+                            AstNode::Switch {
+                                expr: Box::new(expr),
+                                cases,
+                                default: default.pop().map(Box::new),
                             }
+                            .into(),
+                        )
+                    }
+                    (cases, default) => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!(
+                                "Eram esperadas as listas de ramos e do default. Recebido {:?} e {:?}",
+                                cases, default
+                            ),
+                            ast_stack,
+                            vec![expr, cases.into(), default.into()],
+                            span,
+                        ));
+                    }
+                }
+            }
+            ActionKind::CreateCase => {
+                let [label, body] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::Case {
+                        label: Box::new(label),
+                        body: Box::new(body),
+                    }
+                    .into(),
+                );
+            }
+            ActionKind::CreateCond => {
+                let [left, relop_term, right] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                match relop_term.node {
+                    AstNode::CondWrapper(relop) => ast_stack.push(
+                        AstNode::BinaryComp {
+                            relop,
+                            left: Box::new(left),
+                            right: Box::new(right),
                         }
+                        .into(),
+                    ),
+                    relop_term => {
+                        return Err(Self::internal_error(
+                            kind,
+                            format!("Era esperado um relop. Recebido: {:?}", relop_term),
+                            ast_stack,
+                            vec![left, relop_term.into(), right],
+                            span,
+                        ));
                     }
                 }
-                Symbol::Epsilon => {
-                    self.stack.pop();
+            }
+            ActionKind::CreateUnaryOp(op) => {
+                let [expr] = Self::pop_nodes(kind, ast_stack, span, &mut first)?;
+
+                ast_stack.push(
+                    AstNode::UnaryOp {
+                        op: op.clone(),
+                        expr: Box::new(expr),
+                    }
+                    .into(),
+                );
+            }
+        }
+
+        // Sem nodos consumidos (lista vazia, `break`), o intervalo começa na
+        // produção; numa produção vazia ele não tem largura
+        if let (Some((origin, end)), Some(node)) = (extent, ast_stack.last_mut()) {
+            let start = first.map_or(origin, |first: Position| origin.min(first));
+            node.span = Some(Span {
+                start,
+                end: end.max(start),
+            });
+        }
+
+        Ok(())
+    }
+
+    // Monta o tipo de um registro a partir das declarações dos campos. Os campos
+    // não têm valor inicial e os nomes não se repetem.
+    fn record_type(
+        name: String,
+        decls: Vec<Spanned<AstNode>>,
+        records: &HashMap<String, Type>,
+        span: Span,
+    ) -> Result<Type, ParserError> {
+        let error = |message: String| ParserError::Syntax { message, span };
+
+        if records.contains_key(&name) {
+            return Err(error(format!("Tipo '{}' já declarado", name)));
+        }
+
+        let mut fields: Vec<(String, Type)> = vec![];
+        for decl in decls {
+            let AstNode::VarDecl {
+                kind,
+                names,
+                inits,
+                constant,
+            } = decl.node
+            else {
+                return Err(error(format!(
+                    "Era esperada uma declaração de campo no registro '{}'",
+                    name
+                )));
+            };
+
+            for (field, init) in names.into_iter().zip(inits) {
+                if constant || init.is_some() {
+                    return Err(error(format!(
+                        "Campo '{}' do registro '{}' não pode ter valor inicial",
+                        field, name
+                    )));
+                }
+                if kind == Type::Void {
+                    return Err(error(format!(
+                        "Campo '{}' do registro '{}' não pode ser void",
+                        field, name
+                    )));
                 }
+                if fields.iter().any(|(other, _)| *other == field) {
+                    return Err(error(format!(
+                        "Campo '{}' repetido no registro '{}'",
+                        field, name
+                    )));
+                }
+                fields.push((field, kind.clone()));
             }
         }
 
-        if let Some(ast) = ast_stack.pop() {
-            Ok(ast)
-        } else {
-            Err("Falha ao construir AST".to_string())
+        if fields.is_empty() {
+            return Err(error(format!("Registro '{}' sem campos", name)));
+        }
+
+        Type::record(name.clone(), fields)
+            .ok_or_else(|| error(format!("Registro '{}' grande demais", name)))
+    }
+
+    // Remove os `N` nodos do topo da pilha da AST, na ordem em que foram
+    // empilhados, guardando em `first` o início do primeiro deles
+    fn pop_nodes<const N: usize>(
+        kind: &ActionKind,
+        ast_stack: &mut Vec<Spanned<AstNode>>,
+        span: Span,
+        first: &mut Option<Position>,
+    ) -> Result<[Spanned<AstNode>; N], ParserError> {
+        if ast_stack.len() < N {
+            return Err(Self::internal_error(
+                kind,
+                format!(
+                    "Era esperado ao menos {} nodos, recebido {}",
+                    N,
+                    ast_stack.len()
+                ),
+                ast_stack,
+                vec![],
+                span,
+            ));
+        }
+
+        let nodes = ast_stack.split_off(ast_stack.len() - N);
+        *first = nodes
+            .first()
+            .and_then(|node| node.span)
+            .map(|span| span.start);
+        nodes.try_into().map_err(|nodes: Vec<Spanned<AstNode>>| {
+            Self::internal_error(
+                kind,
+                "Quantidade de nodos inesperada".to_string(),
+                ast_stack,
+                nodes,
+                span,
+            )
+        })
+    }
+
+    // Atribuição de `expr` a uma variável, elemento ou campo; devolve os nós se o
+    // alvo for outro
+    fn assignment(
+        target: Spanned<AstNode>,
+        expr: Spanned<AstNode>,
+    ) -> Result<AstNode, Box<[Spanned<AstNode>; 2]>> {
+        let expr = Box::new(expr);
+        match target.node {
+            AstNode::Identifier { name } => Ok(AstNode::Assignment { id: name, expr }),
+            AstNode::ArrayAccess { array, index } => {
+                Ok(AstNode::ArrayAssignment { array, index, expr })
+            }
+            AstNode::FieldAccess { record, field } => Ok(AstNode::FieldAssignment {
+                record,
+                field,
+                expr,
+            }),
+            node => Err(Box::new([
+                Spanned {
+                    node,
+                    span: target.span,
+                },
+                *expr,
+            ])),
+        }
+    }
+
+    // Se há alguma chamada de função dentro da expressão
+    fn has_call(node: &AstNode) -> bool {
+        matches!(node, AstNode::Call { .. })
+            || node.children().iter().any(|child| Self::has_call(child))
+    }
+
+    // Monta o erro interno de uma ação, devolvendo à cópia da pilha os nodos já removidos
+    fn internal_error(
+        kind: &ActionKind,
+        message: String,
+        ast_stack: &mut Vec<Spanned<AstNode>>,
+        popped: Vec<Spanned<AstNode>>,
+        span: Span,
+    ) -> ParserError {
+        let mut snapshot = std::mem::take(ast_stack);
+        snapshot.extend(popped);
+
+        ParserError::Internal {
+            action: kind.clone(),
+            message,
+            span,
+            ast_stack: snapshot,
         }
     }
 
     fn handle_terminal(
         &mut self,
         symbol: &Symbol,
+        node: Option<usize>,
         current_token: &mut Token,
-        ast_stack: &mut Vec<AstNode>,
-    ) -> Result<(), String> {
+        ast_stack: &mut Vec<Spanned<AstNode>>,
+    ) -> Result<(), ParserError> {
         if symbol.is_end() {
             if *current_token == Token::Eof {
                 self.stack.pop();
                 return Ok(());
             } else {
-                return Err("Esperado fim de arquivo".into());
+                return Err(ParserError::Syntax {
+                    message: "Esperado fim de arquivo".into(),
+                    span: self.current_span,
+                });
             }
         }
 
@@ -426,119 +1321,143 @@ impl<'a> Parser<'a> {
             // Verifica compatibilidade entre Token esperado e atual
             if terminal_type == current_token.clone().into() {
                 // Se for número ou ID ou tipo ou relop, empilha na AST Stack
-                match current_token {
-                    Token::Id { value: name, .. } => {
-                        ast_stack.push(AstNode::Identifier { name: name.clone() });
-                    }
-                    Token::Number { value, .. } => {
-                        ast_stack.push(AstNode::Number {
-                            value: value.clone(),
-                        });
-                    }
-                    Token::Char { value, .. } => {
-                        ast_stack.push(AstNode::Literal { value: *value });
-                    }
-                    Token::Keyword {
-                        kind: KeywordKind::Char,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::TypeWrapper(Type::Char));
-                    }
-                    Token::Keyword {
-                        kind: KeywordKind::Float,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::TypeWrapper(Type::Float));
-                    }
-                    Token::Keyword {
-                        kind: KeywordKind::Int,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::TypeWrapper(Type::Int));
-                    }
-                    Token::Keyword {
-                        kind: KeywordKind::Void,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::TypeWrapper(Type::Void));
-                    }
-                    Token::Relop {
-                        kind: RelopKind::GT,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::CondWrapper(RelopKind::GT));
-                    }
-                    Token::Relop {
-                        kind: RelopKind::LT,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::CondWrapper(RelopKind::LT));
-                    }
-                    Token::Relop {
-                        kind: RelopKind::LE,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::CondWrapper(RelopKind::LE));
-                    }
-                    Token::Relop {
-                        kind: RelopKind::EQ,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::CondWrapper(RelopKind::EQ));
-                    }
-                    Token::Relop {
-                        kind: RelopKind::NE,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::CondWrapper(RelopKind::NE));
-                    }
-                    Token::Relop {
-                        kind: RelopKind::GE,
-                        ..
-                    } => {
-                        ast_stack.push(AstNode::CondWrapper(RelopKind::GE));
-                    }
-                    _ => {} //Parênteses e outros tokens não geram nós folhas diretos
-                };
+                let span = self.current_span;
+                ast_stack
+                    .extend(Self::leaf_node(current_token).map(|leaf| Spanned::new(leaf, span)));
 
                 self.stack.pop();
-                *current_token = self.lexer.get_next_token()?;
+                if let (Some(builder), Some(node)) = (self.parse_tree.as_mut(), node) {
+                    builder.set_terminal(node, current_token.clone(), self.current_span);
+                }
+
+                self.last_end = self.current_span.end;
+                *current_token = self.next_token()?;
                 Ok(())
             } else {
-                Err(format!(
-                    "Erro de sintaxe. Esperado {:?}, encontrado {}",
-                    terminal_type, current_token
-                ))
+                Err(ParserError::Syntax {
+                    message: format!(
+                        "Erro de sintaxe. Esperado {:?}, encontrado {}",
+                        terminal_type, current_token
+                    ),
+                    span: self.current_span,
+                })
             }
         } else {
             Ok(())
         }
     }
 
+    // Nó folha empilhado na AST ao casar o token
+    fn leaf_node(token: &Token) -> Option<AstNode> {
+        let node = match token {
+            Token::Id { value: name, .. } => AstNode::Identifier { name: name.clone() },
+            Token::Number { value, .. } => AstNode::Number {
+                value: value.clone(),
+            },
+            Token::Char { value, .. } => AstNode::Literal { value: *value },
+            Token::Keyword {
+                kind: KeywordKind::Char,
+                ..
+            } => AstNode::TypeWrapper(Type::Char),
+            Token::Keyword {
+                kind: KeywordKind::Float,
+                ..
+            } => AstNode::TypeWrapper(Type::Float),
+            Token::Keyword {
+                kind: KeywordKind::Int,
+                ..
+            } => AstNode::TypeWrapper(Type::Int),
+            Token::Keyword {
+                kind: KeywordKind::Void,
+                ..
+            } => AstNode::TypeWrapper(Type::Void),
+            Token::Keyword {
+                kind: KeywordKind::Bool,
+                ..
+            } => AstNode::TypeWrapper(Type::Bool),
+            Token::Keyword {
+                kind: KeywordKind::True,
+                ..
+            } => AstNode::Bool { value: true },
+            Token::Keyword {
+                kind: KeywordKind::False,
+                ..
+            } => AstNode::Bool { value: false },
+            Token::Relop {
+                kind: RelopKind::GT,
+                ..
+            } => AstNode::CondWrapper(RelopKind::GT),
+            Token::Relop {
+                kind: RelopKind::LT,
+                ..
+            } => AstNode::CondWrapper(RelopKind::LT),
+            Token::Relop {
+                kind: RelopKind::LE,
+                ..
+            } => AstNode::CondWrapper(RelopKind::LE),
+            Token::Relop {
+                kind: RelopKind::EQ,
+                ..
+            } => AstNode::CondWrapper(RelopKind::EQ),
+            Token::Relop {
+                kind: RelopKind::NE,
+                ..
+            } => AstNode::CondWrapper(RelopKind::NE),
+            Token::Relop {
+                kind: RelopKind::GE,
+                ..
+            } => AstNode::CondWrapper(RelopKind::GE),
+            _ => return None, //Parênteses e outros tokens não geram nós folhas diretos
+        };
+
+        Some(node)
+    }
+
     fn handle_non_terminal(
         &mut self,
         non_terminal: &str,
+        origin: Position,
+        node: Option<usize>,
         current_token: &Token,
-    ) -> Result<(), String> {
+    ) -> Result<(), ParserError> {
         if let Some(production) = self
             .parse_table
             .get_entry(non_terminal, &current_token.clone().into())
         {
             self.stack.pop();
 
+            // Não terminais com apóstrofo (T', cmd_if', ...) continuam o nó iniciado
+            // por quem os empilhou, então suas ações herdam a origem recebida
+            let start = self.current_span.start;
+            let continues_parent = non_terminal.ends_with('\'');
+
+            let symbol_nodes = match (self.parse_tree.as_mut(), node) {
+                (Some(builder), Some(node)) => builder.expand(node, non_terminal, production),
+                _ => vec![None; production.len()],
+            };
+
             // Empilha símbolos na ordem inversa
-            for symbol in production.iter().rev() {
+            for (symbol, symbol_node) in production.iter().zip(symbol_nodes).rev() {
                 if !symbol.is_epsilon() {
-                    self.stack.push(symbol.clone());
+                    let symbol_origin = if continues_parent && symbol.is_action() {
+                        origin
+                    } else {
+                        start
+                    };
+                    self.stack
+                        .push((symbol.clone(), symbol_origin, symbol_node));
                 }
             }
 
             Ok(())
         } else {
-            Err(format!(
-                "Erro de sintaxe. Não esperado {}, com não terminal {:?}",
-                current_token, non_terminal
-            ))
+            Err(ParserError::Syntax {
+                message: format!(
+                    "Erro de sintaxe. Não esperado {}, com não terminal {:?}",
+                    current_token, non_terminal
+                ),
+                span: self.current_span,
+            })
         }
     }
 }
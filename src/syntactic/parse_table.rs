@@ -5,21 +5,59 @@ use crate::{ActionKind, OperatorKind, TokenType, syntactic::symbol::Symbol};
 // Tabela de análise LL(1)
 pub struct ParseTable {
     table: HashMap<(String, TokenType), Vec<Symbol>>,
+    // Produções substituídas por outra diferente na mesma célula (conflitos LL(1))
+    conflicts: HashMap<(String, TokenType), Vec<Vec<Symbol>>>,
+    // Não terminais na ordem em que aparecem pela primeira vez
+    non_terminals: Vec<String>,
     pub start_symbol: String,
 }
 
 // Funções da tabela de análise
 impl ParseTable {
+    // FIRST de <atrib_op>: atribuições compostas, `++` e `--`
+    pub(super) const ATRIB_OPS: [TokenType; 6] = [
+        TokenType::AddAssignPunctuation,
+        TokenType::SubAssignPunctuation,
+        TokenType::MultAssignPunctuation,
+        TokenType::DivAssignPunctuation,
+        TokenType::IncrementPunctuation,
+        TokenType::DecrementPunctuation,
+    ];
+
+    // FIRST de <E>: operandos, parêntese e os unários `-` e `~`
+    const FIRST_E: [TokenType; 8] = [
+        TokenType::Id,
+        TokenType::Number,
+        TokenType::CharValue,
+        TokenType::TrueKeyword,
+        TokenType::FalseKeyword,
+        TokenType::LParenOperator,
+        TokenType::MinusOperator,
+        TokenType::BitNotOperator,
+    ];
+
     pub fn new(start_symbol: &str) -> Self {
         ParseTable {
             table: HashMap::new(),
+            conflicts: HashMap::new(),
+            non_terminals: Vec::new(),
             start_symbol: start_symbol.to_string(),
         }
     }
 
+    // Define M[A, a]. Se a célula já tinha outra produção, a nova vale para a
+    // análise e a antiga fica registrada como conflito.
     pub fn set_entry(&mut self, non_terminal: &str, terminal: TokenType, symbols: Vec<Symbol>) {
-        self.table
-            .insert((non_terminal.to_string(), terminal), symbols);
+        if !self.non_terminals.iter().any(|nt| nt == non_terminal) {
+            self.non_terminals.push(non_terminal.to_string());
+        }
+
+        let key = (non_terminal.to_string(), terminal);
+        if let Some(previous) = self.table.insert(key.clone(), symbols)
+            && self.table.get(&key) != Some(&previous)
+        {
+            self.conflicts.entry(key).or_default().push(previous);
+        }
     }
 
     pub fn get_entry(&self, non_terminal: &str, terminal: &TokenType) -> Option<&Vec<Symbol>> {
@@ -27,19 +65,57 @@ impl ParseTable {
             .get(&(non_terminal.to_string(), terminal.clone()))
     }
 
+    pub fn non_terminals(&self) -> &[String] {
+        &self.non_terminals
+    }
+
+    // Todas as produções registradas em M[A, a], da mais antiga à que vale para a
+    // análise. Mais de uma indica conflito.
+    pub fn entries(&self, non_terminal: &str, terminal: &TokenType) -> Vec<&Vec<Symbol>> {
+        let key = (non_terminal.to_string(), terminal.clone());
+
+        self.conflicts
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .chain(self.table.get(&key))
+            .collect()
+    }
+
+    // Células com conflito, na ordem dos não terminais e de `TokenType::ALL`
+    pub fn conflicts(&self) -> Vec<(&str, TokenType)> {
+        self.non_terminals
+            .iter()
+            .flat_map(|nt| {
+                TokenType::ALL
+                    .into_iter()
+                    .filter(|terminal| self.conflicts.contains_key(&(nt.clone(), terminal.clone())))
+                    .map(move |terminal| (nt.as_str(), terminal))
+            })
+            .collect()
+    }
+
     pub fn create_parse_table(start_symbol: &str) -> ParseTable {
         let mut table = ParseTable::new(start_symbol);
         table = ParseTable::create_expression_parse_table(table);
         table = ParseTable::create_programa_table(table);
+        table = ParseTable::create_params_table(table);
+        table = ParseTable::create_params_prime_table(table);
+        table = ParseTable::create_param_table(table);
         table = ParseTable::create_tipo_table(table);
         table = ParseTable::create_bloco_table(table);
         table = ParseTable::create_decls_table(table);
         table = ParseTable::create_decl_table(table);
-        table = ParseTable::create_lista_ids_table(table);
+        table = ParseTable::create_valor_inicial_table(table);
         table = ParseTable::create_lista_ids_prime_table(table);
         table = ParseTable::create_comandos_table(table);
         table = ParseTable::create_comando_table(table);
         table = ParseTable::create_cmd_atrib_table(table);
+        table = ParseTable::create_atrib_acesso_table(table);
+        table = ParseTable::create_atrib_op_table(table);
+        table = ParseTable::create_args_table(table);
+        table = ParseTable::create_args_prime_table(table);
+        table = ParseTable::create_cmd_return_table(table);
         table = ParseTable::create_cmd_if_table(table);
         table = ParseTable::create_cond_table(table);
         table = ParseTable::create_op_rel_table(table);
@@ -48,86 +124,385 @@ impl ParseTable {
         table = ParseTable::create_cmd_while_table(table);
         table = ParseTable::create_cmd_do_table(table);
         table = ParseTable::create_cmd_for_table(table);
+        table = ParseTable::create_cmd_switch_table(table);
+        table = ParseTable::create_casos_table(table);
+        table = ParseTable::create_rotulo_table(table);
+        table = ParseTable::create_padrao_table(table);
         table
     }
 
     /**
-     <inicio> -> <tipo> main ( ) <bloco>
+     <inicio> -> <tipo> <global>
+     <inicio> -> const <tipo> id <decl'> <globais>
+     <inicio> -> tipo id <inicio_tipo>
+     <inicio_tipo> -> [ <decls> ] <inicio>
+     <inicio_tipo> -> <global>
+     <global> -> main ( ) <bloco>
+     <global> -> id <global'>
+     <global'> -> ( <params> ) <bloco> <funcoes>
+     <global'> -> <decl'> <globais>
+     <globais> -> <tipo> <global>
+     <globais> -> const <tipo> id <decl'> <globais>
+     <inicio'> -> main ( ) <bloco>
+     <inicio'> -> id ( <params> ) <bloco> <funcoes>
+     <funcoes> -> <tipo> <inicio'>
+
+     Os registros vêm antes das declarações globais, que vêm antes das funções,
+     e por último `main`, o ponto de entrada que fecha o programa. Depois de
+     `tipo id`, o `[` decide entre a declaração de um registro e uma global ou
+     função do tipo registro. Depois de `<tipo> id`, o `(` decide entre uma
+     função e uma global; a partir da primeira função só vêm funções.
     */
     fn create_programa_table(mut table: ParseTable) -> ParseTable {
         let tipo = || Symbol::NonTerminal("tipo".to_string());
-        let main = || Symbol::Terminal(TokenType::MainKeyword);
+        let inicio = || Symbol::NonTerminal("inicio".to_string());
+        let inicio_prime = || Symbol::NonTerminal("inicio'".to_string());
+        let main = Symbol::Terminal(TokenType::MainKeyword);
+        let id = Symbol::Terminal(TokenType::Id);
         let lparen = || Symbol::Terminal(TokenType::LParenOperator);
         let rparen = || Symbol::Terminal(TokenType::RParenOperator);
+        let params = Symbol::NonTerminal("params".to_string());
         let bloco = || Symbol::NonTerminal("bloco".to_string());
-        let create_program_action = || Symbol::Action(ActionKind::CreateProgram);
-
+        let funcoes = || Symbol::NonTerminal("funcoes".to_string());
+        let inicio_tipo = Symbol::NonTerminal("inicio_tipo".to_string());
+        let decls = Symbol::NonTerminal("decls".to_string());
+        let create_program_action = Symbol::Action(ActionKind::CreateProgram);
+        let create_function_action = Symbol::Action(ActionKind::CreateFunction);
+        let add_function_action = Symbol::Action(ActionKind::AddFunction);
+        let record_type_action = || Symbol::Action(ActionKind::RecordType);
+        let global = || Symbol::NonTerminal("global".to_string());
+        let global_prime = Symbol::NonTerminal("global'".to_string());
+        let globais = || Symbol::NonTerminal("globais".to_string());
+        let decl_prime = || Symbol::NonTerminal("decl'".to_string());
+        let add_global_action = || Symbol::Action(ActionKind::AddGlobal);
+        // const <tipo> id <decl'> {MarkConst} <globais> {AddGlobal}
+        let const_global = || {
+            vec![
+                Symbol::Terminal(TokenType::ConstKeyword),
+                tipo(),
+                Symbol::Terminal(TokenType::Id),
+                decl_prime(),
+                Symbol::Action(ActionKind::MarkConst),
+                globais(),
+                add_global_action(),
+            ]
+        };
+
+        table.set_entry("inicio", TokenType::CharKeyword, vec![tipo(), global()]);
+        table.set_entry("inicio", TokenType::IntKeyword, vec![tipo(), global()]);
+        table.set_entry("inicio", TokenType::FloatKeyword, vec![tipo(), global()]);
+        table.set_entry("inicio", TokenType::VoidKeyword, vec![tipo(), global()]);
+        table.set_entry("inicio", TokenType::BoolKeyword, vec![tipo(), global()]);
         table.set_entry(
             "inicio",
-            TokenType::CharKeyword,
+            TokenType::TypeKeyword,
             vec![
-                tipo(),
-                main(),
-                lparen(),
-                rparen(),
-                bloco(),
-                create_program_action(),
+                Symbol::Terminal(TokenType::TypeKeyword),
+                Symbol::Terminal(TokenType::Id),
+                inicio_tipo,
             ],
         );
+        table.set_entry("inicio", TokenType::ConstKeyword, const_global());
+
         table.set_entry(
-            "inicio",
-            TokenType::IntKeyword,
+            "inicio_tipo",
+            TokenType::BeginBlockPunctuation,
             vec![
-                tipo(),
-                main(),
+                Symbol::Terminal(TokenType::BeginBlockPunctuation),
+                decls,
+                Symbol::Terminal(TokenType::EndBlockPunctuation),
+                Symbol::Action(ActionKind::CreateRecord),
+                inicio(),
+                Symbol::Action(ActionKind::AddRecord),
+            ],
+        );
+        table.set_entry(
+            "inicio_tipo",
+            TokenType::MainKeyword,
+            vec![record_type_action(), global()],
+        );
+        table.set_entry(
+            "inicio_tipo",
+            TokenType::Id,
+            vec![record_type_action(), global()],
+        );
+
+        // <global> -> main ( ) <bloco>, sem globais nem funções
+        table.set_entry(
+            "global",
+            TokenType::MainKeyword,
+            vec![
+                Symbol::Terminal(TokenType::MainKeyword),
                 lparen(),
                 rparen(),
                 bloco(),
-                create_program_action(),
+                Symbol::Action(ActionKind::CreateProgram),
             ],
         );
         table.set_entry(
-            "inicio",
-            TokenType::FloatKeyword,
+            "global",
+            TokenType::Id,
+            vec![Symbol::Terminal(TokenType::Id), global_prime],
+        );
+
+        // <global'> -> ( <params> ) <bloco> <funcoes>: a primeira função
+        table.set_entry(
+            "global'",
+            TokenType::LParenOperator,
             vec![
-                tipo(),
-                main(),
                 lparen(),
+                Symbol::NonTerminal("params".to_string()),
                 rparen(),
                 bloco(),
-                create_program_action(),
+                Symbol::Action(ActionKind::CreateFunction),
+                funcoes(),
+                Symbol::Action(ActionKind::AddFunction),
             ],
         );
-        table.set_entry(
-            "inicio",
+
+        // <global'> -> <decl'> <globais>, no FIRST de <decl'>
+        for terminal in [
+            TokenType::BeginBlockPunctuation,
+            TokenType::AssignPunctuation,
+            TokenType::CommaPunctuation,
+            TokenType::SemiColonPunctuation,
+        ] {
+            table.set_entry(
+                "global'",
+                terminal,
+                vec![decl_prime(), globais(), add_global_action()],
+            );
+        }
+
+        for terminal in [
+            TokenType::CharKeyword,
+            TokenType::IntKeyword,
+            TokenType::FloatKeyword,
             TokenType::VoidKeyword,
+            TokenType::BoolKeyword,
+            TokenType::TypeKeyword,
+        ] {
+            table.set_entry("globais", terminal, vec![tipo(), global()]);
+        }
+        table.set_entry("globais", TokenType::ConstKeyword, const_global());
+
+        table.set_entry(
+            "inicio'",
+            TokenType::MainKeyword,
+            vec![main, lparen(), rparen(), bloco(), create_program_action],
+        );
+        table.set_entry(
+            "inicio'",
+            TokenType::Id,
             vec![
-                tipo(),
-                main(),
+                id,
                 lparen(),
+                params,
                 rparen(),
                 bloco(),
-                create_program_action(),
+                create_function_action,
+                funcoes(),
+                add_function_action,
             ],
         );
 
+        table.set_entry(
+            "funcoes",
+            TokenType::CharKeyword,
+            vec![tipo(), inicio_prime()],
+        );
+        table.set_entry(
+            "funcoes",
+            TokenType::IntKeyword,
+            vec![tipo(), inicio_prime()],
+        );
+        table.set_entry(
+            "funcoes",
+            TokenType::FloatKeyword,
+            vec![tipo(), inicio_prime()],
+        );
+        table.set_entry(
+            "funcoes",
+            TokenType::VoidKeyword,
+            vec![tipo(), inicio_prime()],
+        );
+        table.set_entry(
+            "funcoes",
+            TokenType::BoolKeyword,
+            vec![tipo(), inicio_prime()],
+        );
+        table.set_entry(
+            "funcoes",
+            TokenType::TypeKeyword,
+            vec![tipo(), inicio_prime()],
+        );
+
         table
     }
 
     /**
-     <tipo> -> float | int | char | void
+     <params> -> <param> <params'> | ε
+    */
+    fn create_params_table(mut table: ParseTable) -> ParseTable {
+        let param = || Symbol::NonTerminal("param".to_string());
+        let params_prime = || Symbol::NonTerminal("params'".to_string());
+        let action_append_list = || Symbol::Action(ActionKind::AppendList);
+
+        table.set_entry(
+            "params",
+            TokenType::CharKeyword,
+            vec![param(), params_prime(), action_append_list()],
+        );
+        table.set_entry(
+            "params",
+            TokenType::IntKeyword,
+            vec![param(), params_prime(), action_append_list()],
+        );
+        table.set_entry(
+            "params",
+            TokenType::FloatKeyword,
+            vec![param(), params_prime(), action_append_list()],
+        );
+        table.set_entry(
+            "params",
+            TokenType::VoidKeyword,
+            vec![param(), params_prime(), action_append_list()],
+        );
+        table.set_entry(
+            "params",
+            TokenType::BoolKeyword,
+            vec![param(), params_prime(), action_append_list()],
+        );
+        table.set_entry(
+            "params",
+            TokenType::TypeKeyword,
+            vec![param(), params_prime(), action_append_list()],
+        );
+
+        table.set_entry(
+            "params",
+            TokenType::RParenOperator,
+            vec![Symbol::Epsilon, Symbol::Action(ActionKind::MakeList)],
+        );
+
+        table
+    }
+
+    /**
+     <params'> -> , <param> <params'> | ε
+    */
+    fn create_params_prime_table(mut table: ParseTable) -> ParseTable {
+        let comma = Symbol::Terminal(TokenType::CommaPunctuation);
+        let param = Symbol::NonTerminal("param".to_string());
+        let params_prime = Symbol::NonTerminal("params'".to_string());
+        let action_append_list = Symbol::Action(ActionKind::AppendList);
+        let action_make_list = Symbol::Action(ActionKind::MakeList);
+
+        table.set_entry(
+            "params'",
+            TokenType::CommaPunctuation,
+            vec![comma, param, params_prime, action_append_list],
+        );
+
+        table.set_entry(
+            "params'",
+            TokenType::RParenOperator,
+            vec![Symbol::Epsilon, action_make_list],
+        );
+
+        table
+    }
+
+    /**
+     <param> -> <tipo> id <param'>
+     <param'> -> [ num ] | ε
+    */
+    fn create_param_table(mut table: ParseTable) -> ParseTable {
+        let tipo = || Symbol::NonTerminal("tipo".to_string());
+        let id = || Symbol::Terminal(TokenType::Id);
+        let param_prime = || Symbol::NonTerminal("param'".to_string());
+        let lbracket = Symbol::Terminal(TokenType::BeginBlockPunctuation);
+        let num = Symbol::Terminal(TokenType::Number);
+        let rbracket = Symbol::Terminal(TokenType::EndBlockPunctuation);
+        let action_create_param = || Symbol::Action(ActionKind::CreateParam);
+        let action_create_array_decl = Symbol::Action(ActionKind::CreateArrayDecl);
+
+        table.set_entry(
+            "param",
+            TokenType::CharKeyword,
+            vec![tipo(), id(), param_prime()],
+        );
+        table.set_entry(
+            "param",
+            TokenType::IntKeyword,
+            vec![tipo(), id(), param_prime()],
+        );
+        table.set_entry(
+            "param",
+            TokenType::FloatKeyword,
+            vec![tipo(), id(), param_prime()],
+        );
+        table.set_entry(
+            "param",
+            TokenType::VoidKeyword,
+            vec![tipo(), id(), param_prime()],
+        );
+        table.set_entry(
+            "param",
+            TokenType::BoolKeyword,
+            vec![tipo(), id(), param_prime()],
+        );
+        table.set_entry(
+            "param",
+            TokenType::TypeKeyword,
+            vec![tipo(), id(), param_prime()],
+        );
+
+        table.set_entry(
+            "param'",
+            TokenType::BeginBlockPunctuation,
+            vec![lbracket, num, rbracket, action_create_array_decl],
+        );
+        table.set_entry(
+            "param'",
+            TokenType::CommaPunctuation,
+            vec![Symbol::Epsilon, action_create_param()],
+        );
+        table.set_entry(
+            "param'",
+            TokenType::RParenOperator,
+            vec![Symbol::Epsilon, action_create_param()],
+        );
+
+        table
+    }
+
+    /**
+     <tipo> -> float | int | char | bool | void | tipo id
+
+     Um registro é usado pelo nome, e precisa ter sido declarado antes
     */
     fn create_tipo_table(mut table: ParseTable) -> ParseTable {
         let float = || Symbol::Terminal(TokenType::FloatKeyword);
         let int = || Symbol::Terminal(TokenType::IntKeyword);
         let char = || Symbol::Terminal(TokenType::CharKeyword);
         let void = || Symbol::Terminal(TokenType::VoidKeyword);
+        let bool = || Symbol::Terminal(TokenType::BoolKeyword);
+        let tipo = Symbol::Terminal(TokenType::TypeKeyword);
+        let id = Symbol::Terminal(TokenType::Id);
+        let action_record_type = Symbol::Action(ActionKind::RecordType);
 
         // Não sei ainda se tem Action aqui, acho que não
         table.set_entry("tipo", TokenType::FloatKeyword, vec![float()]);
         table.set_entry("tipo", TokenType::IntKeyword, vec![int()]);
         table.set_entry("tipo", TokenType::CharKeyword, vec![char()]);
         table.set_entry("tipo", TokenType::VoidKeyword, vec![void()]);
+        table.set_entry("tipo", TokenType::BoolKeyword, vec![bool()]);
+        table.set_entry(
+            "tipo",
+            TokenType::TypeKeyword,
+            vec![tipo, id, action_record_type],
+        );
 
         table
     }
@@ -182,6 +557,21 @@ impl ParseTable {
             TokenType::VoidKeyword,
             vec![decl(), decls(), action_append_list()],
         );
+        table.set_entry(
+            "decls",
+            TokenType::BoolKeyword,
+            vec![decl(), decls(), action_append_list()],
+        );
+        table.set_entry(
+            "decls",
+            TokenType::TypeKeyword,
+            vec![decl(), decls(), action_append_list()],
+        );
+        table.set_entry(
+            "decls",
+            TokenType::ConstKeyword,
+            vec![decl(), decls(), action_append_list()],
+        );
 
         // <delcs> -> ε
         table.set_entry("decls", TokenType::Id, vec![epsilon(), action_make_list()]);
@@ -205,6 +595,31 @@ impl ParseTable {
             TokenType::ForKeyword,
             vec![epsilon(), action_make_list()],
         );
+        table.set_entry(
+            "decls",
+            TokenType::ReturnKeyword,
+            vec![epsilon(), action_make_list()],
+        );
+        table.set_entry(
+            "decls",
+            TokenType::BreakKeyword,
+            vec![epsilon(), action_make_list()],
+        );
+        table.set_entry(
+            "decls",
+            TokenType::ContinueKeyword,
+            vec![epsilon(), action_make_list()],
+        );
+        table.set_entry(
+            "decls",
+            TokenType::SwitchKeyword,
+            vec![epsilon(), action_make_list()],
+        );
+        table.set_entry(
+            "decls",
+            TokenType::BeginBlockPunctuation,
+            vec![epsilon(), action_make_list()],
+        );
         table.set_entry(
             "decls",
             TokenType::EndBlockPunctuation,
@@ -215,61 +630,147 @@ impl ParseTable {
     }
 
     /**
-     <decl> -> <tipo> <lista_ids> ;
+     <decl> -> <tipo> id <decl'>
+     <decl> -> const <tipo> id <decl'>
+     <decl'> -> [ num ] ;
+     <decl'> -> <valor_inicial> <lista_ids'> ;
+
+     Um vetor é declarado sozinho (`int v[10];`); o `[` depois do primeiro
+     identificador decide entre vetor e lista de variáveis. A exigência de valor
+     inicial nas constantes é verificada pela ação `MarkConst`.
     */
     fn create_decl_table(mut table: ParseTable) -> ParseTable {
         let tipo = || Symbol::NonTerminal("tipo".to_string());
-        let lista_ids = || Symbol::NonTerminal("lista_ids".to_string());
+        let id = || Symbol::Terminal(TokenType::Id);
+        let decl_prime = || Symbol::NonTerminal("decl'".to_string());
+        let lbracket = Symbol::Terminal(TokenType::BeginBlockPunctuation);
+        let num = Symbol::Terminal(TokenType::Number);
+        let rbracket = Symbol::Terminal(TokenType::EndBlockPunctuation);
+        let lista_ids_prime = || Symbol::NonTerminal("lista_ids'".to_string());
         let end_exp = || Symbol::Terminal(TokenType::SemiColonPunctuation);
+        let action_append_list = || Symbol::Action(ActionKind::AppendList);
         let action_create_decl = || Symbol::Action(ActionKind::CreateDecl);
+        let action_create_array_decl = Symbol::Action(ActionKind::CreateArrayDecl);
+        let valor_inicial = || Symbol::NonTerminal("valor_inicial".to_string());
+        let const_keyword = Symbol::Terminal(TokenType::ConstKeyword);
+        let action_mark_const = Symbol::Action(ActionKind::MarkConst);
 
         table.set_entry(
             "decl",
             TokenType::CharKeyword,
-            vec![tipo(), lista_ids(), end_exp(), action_create_decl()],
+            vec![tipo(), id(), decl_prime()],
         );
         table.set_entry(
             "decl",
             TokenType::IntKeyword,
-            vec![tipo(), lista_ids(), end_exp(), action_create_decl()],
+            vec![tipo(), id(), decl_prime()],
         );
         table.set_entry(
             "decl",
             TokenType::FloatKeyword,
-            vec![tipo(), lista_ids(), end_exp(), action_create_decl()],
+            vec![tipo(), id(), decl_prime()],
         );
         table.set_entry(
             "decl",
             TokenType::VoidKeyword,
-            vec![tipo(), lista_ids(), end_exp(), action_create_decl()],
+            vec![tipo(), id(), decl_prime()],
+        );
+        table.set_entry(
+            "decl",
+            TokenType::BoolKeyword,
+            vec![tipo(), id(), decl_prime()],
+        );
+        table.set_entry(
+            "decl",
+            TokenType::TypeKeyword,
+            vec![tipo(), id(), decl_prime()],
+        );
+        table.set_entry(
+            "decl",
+            TokenType::ConstKeyword,
+            vec![const_keyword, tipo(), id(), decl_prime(), action_mark_const],
+        );
+
+        table.set_entry(
+            "decl'",
+            TokenType::BeginBlockPunctuation,
+            vec![lbracket, num, rbracket, end_exp(), action_create_array_decl],
+        );
+        table.set_entry(
+            "decl'",
+            TokenType::AssignPunctuation,
+            vec![
+                valor_inicial(),
+                lista_ids_prime(),
+                action_append_list(),
+                end_exp(),
+                action_create_decl(),
+            ],
+        );
+        table.set_entry(
+            "decl'",
+            TokenType::CommaPunctuation,
+            vec![
+                valor_inicial(),
+                lista_ids_prime(),
+                action_append_list(),
+                end_exp(),
+                action_create_decl(),
+            ],
+        );
+        table.set_entry(
+            "decl'",
+            TokenType::SemiColonPunctuation,
+            vec![
+                valor_inicial(),
+                lista_ids_prime(),
+                action_append_list(),
+                end_exp(),
+                action_create_decl(),
+            ],
         );
 
         table
     }
 
     /**
-     <lista_ids> -> id <lista_ids'>
+     <valor_inicial> -> := <E> | ε
+
+     Com valor, o identificador vira uma atribuição na lista de nomes
     */
-    fn create_lista_ids_table(mut table: ParseTable) -> ParseTable {
-        let id = Symbol::Terminal(TokenType::Id);
-        let lista_ids_prime = Symbol::NonTerminal("lista_ids'".to_string());
-        let action_append_list = Symbol::Action(ActionKind::AppendList);
+    fn create_valor_inicial_table(mut table: ParseTable) -> ParseTable {
+        let assign = Symbol::Terminal(TokenType::AssignPunctuation);
+        let expression = Symbol::NonTerminal("E".to_string());
+        let action_assign = Symbol::Action(ActionKind::Assign);
+        let epsilon = || Symbol::Epsilon;
 
         table.set_entry(
-            "lista_ids",
-            TokenType::Id,
-            vec![id, lista_ids_prime, action_append_list],
+            "valor_inicial",
+            TokenType::AssignPunctuation,
+            vec![assign, expression, action_assign],
+        );
+
+        table.set_entry(
+            "valor_inicial",
+            TokenType::CommaPunctuation,
+            vec![epsilon()],
+        );
+        table.set_entry(
+            "valor_inicial",
+            TokenType::SemiColonPunctuation,
+            vec![epsilon()],
         );
 
         table
     }
 
     /**
-     <lista_ids'> -> , id <lista_ids'> | ε
+     <lista_ids'> -> , id <valor_inicial> <lista_ids'> | ε
     */
     fn create_lista_ids_prime_table(mut table: ParseTable) -> ParseTable {
         let comma = Symbol::Terminal(TokenType::CommaPunctuation);
         let id = Symbol::Terminal(TokenType::Id);
+        let valor_inicial = Symbol::NonTerminal("valor_inicial".to_string());
         let lista_ids_prime = Symbol::NonTerminal("lista_ids'".to_string());
         let epsilon = Symbol::Epsilon;
         let action_append_list = Symbol::Action(ActionKind::AppendList);
@@ -278,7 +779,13 @@ impl ParseTable {
         table.set_entry(
             "lista_ids'",
             TokenType::CommaPunctuation,
-            vec![comma, id, lista_ids_prime, action_append_list],
+            vec![
+                comma,
+                id,
+                valor_inicial,
+                lista_ids_prime,
+                action_append_list,
+            ],
         );
 
         table.set_entry(
@@ -311,236 +818,597 @@ impl ParseTable {
             vec![comando(), comandos(), action_append_list()],
         );
         table.set_entry(
-            "comandos",
-            TokenType::WhileKeyword,
-            vec![comando(), comandos(), action_append_list()],
+            "comandos",
+            TokenType::WhileKeyword,
+            vec![comando(), comandos(), action_append_list()],
+        );
+        table.set_entry(
+            "comandos",
+            TokenType::DoKeyword,
+            vec![comando(), comandos(), action_append_list()],
+        );
+        table.set_entry(
+            "comandos",
+            TokenType::ForKeyword,
+            vec![comando(), comandos(), action_append_list()],
+        );
+        table.set_entry(
+            "comandos",
+            TokenType::ReturnKeyword,
+            vec![comando(), comandos(), action_append_list()],
+        );
+        table.set_entry(
+            "comandos",
+            TokenType::BreakKeyword,
+            vec![comando(), comandos(), action_append_list()],
+        );
+        table.set_entry(
+            "comandos",
+            TokenType::ContinueKeyword,
+            vec![comando(), comandos(), action_append_list()],
+        );
+        table.set_entry(
+            "comandos",
+            TokenType::SwitchKeyword,
+            vec![comando(), comandos(), action_append_list()],
+        );
+        table.set_entry(
+            "comandos",
+            TokenType::BeginBlockPunctuation,
+            vec![comando(), comandos(), action_append_list()],
+        );
+
+        table.set_entry(
+            "comandos",
+            TokenType::EndBlockPunctuation,
+            vec![epsilon(), action_make_list()],
+        );
+
+        table
+    }
+
+    /**
+     <comando> -> <cmd_atrib> | <cmd_if> | <cmd_while> | <cmd_do> | <cmd_for> | <cmd_switch> | <cmd_return> | <bloco>
+     <comando> -> break ; | continue ;
+
+     `break` e `continue` fora de um laço são rejeitados ao executar a ação
+    */
+    fn create_comando_table(mut table: ParseTable) -> ParseTable {
+        let cmd_atrib = || Symbol::NonTerminal("cmd_atrib".to_string());
+        let cmd_if = || Symbol::NonTerminal("cmd_if".to_string());
+        let cmd_while = || Symbol::NonTerminal("cmd_while".to_string());
+        let cmd_do = || Symbol::NonTerminal("cmd_do".to_string());
+        let cmd_for = || Symbol::NonTerminal("cmd_for".to_string());
+        let cmd_switch = || Symbol::NonTerminal("cmd_switch".to_string());
+        let cmd_return = || Symbol::NonTerminal("cmd_return".to_string());
+        let bloco = || Symbol::NonTerminal("bloco".to_string());
+        let semicolon = || Symbol::Terminal(TokenType::SemiColonPunctuation);
+
+        table.set_entry("comando", TokenType::Id, vec![cmd_atrib()]);
+
+        table.set_entry("comando", TokenType::IfKeyword, vec![cmd_if()]);
+
+        table.set_entry("comando", TokenType::WhileKeyword, vec![cmd_while()]);
+
+        table.set_entry("comando", TokenType::DoKeyword, vec![cmd_do()]);
+
+        table.set_entry("comando", TokenType::ForKeyword, vec![cmd_for()]);
+
+        table.set_entry("comando", TokenType::SwitchKeyword, vec![cmd_switch()]);
+
+        table.set_entry("comando", TokenType::ReturnKeyword, vec![cmd_return()]);
+
+        table.set_entry(
+            "comando",
+            TokenType::BreakKeyword,
+            vec![
+                Symbol::Terminal(TokenType::BreakKeyword),
+                semicolon(),
+                Symbol::Action(ActionKind::CreateBreak),
+            ],
+        );
+
+        table.set_entry(
+            "comando",
+            TokenType::ContinueKeyword,
+            vec![
+                Symbol::Terminal(TokenType::ContinueKeyword),
+                semicolon(),
+                Symbol::Action(ActionKind::CreateContinue),
+            ],
+        );
+
+        table.set_entry("comando", TokenType::BeginBlockPunctuation, vec![bloco()]);
+
+        table
+    }
+
+    /**
+     <cmd_atrib> -> id <cmd_atrib'>
+     <cmd_atrib'> -> := <E> ;
+     <cmd_atrib'> -> <atrib_op>
+     <cmd_atrib'> -> ( <args> ) ;
+     <cmd_atrib'> -> <acesso> <acessos> <atrib_acesso>
+
+     A chamada de função como comando também começa com o identificador
+    */
+    fn create_cmd_atrib_table(mut table: ParseTable) -> ParseTable {
+        let id = Symbol::Terminal(TokenType::Id);
+        let cmd_atrib_prime = Symbol::NonTerminal("cmd_atrib'".to_string());
+        let atrib = Symbol::Terminal(TokenType::AssignPunctuation);
+        let expr = Symbol::NonTerminal("E".to_string());
+        let atrib_op = || Symbol::NonTerminal("atrib_op".to_string());
+        let lparen = Symbol::Terminal(TokenType::LParenOperator);
+        let args = Symbol::NonTerminal("args".to_string());
+        let rparen = Symbol::Terminal(TokenType::RParenOperator);
+        let end_expr = || Symbol::Terminal(TokenType::SemiColonPunctuation);
+        let action_assign = Symbol::Action(ActionKind::Assign);
+        let action_create_call = Symbol::Action(ActionKind::CreateCall);
+        let acesso = || Symbol::NonTerminal("acesso".to_string());
+        let acessos = || Symbol::NonTerminal("acessos".to_string());
+        let atrib_acesso = || Symbol::NonTerminal("atrib_acesso".to_string());
+
+        table.set_entry("cmd_atrib", TokenType::Id, vec![id, cmd_atrib_prime]);
+
+        table.set_entry(
+            "cmd_atrib'",
+            TokenType::AssignPunctuation,
+            vec![atrib, expr, end_expr(), action_assign],
         );
+        for op in ParseTable::ATRIB_OPS {
+            table.set_entry("cmd_atrib'", op, vec![atrib_op()]);
+        }
         table.set_entry(
-            "comandos",
-            TokenType::DoKeyword,
-            vec![comando(), comandos(), action_append_list()],
+            "cmd_atrib'",
+            TokenType::LParenOperator,
+            vec![lparen, args, rparen, end_expr(), action_create_call],
         );
         table.set_entry(
-            "comandos",
-            TokenType::ForKeyword,
-            vec![comando(), comandos(), action_append_list()],
+            "cmd_atrib'",
+            TokenType::BeginBlockPunctuation,
+            vec![acesso(), acessos(), atrib_acesso()],
         );
         table.set_entry(
-            "comandos",
-            TokenType::BeginBlockPunctuation,
-            vec![comando(), comandos(), action_append_list()],
+            "cmd_atrib'",
+            TokenType::DotPunctuation,
+            vec![acesso(), acessos(), atrib_acesso()],
         );
 
+        table
+    }
+
+    /**
+     <atrib_acesso> -> := <E> ; | <atrib_op>
+    */
+    fn create_atrib_acesso_table(mut table: ParseTable) -> ParseTable {
+        let atrib = Symbol::Terminal(TokenType::AssignPunctuation);
+        let expr = Symbol::NonTerminal("E".to_string());
+        let end_expr = Symbol::Terminal(TokenType::SemiColonPunctuation);
+        let atrib_op = || Symbol::NonTerminal("atrib_op".to_string());
+        let action_assign_access = Symbol::Action(ActionKind::AssignAccess);
+
         table.set_entry(
-            "comandos",
-            TokenType::EndBlockPunctuation,
-            vec![epsilon(), action_make_list()],
+            "atrib_acesso",
+            TokenType::AssignPunctuation,
+            vec![atrib, expr, end_expr, action_assign_access],
         );
+        for op in ParseTable::ATRIB_OPS {
+            table.set_entry("atrib_acesso", op, vec![atrib_op()]);
+        }
 
         table
     }
 
     /**
-     <comando> -> <cmd_atrib> | <cmd_if> | <cmd_while> | <cmd_do> | <cmd_for> | <bloco>
+     <atrib_op> -> += <E> ; | -= <E> ; | *= <E> ; | /= <E> ;
+     <atrib_op> -> ++ ; | -- ;
+
+     Açúcar sintático: `x += e` vira `x := x + e` e `x++` vira `x := x + 1`,
+     também quando o alvo é um elemento ou campo. O alvo é avaliado duas vezes,
+     então `v[f()] += 1` chama `f` duas vezes.
     */
-    fn create_comando_table(mut table: ParseTable) -> ParseTable {
-        let cmd_atrib = || Symbol::NonTerminal("cmd_atrib".to_string());
-        let cmd_if = || Symbol::NonTerminal("cmd_if".to_string());
-        let cmd_while = || Symbol::NonTerminal("cmd_while".to_string());
-        let cmd_do = || Symbol::NonTerminal("cmd_do".to_string());
-        let cmd_for = || Symbol::NonTerminal("cmd_for".to_string());
-        let bloco = || Symbol::NonTerminal("bloco".to_string());
+    fn create_atrib_op_table(mut table: ParseTable) -> ParseTable {
+        let expr = || Symbol::NonTerminal("E".to_string());
+        let end_expr = || Symbol::Terminal(TokenType::SemiColonPunctuation);
+
+        for (terminal, op) in [
+            (TokenType::AddAssignPunctuation, OperatorKind::Sum),
+            (TokenType::SubAssignPunctuation, OperatorKind::Sub),
+            (TokenType::MultAssignPunctuation, OperatorKind::Mult),
+            (TokenType::DivAssignPunctuation, OperatorKind::Div),
+        ] {
+            table.set_entry(
+                "atrib_op",
+                terminal.clone(),
+                vec![
+                    Symbol::Terminal(terminal),
+                    expr(),
+                    end_expr(),
+                    Symbol::Action(ActionKind::AssignOp(op)),
+                ],
+            );
+        }
 
-        table.set_entry("comando", TokenType::Id, vec![cmd_atrib()]);
+        for (terminal, op) in [
+            (TokenType::IncrementPunctuation, OperatorKind::Sum),
+            (TokenType::DecrementPunctuation, OperatorKind::Sub),
+        ] {
+            table.set_entry(
+                "atrib_op",
+                terminal.clone(),
+                vec![
+                    Symbol::Terminal(terminal),
+                    end_expr(),
+                    Symbol::Action(ActionKind::AssignStep(op)),
+                ],
+            );
+        }
 
-        table.set_entry("comando", TokenType::IfKeyword, vec![cmd_if()]);
+        table
+    }
 
-        table.set_entry("comando", TokenType::WhileKeyword, vec![cmd_while()]);
+    /**
+     <args> -> <E> <args'> | ε
+    */
+    fn create_args_table(mut table: ParseTable) -> ParseTable {
+        let expr = || Symbol::NonTerminal("E".to_string());
+        let args_prime = || Symbol::NonTerminal("args'".to_string());
+        let action_append_list = || Symbol::Action(ActionKind::AppendList);
 
-        table.set_entry("comando", TokenType::DoKeyword, vec![cmd_do()]);
+        for first in ParseTable::FIRST_E {
+            table.set_entry(
+                "args",
+                first,
+                vec![expr(), args_prime(), action_append_list()],
+            );
+        }
 
-        table.set_entry("comando", TokenType::ForKeyword, vec![cmd_for()]);
+        table.set_entry(
+            "args",
+            TokenType::RParenOperator,
+            vec![Symbol::Epsilon, Symbol::Action(ActionKind::MakeList)],
+        );
 
-        table.set_entry("comando", TokenType::BeginBlockPunctuation, vec![bloco()]);
+        table
+    }
+
+    /**
+     <args'> -> , <E> <args'> | ε
+    */
+    fn create_args_prime_table(mut table: ParseTable) -> ParseTable {
+        let comma = Symbol::Terminal(TokenType::CommaPunctuation);
+        let expr = Symbol::NonTerminal("E".to_string());
+        let args_prime = Symbol::NonTerminal("args'".to_string());
+        let action_append_list = Symbol::Action(ActionKind::AppendList);
+        let action_make_list = Symbol::Action(ActionKind::MakeList);
+
+        table.set_entry(
+            "args'",
+            TokenType::CommaPunctuation,
+            vec![comma, expr, args_prime, action_append_list],
+        );
+
+        table.set_entry(
+            "args'",
+            TokenType::RParenOperator,
+            vec![Symbol::Epsilon, action_make_list],
+        );
 
         table
     }
 
     /**
-     <cmd_atrib> -> id := <E> ;
+     <cmd_return> -> return <E> ;
     */
-    fn create_cmd_atrib_table(mut table: ParseTable) -> ParseTable {
-        let id = Symbol::Terminal(TokenType::Id);
-        let atrib = Symbol::Terminal(TokenType::AssignPunctuation);
+    fn create_cmd_return_table(mut table: ParseTable) -> ParseTable {
+        let retorno = Symbol::Terminal(TokenType::ReturnKeyword);
         let expr = Symbol::NonTerminal("E".to_string());
         let end_expr = Symbol::Terminal(TokenType::SemiColonPunctuation);
-        let action_assign = Symbol::Action(ActionKind::Assign);
+        let action_create_return = Symbol::Action(ActionKind::CreateReturn);
 
         table.set_entry(
-            "cmd_atrib",
-            TokenType::Id,
-            vec![id, atrib, expr, end_expr, action_assign],
+            "cmd_return",
+            TokenType::ReturnKeyword,
+            vec![retorno, expr, end_expr, action_create_return],
         );
 
         table
     }
 
     /**
-     E  → T E'
-     E' → + T E' | - T E' | ε
-     T  → F T'
-     T' → * F T' | / F T' | ε
-     F  → - F | U F'
-     F' → ** U F' | ε
-     U  → id | num | char | ( E )
+     Precedência e associatividade, da menor para a maior:
+
+     | nível | operadores            | associatividade | não terminais |
+     |-------|-----------------------|-----------------|---------------|
+     | 1     | == != < > <= >=       | não associativa | E, R          |
+     | 2     | `|`                   | à esquerda      | O, O'         |
+     | 3     | ^                     | à esquerda      | X, X'         |
+     | 4     | &                     | à esquerda      | A, A'         |
+     | 5     | << >>                 | à esquerda      | D, D'         |
+     | 6     | + -                   | à esquerda      | S, S'         |
+     | 7     | * / %                 | à esquerda      | T, T'         |
+     | 8     | - ~ (unários)         | prefixo         | F             |
+     | 9     | **                    | à direita       | F'            |
+
+     E  → O R
+     R  → <op_rel> O | ε
+     O  → X O'          O' → | X O' | ε
+     X  → A X'          X' → ^ A X' | ε
+     A  → D A'          A' → & D A' | ε
+     D  → S D'          D' → << S D' | >> S D' | ε
+     S  → T S'          S' → + T S' | - T S' | ε
+     T  → F T'          T' → * F T' | / F T' | % F T' | ε
+     F  → - F | ~ F | U F'
+     F' → ** F | ε
+     U  → id U' | num | char | true | false | ( E )
+     U' → ( <args> ) | <acesso> <acessos> | ε
+     <acesso> → [ E ] | . id
+     <acessos> → <acesso> <acessos> | ε
+
+     Nos níveis à esquerda a ação {Math(op)} vem antes da cauda, então `a - b - c`
+     vira `(a - b) - c`. Em F' a ação vem depois de F, que já inclui o próximo
+     `**`: `a ** b ** c` vira `a ** (b ** c)`. Como F' chama F, o expoente aceita
+     unários (`2 ** -1`), e `-a ** b` é `-(a ** b)`.
+
+     A comparação fica abaixo dos operadores bit a bit e não se encadeia:
+     `a < b < c` é erro de sintaxe, e `(a < b) == c` compara dois lógicos.
+
+     Os mesmos níveis estão em `BINARY_OPERATORS`, usado quando o parser analisa
+     as expressões por precedência (`with_pratt_expressions`); um operador novo
+     precisa entrar nos dois
     */
     fn create_expression_parse_table(mut table: ParseTable) -> ParseTable {
         // Define os símbolos
         let e = || Symbol::NonTerminal("E".to_string());
-        let e_prime = || Symbol::NonTerminal("E'".to_string());
-        let t = || Symbol::NonTerminal("T".to_string());
-        let t_prime = || Symbol::NonTerminal("T'".to_string());
+        let r = || Symbol::NonTerminal("R".to_string());
+        let o = || Symbol::NonTerminal("O".to_string());
+        let op_rel = || Symbol::NonTerminal("op_rel".to_string());
         let f = || Symbol::NonTerminal("F".to_string());
         let f_prime = || Symbol::NonTerminal("F'".to_string());
         let u = || Symbol::NonTerminal("U".to_string());
+        let u_prime = || Symbol::NonTerminal("U'".to_string());
+        let args = || Symbol::NonTerminal("args".to_string());
+        let acesso = || Symbol::NonTerminal("acesso".to_string());
+        let acessos = || Symbol::NonTerminal("acessos".to_string());
 
-        let plus = || Symbol::Terminal(TokenType::PlusOperator);
-        let minus = || Symbol::Terminal(TokenType::MinusOperator);
-        let mult = || Symbol::Terminal(TokenType::MultOperator);
-        let div = || Symbol::Terminal(TokenType::DivOperator);
-        let exp = || Symbol::Terminal(TokenType::ExpOperator);
+        let exp = Symbol::Terminal(TokenType::ExpOperator);
         let lparen = || Symbol::Terminal(TokenType::LParenOperator);
         let rparen = || Symbol::Terminal(TokenType::RParenOperator);
         let id = || Symbol::Terminal(TokenType::Id);
-        let num = || Symbol::Terminal(TokenType::Number);
-        let char = || Symbol::Terminal(TokenType::CharValue);
+        let num = Symbol::Terminal(TokenType::Number);
+        let char = Symbol::Terminal(TokenType::CharValue);
+        let true_keyword = Symbol::Terminal(TokenType::TrueKeyword);
+        let false_keyword = Symbol::Terminal(TokenType::FalseKeyword);
         let epsilon = || Symbol::Epsilon;
 
-        // Adicione definições de Ação:
-        let action_add = || Symbol::Action(ActionKind::Math(OperatorKind::Sum));
-        let action_sub = || Symbol::Action(ActionKind::Math(OperatorKind::Sub));
-        let action_mult = || Symbol::Action(ActionKind::Math(OperatorKind::Mult));
-        let action_div = || Symbol::Action(ActionKind::Math(OperatorKind::Div));
-        let action_exp = || Symbol::Action(ActionKind::Math(OperatorKind::Exp));
-        let action_unary = || Symbol::Action(ActionKind::CreateUnaryOp);
+        let action_exp = Symbol::Action(ActionKind::Math(OperatorKind::Exp));
+        let action_cond = || Symbol::Action(ActionKind::CreateCond);
+        let action_call = Symbol::Action(ActionKind::CreateCall);
+        let action_array_access = Symbol::Action(ActionKind::CreateArrayAccess);
+        let action_field_access = Symbol::Action(ActionKind::CreateFieldAccess);
+
+        // E → O R
+        for first in ParseTable::FIRST_E {
+            table.set_entry("E", first, vec![o(), r()]);
+        }
+
+        // R → <op_rel> O
+        let relops = [
+            TokenType::GTOperator,
+            TokenType::LTOperator,
+            TokenType::GEOperator,
+            TokenType::LEOperator,
+            TokenType::NEOperator,
+            TokenType::EQOperator,
+        ];
+        for relop in relops.clone() {
+            table.set_entry("R", relop, vec![op_rel(), o(), action_cond()]);
+        }
+
+        // R → ε, no FOLLOW de E
+        let mut follow = vec![
+            TokenType::RParenOperator,
+            TokenType::SemiColonPunctuation,
+            TokenType::CommaPunctuation,
+            TokenType::EndBlockPunctuation,
+            TokenType::Eof,
+        ];
+        for terminal in follow.clone() {
+            table.set_entry("R", terminal, vec![epsilon()]);
+        }
+        follow.extend(relops);
+
+        // Níveis binários à esquerda, do menos ao mais prioritário:
+        // N → M N' e N' → op M {Math(op)} N' | ε, com M o nível seguinte.
+        // `follow` acumula os operadores dos níveis já vistos, que formam o
+        // FOLLOW do nível atual
+        let levels = [
+            (
+                "O",
+                "O'",
+                "X",
+                vec![(TokenType::BitOrOperator, OperatorKind::BitOr)],
+            ),
+            (
+                "X",
+                "X'",
+                "A",
+                vec![(TokenType::BitXorOperator, OperatorKind::BitXor)],
+            ),
+            (
+                "A",
+                "A'",
+                "D",
+                vec![(TokenType::BitAndOperator, OperatorKind::BitAnd)],
+            ),
+            (
+                "D",
+                "D'",
+                "S",
+                vec![
+                    (TokenType::ShlOperator, OperatorKind::Shl),
+                    (TokenType::ShrOperator, OperatorKind::Shr),
+                ],
+            ),
+            (
+                "S",
+                "S'",
+                "T",
+                vec![
+                    (TokenType::PlusOperator, OperatorKind::Sum),
+                    (TokenType::MinusOperator, OperatorKind::Sub),
+                ],
+            ),
+            (
+                "T",
+                "T'",
+                "F",
+                vec![
+                    (TokenType::MultOperator, OperatorKind::Mult),
+                    (TokenType::DivOperator, OperatorKind::Div),
+                    (TokenType::ModOperator, OperatorKind::Mod),
+                ],
+            ),
+        ];
+
+        for (head, tail, next, ops) in levels {
+            let tail_symbol = || Symbol::NonTerminal(tail.to_string());
+            let next_symbol = || Symbol::NonTerminal(next.to_string());
+
+            for first in ParseTable::FIRST_E {
+                table.set_entry(head, first, vec![next_symbol(), tail_symbol()]);
+            }
+
+            for terminal in follow.clone() {
+                table.set_entry(tail, terminal, vec![epsilon()]);
+            }
+
+            for (terminal, op) in ops {
+                table.set_entry(
+                    tail,
+                    terminal.clone(),
+                    vec![
+                        Symbol::Terminal(terminal.clone()),
+                        next_symbol(),
+                        Symbol::Action(ActionKind::Math(op)),
+                        tail_symbol(),
+                    ],
+                );
+                follow.push(terminal);
+            }
+        }
+
+        // F → - F | ~ F (unários)
+        for (terminal, op) in [
+            (TokenType::MinusOperator, OperatorKind::Sub),
+            (TokenType::BitNotOperator, OperatorKind::BitNot),
+        ] {
+            table.set_entry(
+                "F",
+                terminal.clone(),
+                vec![
+                    Symbol::Terminal(terminal),
+                    f(),
+                    Symbol::Action(ActionKind::CreateUnaryOp(op)),
+                ],
+            );
+        }
+
+        // F → U F'
+        for first in ParseTable::FIRST_E {
+            if !matches!(first, TokenType::MinusOperator | TokenType::BitNotOperator) {
+                table.set_entry("F", first, vec![u(), f_prime()]);
+            }
+        }
+
+        // F' → ** F, à direita: o expoente é um F inteiro, com seus próprios `**`
+        table.set_entry("F'", TokenType::ExpOperator, vec![exp, f(), action_exp]);
+
+        // F' → ε, no FOLLOW de F
+        for terminal in follow.clone() {
+            table.set_entry("F'", terminal, vec![epsilon()]);
+        }
+        follow.push(TokenType::ExpOperator);
+
+        // U → id U'
+        table.set_entry("U", TokenType::Id, vec![id(), u_prime()]);
 
-        // Produção 1: E → T E'
-        table.set_entry("E", TokenType::Id, vec![t(), e_prime()]);
-        table.set_entry("E", TokenType::Number, vec![t(), e_prime()]);
-        table.set_entry("E", TokenType::CharValue, vec![t(), e_prime()]);
-        table.set_entry("E", TokenType::LParenOperator, vec![t(), e_prime()]);
-        table.set_entry("E", TokenType::MinusOperator, vec![t(), e_prime()]);
+        // U → num | char | true | false
+        table.set_entry("U", TokenType::Number, vec![num]);
+        table.set_entry("U", TokenType::CharValue, vec![char]);
+        table.set_entry("U", TokenType::TrueKeyword, vec![true_keyword]);
+        table.set_entry("U", TokenType::FalseKeyword, vec![false_keyword]);
 
-        // Produção 2: E' → + T E'
+        // U → ( E )
         table.set_entry(
-            "E'",
-            TokenType::PlusOperator,
-            vec![plus(), t(), action_add(), e_prime()],
+            "U",
+            TokenType::LParenOperator,
+            vec![lparen(), e(), rparen()],
         );
 
-        // Produção 3: E' → - T E'
+        // Produção adicional (chamadas): U' → ( <args> )
         table.set_entry(
-            "E'",
-            TokenType::MinusOperator,
-            vec![minus(), t(), action_sub(), e_prime()],
-        );
-
-        // Produção 4: E' → ε
-        table.set_entry("E'", TokenType::RParenOperator, vec![epsilon()]);
-        table.set_entry("E'", TokenType::SemiColonPunctuation, vec![epsilon()]);
-        table.set_entry("E'", TokenType::GTOperator, vec![epsilon()]);
-        table.set_entry("E'", TokenType::LTOperator, vec![epsilon()]);
-        table.set_entry("E'", TokenType::GEOperator, vec![epsilon()]);
-        table.set_entry("E'", TokenType::LEOperator, vec![epsilon()]);
-        table.set_entry("E'", TokenType::NEOperator, vec![epsilon()]);
-        table.set_entry("E'", TokenType::EQOperator, vec![epsilon()]);
-        table.set_entry("E'", TokenType::Eof, vec![epsilon()]);
-
-        // Produção 5: T → F T'
-        table.set_entry("T", TokenType::Id, vec![f(), t_prime()]);
-        table.set_entry("T", TokenType::Number, vec![f(), t_prime()]);
-        table.set_entry("T", TokenType::CharValue, vec![f(), e_prime()]);
-        table.set_entry("T", TokenType::LParenOperator, vec![f(), t_prime()]);
-        table.set_entry("T", TokenType::MinusOperator, vec![f(), t_prime()]);
-
-        // Produção 6: T' → * F T'
-        table.set_entry(
-            "T'",
-            TokenType::MultOperator,
-            vec![mult(), f(), action_mult(), t_prime()],
-        );
-
-        // Produção 7: T' → / F T'
-        table.set_entry(
-            "T'",
-            TokenType::DivOperator,
-            vec![div(), f(), action_div(), t_prime()],
-        );
-
-        // Produção 8: T' → ε
-        table.set_entry("T'", TokenType::PlusOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::MinusOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::RParenOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::SemiColonPunctuation, vec![epsilon()]);
-        table.set_entry("T'", TokenType::GTOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::LTOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::GEOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::LEOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::NEOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::EQOperator, vec![epsilon()]);
-        table.set_entry("T'", TokenType::Eof, vec![epsilon()]);
-
-        // Produção 9: F → U F'
-        table.set_entry("F", TokenType::Id, vec![u(), f_prime()]);
-        table.set_entry("F", TokenType::Number, vec![u(), f_prime()]);
-        table.set_entry("F", TokenType::CharValue, vec![u(), e_prime()]);
-        table.set_entry("F", TokenType::LParenOperator, vec![u(), f_prime()]);
-
-        // Produção adicional (unarios): F -> - F
-        table.set_entry(
-            "F",
-            TokenType::MinusOperator,
-            vec![minus(), f(), action_unary()],
+            "U'",
+            TokenType::LParenOperator,
+            vec![lparen(), args(), rparen(), action_call],
         );
 
-        // Produção 10: F' → ** U F'
+        // Produção adicional (vetores e registros): U' → <acesso> <acessos>
         table.set_entry(
-            "F'",
-            TokenType::ExpOperator,
-            vec![exp(), u(), action_exp(), f_prime()],
+            "U'",
+            TokenType::BeginBlockPunctuation,
+            vec![acesso(), acessos()],
         );
+        table.set_entry("U'", TokenType::DotPunctuation, vec![acesso(), acessos()]);
 
-        // Produção 11: F' → ε
-        table.set_entry("F'", TokenType::PlusOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::MinusOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::MultOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::DivOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::RParenOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::SemiColonPunctuation, vec![epsilon()]);
-        table.set_entry("F'", TokenType::GTOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::LTOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::GEOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::LEOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::NEOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::EQOperator, vec![epsilon()]);
-        table.set_entry("F'", TokenType::Eof, vec![epsilon()]); // pra conseguir rodar testes
-
-        // Produção 9: U → id
-        table.set_entry("U", TokenType::Id, vec![id()]);
-
-        // Produção 10: U → num
-        table.set_entry("U", TokenType::Number, vec![num()]);
+        // <acesso> → [ E ], sobre o valor à esquerda
+        table.set_entry(
+            "acesso",
+            TokenType::BeginBlockPunctuation,
+            vec![
+                Symbol::Terminal(TokenType::BeginBlockPunctuation),
+                e(),
+                Symbol::Terminal(TokenType::EndBlockPunctuation),
+                action_array_access,
+            ],
+        );
 
-        // Produção adicional: U → char
-        table.set_entry("U", TokenType::CharValue, vec![char()]);
+        // <acesso> → . id
+        table.set_entry(
+            "acesso",
+            TokenType::DotPunctuation,
+            vec![
+                Symbol::Terminal(TokenType::DotPunctuation),
+                id(),
+                action_field_access,
+            ],
+        );
 
-        // Produção 11: U → ( E )
+        // <acessos> → <acesso> <acessos>
         table.set_entry(
-            "U",
-            TokenType::LParenOperator,
-            vec![lparen(), e(), rparen()],
+            "acessos",
+            TokenType::BeginBlockPunctuation,
+            vec![acesso(), acessos()],
+        );
+        table.set_entry(
+            "acessos",
+            TokenType::DotPunctuation,
+            vec![acesso(), acessos()],
         );
 
+        // <acessos> → ε, no FOLLOW de U e antes do `:=` (ou `+=`, `++`...) de uma
+        // atribuição
+        table.set_entry("acessos", TokenType::AssignPunctuation, vec![epsilon()]);
+        for op in ParseTable::ATRIB_OPS {
+            table.set_entry("acessos", op, vec![epsilon()]);
+        }
+
+        // U' → ε e <acessos> → ε, no FOLLOW de U
+        for terminal in follow {
+            table.set_entry("U'", terminal.clone(), vec![epsilon()]);
+            table.set_entry("acessos", terminal, vec![epsilon()]);
+        }
+
         table
     }
 
@@ -574,28 +1442,17 @@ impl ParseTable {
     }
 
     /**
-     <cond> -> <E> <op_rel> <E>
+     <cond> -> <E>
+
+     A comparação já faz parte de <E>; a análise semântica exige que a
+     condição seja do tipo bool
     */
     fn create_cond_table(mut table: ParseTable) -> ParseTable {
         let expr = || Symbol::NonTerminal("E".to_string());
-        let op_rel = || Symbol::NonTerminal("op_rel".to_string());
-        let action_create_cond = || Symbol::Action(ActionKind::CreateCond);
 
-        table.set_entry(
-            "cond",
-            TokenType::Id,
-            vec![expr(), op_rel(), expr(), action_create_cond()],
-        );
-        table.set_entry(
-            "cond",
-            TokenType::Number,
-            vec![expr(), op_rel(), expr(), action_create_cond()],
-        );
-        table.set_entry(
-            "cond",
-            TokenType::LParenOperator,
-            vec![expr(), op_rel(), expr(), action_create_cond()],
-        );
+        for first in ParseTable::FIRST_E {
+            table.set_entry("cond", first, vec![expr()]);
+        }
 
         table
     }
@@ -633,12 +1490,10 @@ impl ParseTable {
         table.set_entry("cmd_ou_bloco", TokenType::WhileKeyword, vec![comando()]);
         table.set_entry("cmd_ou_bloco", TokenType::DoKeyword, vec![comando()]);
         table.set_entry("cmd_ou_bloco", TokenType::ForKeyword, vec![comando()]);
-        table.set_entry(
-            "cmd_ou_bloco",
-            TokenType::BeginBlockPunctuation,
-            vec![comando()],
-        );
-
+        table.set_entry("cmd_ou_bloco", TokenType::ReturnKeyword, vec![comando()]);
+        table.set_entry("cmd_ou_bloco", TokenType::BreakKeyword, vec![comando()]);
+        table.set_entry("cmd_ou_bloco", TokenType::ContinueKeyword, vec![comando()]);
+        table.set_entry("cmd_ou_bloco", TokenType::SwitchKeyword, vec![comando()]);
         table.set_entry(
             "cmd_ou_bloco",
             TokenType::BeginBlockPunctuation,
@@ -712,11 +1567,46 @@ impl ParseTable {
             TokenType::ForKeyword,
             vec![epsilon(), action_create_if()],
         );
+        table.set_entry(
+            "cmd_if'",
+            TokenType::ReturnKeyword,
+            vec![epsilon(), action_create_if()],
+        );
+        table.set_entry(
+            "cmd_if'",
+            TokenType::BreakKeyword,
+            vec![epsilon(), action_create_if()],
+        );
+        table.set_entry(
+            "cmd_if'",
+            TokenType::ContinueKeyword,
+            vec![epsilon(), action_create_if()],
+        );
+        table.set_entry(
+            "cmd_if'",
+            TokenType::SwitchKeyword,
+            vec![epsilon(), action_create_if()],
+        );
+        table.set_entry(
+            "cmd_if'",
+            TokenType::CaseKeyword,
+            vec![epsilon(), action_create_if()],
+        );
+        table.set_entry(
+            "cmd_if'",
+            TokenType::DefaultKeyword,
+            vec![epsilon(), action_create_if()],
+        );
         table.set_entry(
             "cmd_if'",
             TokenType::BeginBlockPunctuation,
             vec![epsilon(), action_create_if()],
         );
+        table.set_entry(
+            "cmd_if'",
+            TokenType::EndBlockPunctuation,
+            vec![epsilon(), action_create_if()],
+        );
         table.set_entry(
             "cmd_if'",
             TokenType::Eof,
@@ -787,15 +1677,16 @@ impl ParseTable {
     }
 
     /**
-     <cmd_for> -> for ( id ; num ; num ; <E> ) <cmd_ou_bloco>
+     <cmd_for> -> for ( id ; <E> ; <E> ; <E> ) <cmd_ou_bloco>
+
+     Início, fim (exclusivo) e passo; o passo é o próximo valor de `id`
     */
     fn create_cmd_for_table(mut table: ParseTable) -> ParseTable {
         let enquanto = Symbol::Terminal(TokenType::ForKeyword);
         let lparen = Symbol::Terminal(TokenType::LParenOperator);
         let id = Symbol::Terminal(TokenType::Id);
         let semicolon = || Symbol::Terminal(TokenType::SemiColonPunctuation);
-        let num = || Symbol::Terminal(TokenType::Number);
-        let expr = Symbol::NonTerminal("E".to_string());
+        let expr = || Symbol::NonTerminal("E".to_string());
         let rparen = Symbol::Terminal(TokenType::RParenOperator);
         let cmd_ou_bloco = Symbol::NonTerminal("cmd_ou_bloco".to_string());
         let action_create_for = Symbol::Action(ActionKind::CreateFor);
@@ -808,11 +1699,11 @@ impl ParseTable {
                 lparen,
                 id,
                 semicolon(),
-                num(),
+                expr(),
                 semicolon(),
-                num(),
+                expr(),
                 semicolon(),
-                expr,
+                expr(),
                 rparen,
                 cmd_ou_bloco,
                 action_create_for,
@@ -821,4 +1712,135 @@ impl ParseTable {
 
         table
     }
+
+    /**
+     <cmd_switch> -> switch ( <E> ) [ <casos> <padrao> ]
+
+     Não há passagem de um ramo para o seguinte; o `default`, se houver, é o
+     último ramo
+    */
+    fn create_cmd_switch_table(mut table: ParseTable) -> ParseTable {
+        let escolha = Symbol::Terminal(TokenType::SwitchKeyword);
+        let lparen = Symbol::Terminal(TokenType::LParenOperator);
+        let expr = Symbol::NonTerminal("E".to_string());
+        let rparen = Symbol::Terminal(TokenType::RParenOperator);
+        let begin = Symbol::Terminal(TokenType::BeginBlockPunctuation);
+        let casos = Symbol::NonTerminal("casos".to_string());
+        let padrao = Symbol::NonTerminal("padrao".to_string());
+        let end = Symbol::Terminal(TokenType::EndBlockPunctuation);
+        let action_create_switch = Symbol::Action(ActionKind::CreateSwitch);
+
+        table.set_entry(
+            "cmd_switch",
+            TokenType::SwitchKeyword,
+            vec![
+                escolha,
+                lparen,
+                expr,
+                rparen,
+                begin,
+                casos,
+                padrao,
+                end,
+                action_create_switch,
+            ],
+        );
+
+        table
+    }
+
+    /**
+     <casos> -> case <rotulo> : <cmd_ou_bloco> <casos> | ε
+    */
+    fn create_casos_table(mut table: ParseTable) -> ParseTable {
+        let caso = Symbol::Terminal(TokenType::CaseKeyword);
+        let rotulo = Symbol::NonTerminal("rotulo".to_string());
+        let colon = Symbol::Terminal(TokenType::ColonPunctuation);
+        let cmd_ou_bloco = Symbol::NonTerminal("cmd_ou_bloco".to_string());
+        let casos = Symbol::NonTerminal("casos".to_string());
+        let epsilon = || Symbol::Epsilon;
+        let action_create_case = Symbol::Action(ActionKind::CreateCase);
+        let action_append_list = Symbol::Action(ActionKind::AppendList);
+        let action_make_list = || Symbol::Action(ActionKind::MakeList);
+
+        table.set_entry(
+            "casos",
+            TokenType::CaseKeyword,
+            vec![
+                caso,
+                rotulo,
+                colon,
+                cmd_ou_bloco,
+                action_create_case,
+                casos,
+                action_append_list,
+            ],
+        );
+
+        table.set_entry(
+            "casos",
+            TokenType::DefaultKeyword,
+            vec![epsilon(), action_make_list()],
+        );
+        table.set_entry(
+            "casos",
+            TokenType::EndBlockPunctuation,
+            vec![epsilon(), action_make_list()],
+        );
+
+        table
+    }
+
+    /**
+     <rotulo> -> num | caractere | - num
+    */
+    fn create_rotulo_table(mut table: ParseTable) -> ParseTable {
+        let num = || Symbol::Terminal(TokenType::Number);
+        let char = Symbol::Terminal(TokenType::CharValue);
+        let minus = Symbol::Terminal(TokenType::MinusOperator);
+        let action_unary = Symbol::Action(ActionKind::CreateUnaryOp(OperatorKind::Sub));
+
+        table.set_entry("rotulo", TokenType::Number, vec![num()]);
+        table.set_entry("rotulo", TokenType::CharValue, vec![char]);
+        table.set_entry(
+            "rotulo",
+            TokenType::MinusOperator,
+            vec![minus, num(), action_unary],
+        );
+
+        table
+    }
+
+    /**
+     <padrao> -> default : <cmd_ou_bloco> | ε
+
+     O `default` entra numa lista de até um item
+    */
+    fn create_padrao_table(mut table: ParseTable) -> ParseTable {
+        let padrao = Symbol::Terminal(TokenType::DefaultKeyword);
+        let colon = Symbol::Terminal(TokenType::ColonPunctuation);
+        let cmd_ou_bloco = Symbol::NonTerminal("cmd_ou_bloco".to_string());
+        let action_make_list = || Symbol::Action(ActionKind::MakeList);
+        let action_append_list = Symbol::Action(ActionKind::AppendList);
+
+        table.set_entry(
+            "padrao",
+            TokenType::DefaultKeyword,
+            vec![
+                padrao,
+                colon,
+                cmd_ou_bloco,
+                action_make_list(),
+                action_append_list,
+            ],
+        );
+
+        table.set_entry(
+            "padrao",
+            TokenType::EndBlockPunctuation,
+            vec![Symbol::Epsilon, action_make_list()],
+        );
+
+        table
+    }
 }
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+
+use crate::{
+    ActionKind, AstNode, Parser, ParserError, Position, Span, Spanned, Symbol, Token, Type,
+    syntactic::dot::DotGraph,
+};
+
+// Árvore de derivação (árvore sintática concreta) do analisador LL(1). Cada
+// expansão de um não terminal vira um nó com um filho por símbolo da produção
+// escolhida, inclusive as ações semânticas, o que permite refazer a AST.
+//
+// A árvore não perde informação: cada token guarda o texto exato e os espaços e
+// comentários à sua volta, então `to_source` devolve o código-fonte original.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseTree {
+    NonTerminal {
+        name: String,
+        children: Vec<ParseTree>,
+    },
+    Terminal {
+        token: Token,
+        span: Span,
+        // Texto do token no código-fonte
+        text: String,
+        // Espaços e comentários desde a linha anterior
+        leading_trivia: Vec<Trivia>,
+        // Espaços e comentários até o fim da linha do token, inclusive a quebra.
+        // O último token do arquivo recebe tudo o que vem depois dele.
+        trailing_trivia: Vec<Trivia>,
+    },
+    Action(ActionKind),
+    // Produção vazia
+    Epsilon,
+}
+
+// Trecho do código-fonte ignorado pelo léxico
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia {
+    Whitespace(String),
+    // Comentário completo, com `{%` e `%}`
+    Comment(String),
+}
+
+impl Trivia {
+    pub fn text(&self) -> &str {
+        match self {
+            Trivia::Whitespace(text) | Trivia::Comment(text) => text,
+        }
+    }
+}
+
+impl ParseTree {
+    // Tokens casados, na ordem do código-fonte
+    pub fn tokens(&self) -> Vec<&Token> {
+        match self {
+            ParseTree::NonTerminal { children, .. } => {
+                children.iter().flat_map(|child| child.tokens()).collect()
+            }
+            ParseTree::Terminal { token, .. } => vec![token],
+            ParseTree::Action(_) | ParseTree::Epsilon => vec![],
+        }
+    }
+
+    // Código-fonte exato de onde a árvore foi construída
+    pub fn to_source(&self) -> String {
+        let mut out = String::new();
+        self.write_source(&mut out);
+        out
+    }
+
+    fn write_source(&self, out: &mut String) {
+        match self {
+            ParseTree::NonTerminal { children, .. } => {
+                for child in children {
+                    child.write_source(out);
+                }
+            }
+            ParseTree::Terminal {
+                text,
+                leading_trivia,
+                trailing_trivia,
+                ..
+            } => {
+                leading_trivia
+                    .iter()
+                    .for_each(|trivia| out.push_str(trivia.text()));
+                out.push_str(text);
+                trailing_trivia
+                    .iter()
+                    .for_each(|trivia| out.push_str(trivia.text()));
+            }
+            ParseTree::Action(_) | ParseTree::Epsilon => {}
+        }
+    }
+
+    // Refaz a AST executando as ações semânticas na ordem da derivação, como o
+    // analisador faz ao desempilhá-las. Os nós refeitos ficam sem intervalo.
+    pub fn to_ast(&self) -> Result<AstNode, ParserError> {
+        let mut ast_stack = vec![];
+        let mut span = Span::default();
+        let mut records = HashMap::new();
+        self.replay(&mut ast_stack, &mut records, &mut span)?;
+
+        match (ast_stack.pop(), ast_stack.is_empty()) {
+            (Some(ast), true) => Ok(ast.node),
+            _ => Err(ParserError::Syntax {
+                message: "Falha ao construir AST".to_string(),
+                span,
+            }),
+        }
+    }
+
+    // `span` acompanha o último token, usado nos erros das ações
+    // `records` guarda os tipos registro declarados até o momento
+    fn replay(
+        &self,
+        ast_stack: &mut Vec<Spanned<AstNode>>,
+        records: &mut HashMap<String, Type>,
+        span: &mut Span,
+    ) -> Result<(), ParserError> {
+        match self {
+            ParseTree::NonTerminal { children, .. } => {
+                for child in children {
+                    child.replay(ast_stack, records, span)?;
+                }
+            }
+            ParseTree::Terminal {
+                token,
+                span: token_span,
+                ..
+            } => {
+                *span = *token_span;
+                ast_stack.extend(Parser::leaf_node(token).map(Spanned::from));
+            }
+            ParseTree::Action(kind) => {
+                Parser::handle_action(kind, ast_stack, records, *span, None)?
+            }
+            ParseTree::Epsilon => {}
+        }
+
+        Ok(())
+    }
+
+    // Grafo DOT da derivação: não terminais em elipses e tokens em caixas. As
+    // ações semânticas ficam de fora.
+    pub fn to_dot(&self) -> String {
+        let mut graph = DotGraph::new("ParseTree");
+        self.write_dot(&mut graph);
+        graph.finish()
+    }
+
+    fn write_dot(&self, graph: &mut DotGraph) -> usize {
+        match self {
+            ParseTree::NonTerminal { name, children } => {
+                let id = graph.node(name, "ellipse");
+                for child in children.iter().filter(|child| !child.is_action()) {
+                    let child_id = child.write_dot(graph);
+                    graph.edge(id, child_id);
+                }
+                id
+            }
+            ParseTree::Terminal { token, .. } => graph.node(&token.lexeme(), "box"),
+            ParseTree::Action(kind) => graph.node(&format!("{:?}", kind), "plaintext"),
+            ParseTree::Epsilon => graph.node("ε", "plaintext"),
+        }
+    }
+
+    fn is_action(&self) -> bool {
+        matches!(self, ParseTree::Action(_))
+    }
+
+    // Tokens da árvore, na ordem do código-fonte, para preencher texto e trivia
+    fn terminals_mut(&mut self) -> Vec<&mut ParseTree> {
+        match self {
+            ParseTree::NonTerminal { children, .. } => children
+                .iter_mut()
+                .flat_map(|child| child.terminals_mut())
+                .collect(),
+            ParseTree::Terminal { .. } => vec![self],
+            ParseTree::Action(_) | ParseTree::Epsilon => vec![],
+        }
+    }
+}
+
+// Nó da árvore em construção
+#[derive(Debug)]
+pub(crate) enum PendingNode {
+    // Símbolo ainda na pilha de análise
+    Pending,
+    NonTerminal { name: String, children: Vec<usize> },
+    Terminal { token: Token, span: Span },
+    Action(ActionKind),
+    Epsilon,
+}
+
+// Constrói a árvore de derivação durante a análise. Os nós ficam num vetor e são
+// ligados por índice, para que cada símbolo da pilha saiba qual nó vai preencher.
+#[derive(Debug, Default)]
+pub(crate) struct ParseTreeBuilder {
+    nodes: Vec<PendingNode>,
+}
+
+impl ParseTreeBuilder {
+    // Recomeça a árvore, devolvendo o nó do símbolo inicial
+    pub(crate) fn reset(&mut self) -> usize {
+        self.nodes = vec![PendingNode::Pending];
+        0
+    }
+
+    // Registra a expansão de `node` pela produção dada e devolve o nó criado para
+    // cada símbolo (`None` para ações e ε, que não vão para a pilha)
+    pub(crate) fn expand(
+        &mut self,
+        node: usize,
+        name: &str,
+        production: &[Symbol],
+    ) -> Vec<Option<usize>> {
+        let mut children = vec![];
+        let mut symbol_nodes = vec![];
+
+        for symbol in production {
+            match symbol {
+                Symbol::Action(kind) => {
+                    children.push(self.push(PendingNode::Action(kind.clone())));
+                    symbol_nodes.push(None);
+                }
+                Symbol::Epsilon => {
+                    children.push(self.push(PendingNode::Epsilon));
+                    symbol_nodes.push(None);
+                }
+                _ => {
+                    let child = self.push(PendingNode::Pending);
+                    children.push(child);
+                    symbol_nodes.push(Some(child));
+                }
+            }
+        }
+
+        self.nodes[node] = PendingNode::NonTerminal {
+            name: name.to_string(),
+            children,
+        };
+        symbol_nodes
+    }
+
+    // Registra `node` como o não terminal `name` com os tokens e as ações
+    // produzidos fora da tabela, como no analisador de expressões por precedência
+    pub(crate) fn set_children(&mut self, node: usize, name: &str, nodes: Vec<PendingNode>) {
+        let children = nodes.into_iter().map(|child| self.push(child)).collect();
+
+        self.nodes[node] = PendingNode::NonTerminal {
+            name: name.to_string(),
+            children,
+        };
+    }
+
+    pub(crate) fn set_terminal(&mut self, node: usize, token: Token, span: Span) {
+        self.nodes[node] = PendingNode::Terminal { token, span };
+    }
+
+    // Árvore a partir da raiz, com o texto e a trivia de cada token tirados de
+    // `source`. Símbolos que ficaram na pilha (após um erro) são omitidos.
+    pub(crate) fn build(&self, source: &[char]) -> Option<ParseTree> {
+        let mut tree = self.build_node(0)?;
+        attach_trivia(&mut tree, source);
+        Some(tree)
+    }
+
+    fn build_node(&self, node: usize) -> Option<ParseTree> {
+        match self.nodes.get(node)? {
+            PendingNode::Pending => None,
+            PendingNode::NonTerminal { name, children } => Some(ParseTree::NonTerminal {
+                name: name.clone(),
+                children: children
+                    .iter()
+                    .filter_map(|child| self.build_node(*child))
+                    .collect(),
+            }),
+            PendingNode::Terminal { token, span } => Some(ParseTree::Terminal {
+                token: token.clone(),
+                span: *span,
+                text: String::new(),
+                leading_trivia: vec![],
+                trailing_trivia: vec![],
+            }),
+            PendingNode::Action(kind) => Some(ParseTree::Action(kind.clone())),
+            PendingNode::Epsilon => Some(ParseTree::Epsilon),
+        }
+    }
+
+    fn push(&mut self, node: PendingNode) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+}
+
+// Preenche o texto de cada token e distribui o que há entre eles: o que está na
+// mesma linha fica com o token anterior, o resto com o seguinte
+fn attach_trivia(tree: &mut ParseTree, source: &[char]) {
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        source
+            .iter()
+            .enumerate()
+            .filter(|(_, ch)| **ch == '\n')
+            .map(|(i, _)| i + 1),
+    );
+    let offset = |position: Position| {
+        line_starts
+            .get(position.line.saturating_sub(1))
+            .map_or(source.len(), |start| {
+                start + position.column.saturating_sub(1)
+            })
+            .min(source.len())
+    };
+
+    let mut terminals = tree.terminals_mut();
+    let mut previous_end = 0;
+    let count = terminals.len();
+
+    for (i, terminal) in terminals.iter_mut().enumerate() {
+        let ParseTree::Terminal {
+            span,
+            text,
+            leading_trivia,
+            trailing_trivia,
+            ..
+        } = &mut **terminal
+        else {
+            continue;
+        };
+
+        let start = offset(span.start).max(previous_end);
+        let end = offset(span.end).max(start);
+
+        *leading_trivia = split_trivia(&source[previous_end..start]);
+        *text = source[start..end].iter().collect();
+        previous_end = end;
+
+        if i + 1 == count {
+            *trailing_trivia = split_trivia(&source[end..]);
+        }
+    }
+
+    // Move para o token anterior a trivia que está na mesma linha dele
+    for i in 1..terminals.len() {
+        let (before, after) = terminals.split_at_mut(i);
+        if let (
+            ParseTree::Terminal {
+                trailing_trivia, ..
+            },
+            ParseTree::Terminal { leading_trivia, .. },
+        ) = (&mut *before[i - 1], &mut *after[0])
+        {
+            *trailing_trivia = take_same_line(leading_trivia);
+        }
+    }
+}
+
+// Remove do início de `trivia` tudo até a primeira quebra de linha (inclusive)
+fn take_same_line(trivia: &mut Vec<Trivia>) -> Vec<Trivia> {
+    let mut same_line = vec![];
+
+    while !trivia.is_empty() {
+        match trivia.remove(0) {
+            Trivia::Whitespace(text) => match text.split_once('\n') {
+                Some((line, rest)) => {
+                    same_line.push(Trivia::Whitespace(format!("{}\n", line)));
+                    if !rest.is_empty() {
+                        trivia.insert(0, Trivia::Whitespace(rest.to_string()));
+                    }
+                    break;
+                }
+                None => same_line.push(Trivia::Whitespace(text)),
+            },
+            comment => same_line.push(comment),
+        }
+    }
+
+    same_line
+}
+
+// Separa um trecho ignorado pelo léxico em espaços e comentários `{% %}`
+fn split_trivia(text: &[char]) -> Vec<Trivia> {
+    let text: String = text.iter().collect();
+    let mut trivia = vec![];
+    let mut rest = text.as_str();
+
+    while !rest.is_empty() {
+        if rest.starts_with("{%") {
+            // Comentário sem fechamento vai até o fim do arquivo
+            let end = rest[2..].find("%}").map_or(rest.len(), |i| i + 4);
+            trivia.push(Trivia::Comment(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let end = rest.find("{%").unwrap_or(rest.len());
+            trivia.push(Trivia::Whitespace(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+    }
+
+    trivia
+}
@@ -0,0 +1,309 @@
+use crate::{
+    ActionKind, AstNode, ParserError, Position, Span, Spanned, Token, TokenType, TraceAction,
+};
+
+use super::{Parser, PendingNode};
+
+// Associatividade de um operador binário
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+    // Não se encadeia: `a < b < c` é erro de sintaxe
+    None,
+}
+
+// Operadores binários com a precedência (maior liga mais forte) e a
+// associatividade, os mesmos níveis da tabela de `create_expression_parse_table`
+pub const BINARY_OPERATORS: [(TokenType, u8, Associativity); 17] = [
+    (TokenType::EQOperator, 1, Associativity::None),
+    (TokenType::NEOperator, 1, Associativity::None),
+    (TokenType::LTOperator, 1, Associativity::None),
+    (TokenType::GTOperator, 1, Associativity::None),
+    (TokenType::LEOperator, 1, Associativity::None),
+    (TokenType::GEOperator, 1, Associativity::None),
+    (TokenType::BitOrOperator, 2, Associativity::Left),
+    (TokenType::BitXorOperator, 3, Associativity::Left),
+    (TokenType::BitAndOperator, 4, Associativity::Left),
+    (TokenType::ShlOperator, 5, Associativity::Left),
+    (TokenType::ShrOperator, 5, Associativity::Left),
+    (TokenType::PlusOperator, 6, Associativity::Left),
+    (TokenType::MinusOperator, 6, Associativity::Left),
+    (TokenType::MultOperator, 7, Associativity::Left),
+    (TokenType::DivOperator, 7, Associativity::Left),
+    (TokenType::ModOperator, 7, Associativity::Left),
+    (TokenType::ExpOperator, 9, Associativity::Right),
+];
+
+// Precedência dos unários `-` e `~`: o operando só inclui `**`, então `-a ** b`
+// é `-(a ** b)` e `-a * b` é `(-a) * b`
+pub const PREFIX_PRECEDENCE: u8 = 8;
+
+// Expressões abertas umas dentro das outras (parênteses, índices, argumentos,
+// unários e `**`) antes de desistir. Cada nível recursa na pilha do Rust, que em
+// debug gasta mais de 10KB por nível; 64 é o mínimo que C exige de um compilador.
+const MAX_NESTING: usize = 64;
+
+fn binary_operator(token: &Token) -> Option<(u8, Associativity)> {
+    let token_type: TokenType = token.clone().into();
+
+    BINARY_OPERATORS
+        .iter()
+        .find(|(operator, ..)| *operator == token_type)
+        .map(|(_, precedence, associativity)| (*precedence, *associativity))
+}
+
+// Analisador de expressões por precedência (Pratt), usado no lugar das produções
+// de <E> quando o parser é criado `with_pratt_expressions`. Monta os mesmos nós e
+// os mesmos intervalos que as ações da tabela: um operador aritmético vai do
+// início do operando esquerdo, com seus parênteses, até o último token casado, e
+// os parênteses em volta de uma expressão não entram no intervalo dela.
+impl Parser<'_> {
+    // Analisa um <E> inteiro a partir de `token`, deixando nele o token seguinte
+    pub(super) fn pratt_expression(
+        &mut self,
+        token: &mut Token,
+    ) -> Result<Spanned<AstNode>, ParserError> {
+        self.expression(token, 0).map(|(node, _)| node)
+    }
+
+    // Devolve também o início do texto da expressão, que inclui os parênteses de
+    // um operando esquerdo entre parênteses
+    fn expression(
+        &mut self,
+        token: &mut Token,
+        min_precedence: u8,
+    ) -> Result<(Spanned<AstNode>, Position), ParserError> {
+        if self.nesting == MAX_NESTING {
+            return Err(ParserError::Syntax {
+                message: format!(
+                    "Expressão com mais de {} níveis de aninhamento",
+                    MAX_NESTING
+                ),
+                span: self.current_span,
+            });
+        }
+
+        self.nesting += 1;
+        let result = self.infix(token, min_precedence);
+        self.nesting -= 1;
+        result
+    }
+
+    // Operando seguido dos operadores binários de precedência `min_precedence` ou
+    // maior
+    fn infix(
+        &mut self,
+        token: &mut Token,
+        min_precedence: u8,
+    ) -> Result<(Spanned<AstNode>, Position), ParserError> {
+        let (mut left, start) = self.prefix(token)?;
+        let mut compared = false;
+
+        while let Some((precedence, associativity)) = binary_operator(token)
+            && precedence >= min_precedence
+            && !(compared && associativity == Associativity::None)
+        {
+            let operator = self.advance(token)?;
+            let next_precedence = match associativity {
+                Associativity::Right => precedence,
+                Associativity::Left | Associativity::None => precedence + 1,
+            };
+            let (right, _) = self.expression(token, next_precedence)?;
+            // Como <R> não continua o nó de <E>, a comparação começa no intervalo
+            // do operando esquerdo, sem os parênteses dele
+            let node_start = match (associativity, left.span) {
+                (Associativity::None, Some(left)) => left.start,
+                _ => start,
+            };
+
+            let node = match operator {
+                Token::Relop { kind, .. } => {
+                    self.record(ActionKind::CreateCond);
+                    AstNode::BinaryComp {
+                        relop: kind,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }
+                }
+                Token::Operator { kind, .. } => {
+                    self.record(ActionKind::Math(kind.clone()));
+                    AstNode::BinaryOp {
+                        op: kind,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    }
+                }
+                operator => unreachable!("Operador fora da tabela: {}", operator),
+            };
+            left = Spanned::new(node, self.span_from(node_start));
+            compared = associativity == Associativity::None;
+        }
+
+        Ok((left, start))
+    }
+
+    // Operando: unário, parênteses, literal, variável, chamada ou acesso
+    fn prefix(&mut self, token: &mut Token) -> Result<(Spanned<AstNode>, Position), ParserError> {
+        let start = self.current_span.start;
+
+        match TokenType::from(token.clone()) {
+            TokenType::MinusOperator | TokenType::BitNotOperator => {
+                let Token::Operator { kind, .. } = self.advance(token)? else {
+                    unreachable!()
+                };
+                let (expr, _) = self.expression(token, PREFIX_PRECEDENCE)?;
+                self.record(ActionKind::CreateUnaryOp(kind.clone()));
+
+                let node = AstNode::UnaryOp {
+                    op: kind,
+                    expr: Box::new(expr),
+                };
+                Ok((Spanned::new(node, self.span_from(start)), start))
+            }
+            TokenType::LParenOperator => {
+                self.advance(token)?;
+                let (expr, _) = self.expression(token, 0)?;
+                self.expect(token, TokenType::RParenOperator)?;
+
+                Ok((expr, start))
+            }
+            TokenType::Number
+            | TokenType::CharValue
+            | TokenType::TrueKeyword
+            | TokenType::FalseKeyword => Ok((self.leaf(token)?, start)),
+            TokenType::Id => {
+                let mut node = self.leaf(token)?;
+
+                if TokenType::from(token.clone()) == TokenType::LParenOperator {
+                    return self.call(token, node.node, start);
+                }
+
+                loop {
+                    match TokenType::from(token.clone()) {
+                        TokenType::BeginBlockPunctuation => {
+                            self.advance(token)?;
+                            let (index, _) = self.expression(token, 0)?;
+                            self.expect(token, TokenType::EndBlockPunctuation)?;
+                            self.record(ActionKind::CreateArrayAccess);
+
+                            let access = AstNode::ArrayAccess {
+                                array: Box::new(node),
+                                index: Box::new(index),
+                            };
+                            node = Spanned::new(access, self.span_from(start));
+                        }
+                        TokenType::DotPunctuation => {
+                            self.advance(token)?;
+                            let field = self.expect(token, TokenType::Id)?;
+                            self.record(ActionKind::CreateFieldAccess);
+
+                            let access = AstNode::FieldAccess {
+                                record: Box::new(node),
+                                field: field.lexeme(),
+                            };
+                            node = Spanned::new(access, self.span_from(start));
+                        }
+                        _ => return Ok((node, start)),
+                    }
+                }
+            }
+            _ => Err(ParserError::Syntax {
+                message: format!(
+                    "Erro de sintaxe. Não esperado {}, era esperada uma expressão",
+                    token
+                ),
+                span: self.current_span,
+            }),
+        }
+    }
+
+    // Argumentos de `name( ... )`, com `token` no `(`
+    fn call(
+        &mut self,
+        token: &mut Token,
+        name: AstNode,
+        start: Position,
+    ) -> Result<(Spanned<AstNode>, Position), ParserError> {
+        let AstNode::Identifier { name } = name else {
+            unreachable!()
+        };
+        self.advance(token)?;
+
+        let mut args = vec![];
+        if TokenType::from(token.clone()) != TokenType::RParenOperator {
+            loop {
+                let (arg, _) = self.expression(token, 0)?;
+                args.push(arg);
+
+                if TokenType::from(token.clone()) != TokenType::CommaPunctuation {
+                    break;
+                }
+                self.advance(token)?;
+            }
+        }
+        // A lista é montada do fim para o começo, como em <args'>
+        self.record(ActionKind::MakeList);
+        for _ in &args {
+            self.record(ActionKind::AppendList);
+        }
+        self.expect(token, TokenType::RParenOperator)?;
+        self.record(ActionKind::CreateCall);
+
+        let call = AstNode::Call { name, args };
+        Ok((Spanned::new(call, self.span_from(start)), start))
+    }
+
+    // Literal ou identificador, como o nó folha empilhado pela tabela
+    pub(super) fn leaf(&mut self, token: &mut Token) -> Result<Spanned<AstNode>, ParserError> {
+        let span = self.current_span;
+        let token = self.advance(token)?;
+        let leaf = Self::leaf_node(&token).expect("Token sem nó folha");
+
+        Ok(Spanned::new(leaf, span))
+    }
+
+    fn expect(&mut self, token: &mut Token, expected: TokenType) -> Result<Token, ParserError> {
+        if TokenType::from(token.clone()) == expected {
+            self.advance(token)
+        } else {
+            Err(ParserError::Syntax {
+                message: format!(
+                    "Erro de sintaxe. Esperado {:?}, encontrado {}",
+                    expected, token
+                ),
+                span: self.current_span,
+            })
+        }
+    }
+
+    // Casa o token atual, como um terminal da tabela, e devolve ele
+    pub(super) fn advance(&mut self, token: &mut Token) -> Result<Token, ParserError> {
+        let snapshot = self.trace_snapshot();
+        self.record_step(snapshot, || TraceAction::Match(token.clone()));
+        if self.parse_tree.is_some() {
+            self.expression_nodes.push(PendingNode::Terminal {
+                token: token.clone(),
+                span: self.current_span,
+            });
+        }
+
+        self.last_end = self.current_span.end;
+        let next = self.next_token()?;
+        Ok(std::mem::replace(token, next))
+    }
+
+    // Ação que a tabela executaria neste ponto, para a árvore de derivação
+    fn record(&mut self, kind: ActionKind) {
+        if self.parse_tree.is_some() {
+            self.expression_nodes.push(PendingNode::Action(kind));
+        }
+    }
+
+    fn span_from(&self, start: Position) -> Span {
+        Span {
+            start,
+            end: self.last_end.max(start),
+        }
+    }
+}
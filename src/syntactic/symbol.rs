@@ -1,7 +1,9 @@
+use std::fmt::{Display, Error, Formatter};
+
 use crate::{OperatorKind, TokenType};
 
 // Símbolos da gramática
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Symbol {
     Terminal(TokenType),
     NonTerminal(String),
@@ -10,10 +12,10 @@ pub enum Symbol {
     Action(ActionKind),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ActionKind {
     Math(OperatorKind),
-    CreateUnaryOp,
+    CreateUnaryOp(OperatorKind),
 
     // Comandos
     Assign,
@@ -23,13 +25,45 @@ pub enum ActionKind {
     CreateWhile,
     CreateDoWhile,
     CreateFor,
+    CreateSwitch,
+    CreateCase,
+    CreateBreak,
+    CreateContinue,
 
     MakeList,
     AppendList,
     CreateDecl,
+    MarkConst,
     CreateCond,
 
     CreateProgram,
+
+    // Funções
+    CreateFunction,
+    CreateParam,
+    AddFunction,
+    CreateCall,
+    CreateReturn,
+
+    // Vetores
+    CreateArrayDecl,
+    CreateArrayAccess,
+
+    // Declarações globais, antes das funções
+    AddGlobal,
+
+    // Registros
+    RecordType,
+    CreateRecord,
+    AddRecord,
+    CreateFieldAccess,
+
+    // Atribuição a um elemento ou campo
+    AssignAccess,
+
+    // `alvo op= expr` e `alvo++`/`alvo--`, reescritos como `alvo := alvo op expr`
+    AssignOp(OperatorKind),
+    AssignStep(OperatorKind),
 }
 // Funções para os simbolos
 impl Symbol {
@@ -49,6 +83,10 @@ impl Symbol {
         matches!(self, Symbol::End)
     }
 
+    pub fn is_action(&self) -> bool {
+        matches!(self, Symbol::Action(_))
+    }
+
     pub fn as_terminal(&self) -> Option<TokenType> {
         if let Symbol::Terminal(t) = self {
             Some(t.clone())
@@ -65,3 +103,21 @@ impl Symbol {
         }
     }
 }
+
+// Símbolo na notação da gramática: <não_terminal>, terminais pelo texto e ações
+// semânticas entre chaves
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Symbol::Terminal(terminal) => write!(f, "{}", terminal),
+            Symbol::NonTerminal(non_terminal) => write!(f, "<{}>", non_terminal),
+            Symbol::Epsilon => write!(f, "ε"),
+            Symbol::End => write!(f, "$"),
+            Symbol::Action(ActionKind::Math(op)) => write!(f, "{{Math({})}}", op),
+            Symbol::Action(ActionKind::CreateUnaryOp(op)) => write!(f, "{{CreateUnaryOp({})}}", op),
+            Symbol::Action(ActionKind::AssignOp(op)) => write!(f, "{{AssignOp({})}}", op),
+            Symbol::Action(ActionKind::AssignStep(op)) => write!(f, "{{AssignStep({})}}", op),
+            Symbol::Action(kind) => write!(f, "{{{:?}}}", kind),
+        }
+    }
+}
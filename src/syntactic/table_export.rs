@@ -0,0 +1,167 @@
+use std::fmt::Write;
+
+use crate::{ParseTable, Symbol, TokenType, syntactic::trace::csv_field};
+
+// Conteúdo de uma célula M[A, a]
+enum Cell {
+    // Erro de sintaxe
+    Empty,
+    Entry(String),
+    // Todas as produções registradas na célula
+    Conflict(Vec<String>),
+}
+
+// Exporta a matriz M[A, a] completa (não terminais × todos os terminais), com as
+// produções na notação da gramática e sem as ações semânticas
+impl ParseTable {
+    pub fn to_markdown(&self) -> String {
+        let code = |text: &str| format!("`{}`", text.replace('|', "\\|"));
+
+        let mut out = String::new();
+        let header: Vec<String> = TokenType::ALL
+            .iter()
+            .map(|terminal| code(&terminal.to_string()))
+            .collect();
+        let _ = writeln!(out, "| M[A, a] | {} |", header.join(" | "));
+        let _ = writeln!(out, "|---|{}", "---|".repeat(header.len()));
+
+        for non_terminal in self.non_terminals() {
+            let cells: Vec<String> = TokenType::ALL
+                .iter()
+                .map(|terminal| match self.cell(non_terminal, terminal) {
+                    Cell::Empty => "—".to_string(),
+                    Cell::Entry(production) => code(&production),
+                    Cell::Conflict(productions) => format!(
+                        "**conflito:** {}",
+                        productions
+                            .iter()
+                            .map(|production| code(production))
+                            .collect::<Vec<_>>()
+                            .join("<br>")
+                    ),
+                })
+                .collect();
+
+            let _ = writeln!(
+                out,
+                "| {} | {} |",
+                code(&format!("<{}>", non_terminal)),
+                cells.join(" | ")
+            );
+        }
+
+        out.push_str(
+            "\nCélulas com — são erros de sintaxe; células em **conflito** listam todas as produções registradas.\n",
+        );
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<style>\n\
+             .parse-table td.empty { background: #eeeeee; }\n\
+             .parse-table td.conflict { background: #ffcccc; font-weight: bold; }\n\
+             </style>\n\
+             <table class=\"parse-table\">\n",
+        );
+
+        out.push_str("  <tr><th>M[A, a]</th>");
+        for terminal in TokenType::ALL {
+            let _ = write!(out, "<th>{}</th>", escape_html(&terminal.to_string()));
+        }
+        out.push_str("</tr>\n");
+
+        for non_terminal in self.non_terminals() {
+            let _ = write!(
+                out,
+                "  <tr><th>{}</th>",
+                escape_html(&format!("<{}>", non_terminal))
+            );
+
+            for terminal in TokenType::ALL {
+                match self.cell(non_terminal, &terminal) {
+                    Cell::Empty => out.push_str("<td class=\"empty\"></td>"),
+                    Cell::Entry(production) => {
+                        let _ = write!(out, "<td>{}</td>", escape_html(&production));
+                    }
+                    Cell::Conflict(productions) => {
+                        let productions: Vec<String> = productions
+                            .iter()
+                            .map(|production| escape_html(production))
+                            .collect();
+                        let _ = write!(
+                            out,
+                            "<td class=\"conflict\">{}</td>",
+                            productions.join("<br>")
+                        );
+                    }
+                }
+            }
+            out.push_str("</tr>\n");
+        }
+
+        out.push_str("</table>\n");
+        out
+    }
+
+    // Células vazias ficam em branco e conflitos listam as produções separadas por " || "
+    pub fn to_csv(&self) -> String {
+        let mut out = csv_field("M[A, a]");
+        for terminal in TokenType::ALL {
+            out.push(',');
+            out.push_str(&csv_field(&terminal.to_string()));
+        }
+        out.push('\n');
+
+        for non_terminal in self.non_terminals() {
+            out.push_str(&csv_field(&format!("<{}>", non_terminal)));
+
+            for terminal in TokenType::ALL {
+                let text = match self.cell(non_terminal, &terminal) {
+                    Cell::Empty => String::new(),
+                    Cell::Entry(production) => production,
+                    Cell::Conflict(productions) => {
+                        format!("CONFLITO: {}", productions.join(" || "))
+                    }
+                };
+                out.push(',');
+                out.push_str(&csv_field(&text));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    fn cell(&self, non_terminal: &str, terminal: &TokenType) -> Cell {
+        let mut productions: Vec<String> = self
+            .entries(non_terminal, terminal)
+            .into_iter()
+            .map(|production| production_notation(non_terminal, production))
+            .collect();
+
+        match productions.len() {
+            0 => Cell::Empty,
+            1 => Cell::Entry(productions.remove(0)),
+            _ => Cell::Conflict(productions),
+        }
+    }
+}
+
+// `<A> -> α`, sem as ações semânticas
+fn production_notation(non_terminal: &str, production: &[Symbol]) -> String {
+    let symbols: Vec<String> = production
+        .iter()
+        .filter(|symbol| !symbol.is_action())
+        .map(|symbol| symbol.to_string())
+        .collect();
+
+    format!("<{}> -> {}", non_terminal, symbols.join(" "))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,237 @@
+use std::fmt::Write;
+
+use crate::{ActionKind, AstNode, ParserError, Spanned, Symbol, Token};
+
+// Largura máxima da coluna de entrada na tabela; CSV e JSON não cortam nada
+const TABLE_INPUT_WIDTH: usize = 40;
+
+// Registro passo a passo de uma análise LL(1)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseTrace {
+    // Tokens lidos pelo analisador, na ordem; termina em `Eof` se a análise
+    // chegou ao fim da entrada
+    pub tokens: Vec<Token>,
+    pub steps: Vec<TraceStep>,
+}
+
+// Um passo da análise: a pilha e o token atual antes do passo e o que foi feito
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    // Pilha de análise, da base ($) até o topo
+    pub stack: Vec<Symbol>,
+    // Índice do token atual em `ParseTrace::tokens`
+    pub token: usize,
+    pub action: TraceAction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceAction {
+    // Expansão do não terminal no topo pela produção `get_entry(non_terminal, token)`
+    Expand {
+        non_terminal: String,
+        production: Vec<Symbol>,
+    },
+    // Terminal do topo casado com o token atual
+    Match(Token),
+    // Ação semântica executada e a pilha da AST logo depois dela
+    Execute {
+        kind: ActionKind,
+        ast_stack: Vec<Spanned<AstNode>>,
+    },
+    // Fim de arquivo casado com `$`
+    Accept,
+    // Erro que interrompeu a análise
+    Error(ParserError),
+}
+
+impl TraceStep {
+    fn stack_column(&self) -> String {
+        self.stack
+            .iter()
+            .map(|symbol| symbol.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn action_column(&self) -> String {
+        match &self.action {
+            TraceAction::Expand {
+                non_terminal,
+                production,
+            } => format!(
+                "<{}> -> {}",
+                non_terminal,
+                production
+                    .iter()
+                    .map(|symbol| symbol.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            TraceAction::Match(token) => format!("casa '{}'", token.lexeme()),
+            TraceAction::Execute { kind, .. } => {
+                format!("executa {}", Symbol::Action(kind.clone()))
+            }
+            TraceAction::Accept => "aceita".to_string(),
+            TraceAction::Error(error) => format!("erro: {}", error.message()),
+        }
+    }
+
+    // Pilha da AST depois de uma ação, em S-expressions
+    fn ast_column(&self) -> String {
+        match &self.action {
+            TraceAction::Execute { ast_stack, .. } => ast_stack
+                .iter()
+                .map(|node| node.to_sexp())
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => String::new(),
+        }
+    }
+}
+
+impl ParseTrace {
+    // Entrada restante a partir do token `index`, terminando em `$`
+    fn input_column(&self, index: usize) -> String {
+        let mut input: Vec<String> = self.tokens[index.min(self.tokens.len())..]
+            .iter()
+            .filter(|token| **token != Token::Eof)
+            .map(|token| token.lexeme())
+            .collect();
+        input.push("$".to_string());
+        input.join(" ")
+    }
+
+    fn rows(&self) -> Vec<[String; 4]> {
+        self.steps
+            .iter()
+            .map(|step| {
+                [
+                    step.stack_column(),
+                    self.input_column(step.token),
+                    step.action_column(),
+                    step.ast_column(),
+                ]
+            })
+            .collect()
+    }
+
+    // Tabela clássica de três colunas (Pilha | Entrada | Ação). A pilha cresce para
+    // a direita, então é alinhada por esse lado; a pilha da AST aparece na coluna
+    // de ação, depois de cada ação semântica.
+    pub fn to_table(&self) -> String {
+        let rows: Vec<[String; 3]> = self
+            .rows()
+            .into_iter()
+            .map(|[stack, input, action, ast]| {
+                let input = if input.chars().count() > TABLE_INPUT_WIDTH {
+                    let cut: String = input.chars().take(TABLE_INPUT_WIDTH - 1).collect();
+                    format!("{}…", cut)
+                } else {
+                    input
+                };
+                let action = if ast.is_empty() {
+                    action
+                } else {
+                    format!("{}  AST: {}", action, ast)
+                };
+                [stack, input, action]
+            })
+            .collect();
+
+        let header = ["Pilha", "Entrada", "Ação"].map(String::from);
+        let width = |column: usize| {
+            rows.iter()
+                .chain([&header])
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+
+        let (stack_width, input_width, action_width) = (width(0), width(1), width(2));
+        let write_row = |out: &mut String, row: &[String; 3]| {
+            let _ = writeln!(
+                out,
+                "{:>stack_width$} | {:<input_width$} | {}",
+                row[0], row[1], row[2]
+            );
+        };
+
+        let mut out = String::new();
+        write_row(&mut out, &header);
+        let _ = writeln!(
+            out,
+            "{}-+-{}-+-{}",
+            "-".repeat(stack_width),
+            "-".repeat(input_width),
+            "-".repeat(action_width)
+        );
+        for row in &rows {
+            write_row(&mut out, row);
+        }
+
+        out
+    }
+
+    // CSV com cabeçalho `pilha,entrada,acao,ast`
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("pilha,entrada,acao,ast\n");
+        for row in self.rows() {
+            let fields: Vec<String> = row.iter().map(|value| csv_field(value)).collect();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // Lista JSON com um objeto por passo, com as mesmas colunas do CSV
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self
+            .rows()
+            .into_iter()
+            .map(|[stack, input, action, ast]| {
+                format!(
+                    "  {{\"pilha\": {}, \"entrada\": {}, \"acao\": {}, \"ast\": {}}}",
+                    json_string(&stack),
+                    json_string(&input),
+                    json_string(&action),
+                    json_string(&ast)
+                )
+            })
+            .collect();
+
+        if rows.is_empty() {
+            "[]\n".to_string()
+        } else {
+            format!("[\n{}\n]\n", rows.join(",\n"))
+        }
+    }
+}
+
+// Campo CSV, entre aspas apenas quando necessário
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if ch.is_control() => {
+                let _ = write!(out, "\\u{:04x}", ch as u32);
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
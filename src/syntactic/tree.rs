@@ -1,57 +1,120 @@
-use crate::{OperatorKind, RelopKind, Type};
+use crate::{OperatorKind, RelopKind, Span, Type, syntactic::dot::DotGraph};
 
 // Nó da AST
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AstNode {
     Program {
+        // Registros (`Record`), declarações globais (`VarDecl`) e funções definidos
+        // antes de `main`, na ordem do código-fonte; os registros vêm antes das
+        // globais, que vêm antes de todas as funções
+        records: Vec<Spanned<AstNode>>,
+        globals: Vec<Spanned<AstNode>>,
+        functions: Vec<Spanned<AstNode>>,
+        kind: Type,
+        body: Box<Spanned<AstNode>>,
+    },
+    Function {
+        kind: Type,
+        name: String,
+        // Um `VarDecl` com um único nome por parâmetro
+        params: Vec<Spanned<AstNode>>,
+        body: Box<Spanned<AstNode>>,
+    },
+    // `tipo Nome [ ... ]`; o tipo é sempre um `Type::Record`
+    Record {
         kind: Type,
-        body: Box<AstNode>,
     },
     Block {
-        decls: Vec<AstNode>,
-        stmts: Vec<AstNode>,
+        decls: Vec<Spanned<AstNode>>,
+        stmts: Vec<Spanned<AstNode>>,
     },
     VarDecl {
         kind: Type,
         names: Vec<String>,
+        // Valor inicial de cada nome, na mesma ordem de `names`
+        inits: Vec<Option<Spanned<AstNode>>>,
+        // Declarada com `const`; toda constante tem valor inicial
+        constant: bool,
     },
     Assignment {
         id: String,
-        expr: Box<AstNode>,
+        expr: Box<Spanned<AstNode>>,
+    },
+    // `v[i] := expr`; o vetor pode ser qualquer acesso, como `p.v`
+    ArrayAssignment {
+        array: Box<Spanned<AstNode>>,
+        index: Box<Spanned<AstNode>>,
+        expr: Box<Spanned<AstNode>>,
+    },
+    // `p.x := expr`
+    FieldAssignment {
+        record: Box<Spanned<AstNode>>,
+        field: String,
+        expr: Box<Spanned<AstNode>>,
     },
+    // Chamada de função, como expressão ou como comando
+    Call {
+        name: String,
+        args: Vec<Spanned<AstNode>>,
+    },
+    Return {
+        expr: Box<Spanned<AstNode>>,
+    },
+    // Só aparecem dentro do corpo de um laço
+    Break,
+    Continue,
     If {
-        cond: Box<AstNode>,
-        then_block: Box<AstNode>,
-        else_block: Option<Box<AstNode>>,
+        cond: Box<Spanned<AstNode>>,
+        then_block: Box<Spanned<AstNode>>,
+        else_block: Option<Box<Spanned<AstNode>>>,
     },
     While {
-        cond: Box<AstNode>,
-        body: Box<AstNode>,
+        cond: Box<Spanned<AstNode>>,
+        body: Box<Spanned<AstNode>>,
     },
     DoWhile {
-        body: Box<AstNode>,
-        cond: Box<AstNode>,
+        body: Box<Spanned<AstNode>>,
+        cond: Box<Spanned<AstNode>>,
     },
+    // `for (id; start; end; step)`: `id` percorre o intervalo semiaberto
+    // [start, end). Os limites são avaliados uma vez, antes da primeira volta;
+    // cada volta testa `id < end` e, depois do corpo, faz `id := step`.
     For {
         id: String,
-        start: u32,
-        end: u32,
-        step: Box<AstNode>,
-        body: Box<AstNode>,
+        start: Box<Spanned<AstNode>>,
+        end: Box<Spanned<AstNode>>,
+        step: Box<Spanned<AstNode>>,
+        body: Box<Spanned<AstNode>>,
+    },
+    // `switch (expr) [ case <rótulo>: ... default: ... ]`. Só o ramo do rótulo
+    // igual a `expr` (ou o `default`) é executado, sem passar para o seguinte;
+    // `break` e `continue` dentro de um ramo se referem ao laço em volta.
+    Switch {
+        expr: Box<Spanned<AstNode>>,
+        // Um `Case` por ramo, na ordem do código-fonte
+        cases: Vec<Spanned<AstNode>>,
+        default: Option<Box<Spanned<AstNode>>>,
     },
+    // Ramo de um `switch`; o rótulo é um número inteiro, `- num` ou um caractere
+    Case {
+        label: Box<Spanned<AstNode>>,
+        body: Box<Spanned<AstNode>>,
+    },
+    // Negação (`-`) ou complemento bit a bit (`~`)
     UnaryOp {
-        // aceita apenas subtração como unário então não precisa de campo 'op: OperatorKind'
-        expr: Box<AstNode>,
+        op: OperatorKind,
+        expr: Box<Spanned<AstNode>>,
     },
     BinaryOp {
         op: OperatorKind,
-        left: Box<AstNode>,
-        right: Box<AstNode>,
+        left: Box<Spanned<AstNode>>,
+        right: Box<Spanned<AstNode>>,
     },
     BinaryComp {
         relop: RelopKind,
-        left: Box<AstNode>,
-        right: Box<AstNode>,
+        left: Box<Spanned<AstNode>>,
+        right: Box<Spanned<AstNode>>,
     },
     Number {
         value: String,
@@ -59,33 +122,1246 @@ pub enum AstNode {
     Identifier {
         name: String,
     },
+    // Leitura de um elemento, `v[i]`
+    ArrayAccess {
+        array: Box<Spanned<AstNode>>,
+        index: Box<Spanned<AstNode>>,
+    },
+    // Leitura de um campo, `p.x`
+    FieldAccess {
+        record: Box<Spanned<AstNode>>,
+        field: String,
+    },
     Literal {
         value: char,
     },
+    // `true` ou `false`
+    Bool {
+        value: bool,
+    },
     TypeWrapper(Type),
     CondWrapper(RelopKind),
-    List(Vec<AstNode>),
+    List(Vec<Spanned<AstNode>>),
 }
 
 impl AstNode {
-    // TODO melhorar essa função para aceitar os novos nós
-    pub fn print_ast(ast: &AstNode, indent: usize) {
-        todo!();
-        // match ast {
-        //     AstNode::BinaryOp { op, left, right } => {
-        //         println!("{}├─ BinaryOp: {:?}", " ".repeat(indent * 2), op);
-        //         AstNode::print_ast(left, indent + 1);
-        //         AstNode::print_ast(right, indent + 1);
-        //     }
-        //     AstNode::Number { value } => {
-        //         println!("{}├─ Number: {}", " ".repeat(indent * 2), value);
-        //     }
-        //     AstNode::Identifier { name } => {
-        //         println!("{}├─ Identifier: {}", " ".repeat(indent * 2), name);
-        //     }
-        //     _ => {
-        //         println!("{}├─ {:?}", " ".repeat(indent * 2), ast);
-        //     }
-        // }
+    // Filhos que também são nós da AST, na ordem em que aparecem no código-fonte
+    pub fn children(&self) -> Vec<&Spanned<AstNode>> {
+        match self {
+            AstNode::Program {
+                records,
+                globals,
+                functions,
+                body,
+                ..
+            } => records
+                .iter()
+                .chain(globals)
+                .chain(functions)
+                .chain([body.as_ref()])
+                .collect(),
+            AstNode::Function { params, body, .. } => {
+                params.iter().chain([body.as_ref()]).collect()
+            }
+            AstNode::Block { decls, stmts } => decls.iter().chain(stmts.iter()).collect(),
+            AstNode::Assignment { expr, .. } | AstNode::Return { expr } => vec![expr],
+            AstNode::Call { args, .. } => args.iter().collect(),
+            AstNode::ArrayAssignment { array, index, expr } => vec![array, index, expr],
+            AstNode::FieldAssignment { record, expr, .. } => vec![record, expr],
+            AstNode::ArrayAccess { array, index } => vec![array, index],
+            AstNode::FieldAccess { record, .. } => vec![record],
+            AstNode::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                let mut children: Vec<&Spanned<AstNode>> = vec![cond, then_block];
+                children.extend(else_block.as_deref());
+                children
+            }
+            AstNode::While { cond, body } => vec![cond, body],
+            AstNode::DoWhile { body, cond } => vec![body, cond],
+            AstNode::For {
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => vec![start, end, step, body],
+            AstNode::Switch {
+                expr,
+                cases,
+                default,
+            } => [expr.as_ref()]
+                .into_iter()
+                .chain(cases)
+                .chain(default.as_deref())
+                .collect(),
+            AstNode::Case { label, body } => vec![label, body],
+            AstNode::UnaryOp { expr, .. } => vec![expr],
+            AstNode::BinaryOp { left, right, .. } | AstNode::BinaryComp { left, right, .. } => {
+                vec![left, right]
+            }
+            AstNode::List(items) => items.iter().collect(),
+            AstNode::VarDecl { inits, .. } => inits.iter().flatten().collect(),
+            AstNode::Record { .. }
+            | AstNode::Break
+            | AstNode::Continue
+            | AstNode::Number { .. }
+            | AstNode::Identifier { .. }
+            | AstNode::Literal { .. }
+            | AstNode::Bool { .. }
+            | AstNode::TypeWrapper(_)
+            | AstNode::CondWrapper(_) => vec![],
+        }
+    }
+
+    // Imprime a AST no estilo escolhido
+    pub fn print_ast(&self, style: PrintStyle) {
+        println!("{}", self.format_ast(style));
+    }
+
+    pub fn format_ast(&self, style: PrintStyle) -> String {
+        match style {
+            PrintStyle::Tree => {
+                let mut printer = TreePrinter::default();
+                let mut out = String::new();
+                printer.line_of(self, None).render(&mut out, "", None);
+                out.truncate(out.trim_end().len());
+                out
+            }
+            PrintStyle::SExpr => self.to_sexp(),
+        }
+    }
+
+    // Grafo DOT da AST, com os mesmos rótulos da impressão em árvore
+    pub fn to_dot(&self) -> String {
+        let mut graph = DotGraph::new("AST");
+        TreePrinter::default()
+            .line_of(self, None)
+            .write_dot(&mut graph);
+        graph.finish()
+    }
+
+    // Forma compacta em S-expression, numa única linha. O formato é estável e pode
+    // ser lido por ferramentas externas:
+    //   (program <registro>... <global>... <função>... <tipo> <bloco>)
+    //   (record <id> (<tipo> <campo> <deslocamento>)...)
+    //   (fn <tipo> <id> (<param>...) <bloco>)
+    //   (block <decl|cmd>...)           (var <tipo> <id|(:= <id> <expr>)>...)
+    //   (const <tipo> (:= <id> <expr>)...)
+    //   (:= <id> <expr>)                (call <id> <arg>...)    (return <expr>)
+    //   (break)                         (continue)
+    //   (:= (index <vetor> <i>) <expr>) (index <vetor> <i>)
+    //   (:= (. <registro> <campo>) <expr>)   (. <registro> <campo>)
+    //   (if <cond> <then> [<else>])
+    //   (while <cond> <corpo>)          (do <corpo> <cond>)
+    //   (for <id> <início> <fim> <passo> <corpo>)
+    //   (switch <expr> (case <rótulo> <cmd>)... [(default <cmd>)])
+    //   (- <expr>)   (~ <expr>)   (<op> <esq> <dir>)   (list <nó>...)
+    // Números e identificadores aparecem sem aspas (um número sempre começa com
+    // dígito) e caracteres entre aspas simples: 'a'. Os lógicos são `true` e
+    // `false`, que não podem ser identificadores. As globais são nós `var` ou
+    // `const`, na mesma forma das declarações locais.
+    pub fn to_sexp(&self) -> String {
+        let list = |head: String, items: Vec<String>| {
+            if items.is_empty() {
+                format!("({})", head)
+            } else {
+                format!("({} {})", head, items.join(" "))
+            }
+        };
+
+        match self {
+            AstNode::Program {
+                records,
+                globals,
+                functions,
+                kind,
+                body,
+            } => {
+                let mut items: Vec<String> = records
+                    .iter()
+                    .chain(globals)
+                    .chain(functions)
+                    .map(|node| node.to_sexp())
+                    .collect();
+                items.extend([kind.to_string(), body.to_sexp()]);
+                list("program".into(), items)
+            }
+            AstNode::Function {
+                kind,
+                name,
+                params,
+                body,
+            } => list(
+                "fn".into(),
+                vec![
+                    kind.to_string(),
+                    name.clone(),
+                    format!(
+                        "({})",
+                        params
+                            .iter()
+                            .map(|node| node.to_sexp())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    ),
+                    body.to_sexp(),
+                ],
+            ),
+            AstNode::Record { kind } => {
+                let mut items = vec![match kind {
+                    Type::Record { name, .. } => name.clone(),
+                    kind => kind.to_string(),
+                }];
+                if let Type::Record { fields, .. } = kind {
+                    items.extend(
+                        fields.iter().map(|field| {
+                            format!("({} {} {})", field.kind, field.name, field.offset)
+                        }),
+                    );
+                }
+                list("record".into(), items)
+            }
+            AstNode::Block { decls, stmts } => list(
+                "block".into(),
+                decls
+                    .iter()
+                    .chain(stmts.iter())
+                    .map(|node| node.to_sexp())
+                    .collect(),
+            ),
+            AstNode::VarDecl {
+                kind,
+                names,
+                inits,
+                constant,
+            } => {
+                let mut items = vec![kind.to_string()];
+                items.extend(names.iter().zip(inits).map(|(name, init)| match init {
+                    Some(init) => list(":=".into(), vec![name.clone(), init.to_sexp()]),
+                    None => name.clone(),
+                }));
+                let head = if *constant { "const" } else { "var" };
+                list(head.into(), items)
+            }
+            AstNode::Assignment { id, expr } => list(":=".into(), vec![id.clone(), expr.to_sexp()]),
+            AstNode::Call { name, args } => {
+                let mut items = vec![name.clone()];
+                items.extend(args.iter().map(|node| node.to_sexp()));
+                list("call".into(), items)
+            }
+            AstNode::Return { expr } => list("return".into(), vec![expr.to_sexp()]),
+            AstNode::Break => list("break".into(), vec![]),
+            AstNode::Continue => list("continue".into(), vec![]),
+            AstNode::ArrayAssignment { array, index, expr } => list(
+                ":=".into(),
+                vec![
+                    list("index".into(), vec![array.to_sexp(), index.to_sexp()]),
+                    expr.to_sexp(),
+                ],
+            ),
+            AstNode::FieldAssignment {
+                record,
+                field,
+                expr,
+            } => list(
+                ":=".into(),
+                vec![
+                    list(".".into(), vec![record.to_sexp(), field.clone()]),
+                    expr.to_sexp(),
+                ],
+            ),
+            AstNode::ArrayAccess { array, index } => {
+                list("index".into(), vec![array.to_sexp(), index.to_sexp()])
+            }
+            AstNode::FieldAccess { record, field } => {
+                list(".".into(), vec![record.to_sexp(), field.clone()])
+            }
+            AstNode::If {
+                cond,
+                then_block,
+                else_block,
+            } => {
+                let mut items = vec![cond.to_sexp(), then_block.to_sexp()];
+                items.extend(else_block.iter().map(|node| node.to_sexp()));
+                list("if".into(), items)
+            }
+            AstNode::While { cond, body } => {
+                list("while".into(), vec![cond.to_sexp(), body.to_sexp()])
+            }
+            AstNode::DoWhile { body, cond } => {
+                list("do".into(), vec![body.to_sexp(), cond.to_sexp()])
+            }
+            AstNode::For {
+                id,
+                start,
+                end,
+                step,
+                body,
+            } => list(
+                "for".into(),
+                vec![
+                    id.clone(),
+                    start.to_sexp(),
+                    end.to_sexp(),
+                    step.to_sexp(),
+                    body.to_sexp(),
+                ],
+            ),
+            AstNode::Switch {
+                expr,
+                cases,
+                default,
+            } => {
+                let mut items = vec![expr.to_sexp()];
+                items.extend(cases.iter().map(|node| node.to_sexp()));
+                items.extend(
+                    default
+                        .iter()
+                        .map(|node| list("default".into(), vec![node.to_sexp()])),
+                );
+                list("switch".into(), items)
+            }
+            AstNode::Case { label, body } => {
+                list("case".into(), vec![label.to_sexp(), body.to_sexp()])
+            }
+            AstNode::UnaryOp { op, expr } => list(op.to_string(), vec![expr.to_sexp()]),
+            AstNode::BinaryOp { op, left, right } => {
+                list(op.to_string(), vec![left.to_sexp(), right.to_sexp()])
+            }
+            AstNode::BinaryComp { relop, left, right } => {
+                list(relop.to_string(), vec![left.to_sexp(), right.to_sexp()])
+            }
+            AstNode::Number { value } => value.clone(),
+            AstNode::Identifier { name } => name.clone(),
+            AstNode::Literal { value } => format!("'{}'", value),
+            AstNode::Bool { value } => value.to_string(),
+            AstNode::TypeWrapper(kind) => kind.to_string(),
+            AstNode::CondWrapper(relop) => relop.to_string(),
+            AstNode::List(items) => list(
+                "list".into(),
+                items.iter().map(|node| node.to_sexp()).collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl AstNode {
+    // Serializa a AST em JSON, com cada variante como objeto de uma única chave
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+// Estilos de impressão da AST
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintStyle {
+    // Árvore com conectores de desenho de caixa
+    Tree,
+    // S-expression compacta, numa única linha
+    SExpr,
+}
+
+// Linha da árvore impressa: rótulo do nó e suas linhas filhas
+#[derive(Default)]
+struct TreeLine {
+    label: String,
+    children: Vec<TreeLine>,
+}
+
+impl TreeLine {
+    // `is_last` é `None` apenas para a raiz, que não recebe conector
+    fn render(&self, out: &mut String, prefix: &str, is_last: Option<bool>) {
+        let (connector, child_prefix) = match is_last {
+            None => ("", prefix.to_string()),
+            Some(true) => ("└── ", format!("{}    ", prefix)),
+            Some(false) => ("├── ", format!("{}│   ", prefix)),
+        };
+
+        out.push_str(prefix);
+        out.push_str(connector);
+        out.push_str(&self.label);
+        out.push('\n');
+
+        for (i, child) in self.children.iter().enumerate() {
+            child.render(out, &child_prefix, Some(i + 1 == self.children.len()));
+        }
+    }
+
+    fn write_dot(&self, graph: &mut DotGraph) -> usize {
+        let id = graph.node(&self.label, "box");
+        for child in &self.children {
+            let child_id = child.write_dot(graph);
+            graph.edge(id, child_id);
+        }
+        id
+    }
+}
+
+// Monta as linhas da árvore impressa percorrendo a AST com um `Visitor`. Cada
+// `visit_*` deixa em `current` a linha do nó visitado.
+#[derive(Default)]
+struct TreePrinter {
+    current: TreeLine,
+}
+
+impl TreePrinter {
+    // Linha de `node`, com um papel opcional ("cond", "then", ...)
+    fn line_of(&mut self, node: &AstNode, role: Option<&str>) -> TreeLine {
+        self.visit_node(node);
+
+        let mut line = std::mem::take(&mut self.current);
+        if let Some(role) = role {
+            line.label = format!("{}: {}", role, line.label);
+        }
+        line
+    }
+
+    fn emit(&mut self, label: String, children: Vec<TreeLine>) {
+        self.current = TreeLine { label, children };
+    }
+}
+
+impl Visitor for TreePrinter {
+    fn visit_program(
+        &mut self,
+        records: &[Spanned<AstNode>],
+        globals: &[Spanned<AstNode>],
+        functions: &[Spanned<AstNode>],
+        kind: &Type,
+        body: &Spanned<AstNode>,
+    ) {
+        let mut children: Vec<TreeLine> = records
+            .iter()
+            .map(|node| self.line_of(node, None))
+            .collect();
+        children.extend(
+            globals
+                .iter()
+                .map(|node| self.line_of(node, Some("global"))),
+        );
+        children.extend(functions.iter().map(|node| self.line_of(node, None)));
+        children.push(self.line_of(body, None));
+        self.emit(format!("Program: {}", kind), children);
+    }
+
+    fn visit_function(
+        &mut self,
+        kind: &Type,
+        name: &str,
+        params: &[Spanned<AstNode>],
+        body: &Spanned<AstNode>,
+    ) {
+        let mut children: Vec<TreeLine> = params
+            .iter()
+            .map(|node| self.line_of(node, Some("param")))
+            .collect();
+        children.push(self.line_of(body, Some("body")));
+        self.emit(format!("Function: {} {}", kind, name), children);
+    }
+
+    // Um filho por campo, com o deslocamento em bytes
+    fn visit_record(&mut self, kind: &Type) {
+        let (name, fields, size) = match kind {
+            Type::Record {
+                name, fields, size, ..
+            } => (name.as_str(), fields.as_slice(), *size),
+            _ => ("", [].as_slice(), 0),
+        };
+        let children = fields
+            .iter()
+            .map(|field| TreeLine {
+                label: format!("Field: {} {} @ {}", field.kind, field.name, field.offset),
+                children: vec![],
+            })
+            .collect();
+        self.emit(format!("Record: {} ({} bytes)", name, size), children);
+    }
+
+    fn visit_block(&mut self, decls: &[Spanned<AstNode>], stmts: &[Spanned<AstNode>]) {
+        let children = decls
+            .iter()
+            .chain(stmts)
+            .map(|node| self.line_of(node, None))
+            .collect();
+        self.emit("Block".to_string(), children);
+    }
+
+    // Cada valor inicial vira um filho com o nome da variável como papel
+    fn visit_var_decl(
+        &mut self,
+        kind: &Type,
+        names: &[String],
+        inits: &[Option<Spanned<AstNode>>],
+        constant: bool,
+    ) {
+        let children = names
+            .iter()
+            .zip(inits)
+            .filter_map(|(name, init)| Some(self.line_of(init.as_ref()?, Some(name))))
+            .collect();
+        let qualifier = if constant { "const " } else { "" };
+        self.emit(
+            format!("VarDecl: {}{} {}", qualifier, kind, names.join(", ")),
+            children,
+        );
+    }
+
+    fn visit_assignment(&mut self, id: &str, expr: &Spanned<AstNode>) {
+        let expr = self.line_of(expr, None);
+        self.emit(format!("Assignment: {}", id), vec![expr]);
+    }
+
+    fn visit_array_assignment(
+        &mut self,
+        array: &Spanned<AstNode>,
+        index: &Spanned<AstNode>,
+        expr: &Spanned<AstNode>,
+    ) {
+        let children = vec![
+            self.line_of(array, Some("array")),
+            self.line_of(index, Some("index")),
+            self.line_of(expr, None),
+        ];
+        self.emit("ArrayAssignment".to_string(), children);
+    }
+
+    fn visit_field_assignment(
+        &mut self,
+        record: &Spanned<AstNode>,
+        field: &str,
+        expr: &Spanned<AstNode>,
+    ) {
+        let children = vec![
+            self.line_of(record, Some("record")),
+            self.line_of(expr, None),
+        ];
+        self.emit(format!("FieldAssignment: {}", field), children);
+    }
+
+    fn visit_call(&mut self, name: &str, args: &[Spanned<AstNode>]) {
+        let children = args.iter().map(|node| self.line_of(node, None)).collect();
+        self.emit(format!("Call: {}", name), children);
+    }
+
+    fn visit_return(&mut self, expr: &Spanned<AstNode>) {
+        let expr = self.line_of(expr, None);
+        self.emit("Return".to_string(), vec![expr]);
+    }
+
+    fn visit_break(&mut self) {
+        self.emit("Break".to_string(), vec![]);
+    }
+
+    fn visit_continue(&mut self) {
+        self.emit("Continue".to_string(), vec![]);
+    }
+
+    // Achata a cadeia if/elsif/else: cada `If` no ramo else vira um filho "elsif"
+    fn visit_if(
+        &mut self,
+        cond: &Spanned<AstNode>,
+        then_block: &Spanned<AstNode>,
+        else_block: Option<&Spanned<AstNode>>,
+    ) {
+        let mut lines = vec![
+            self.line_of(cond, Some("cond")),
+            self.line_of(then_block, Some("then")),
+        ];
+        let mut next = else_block;
+
+        while let Some(node) = next {
+            match &node.node {
+                AstNode::If {
+                    cond,
+                    then_block,
+                    else_block,
+                } => {
+                    let branch = vec![
+                        self.line_of(cond, Some("cond")),
+                        self.line_of(then_block, Some("then")),
+                    ];
+                    lines.push(TreeLine {
+                        label: "elsif".to_string(),
+                        children: branch,
+                    });
+                    next = else_block.as_deref();
+                }
+                else_block => {
+                    lines.push(self.line_of(else_block, Some("else")));
+                    next = None;
+                }
+            }
+        }
+
+        self.emit("If".to_string(), lines);
+    }
+
+    fn visit_while(&mut self, cond: &Spanned<AstNode>, body: &Spanned<AstNode>) {
+        let children = vec![
+            self.line_of(cond, Some("cond")),
+            self.line_of(body, Some("body")),
+        ];
+        self.emit("While".to_string(), children);
+    }
+
+    fn visit_do_while(&mut self, body: &Spanned<AstNode>, cond: &Spanned<AstNode>) {
+        let children = vec![
+            self.line_of(body, Some("body")),
+            self.line_of(cond, Some("cond")),
+        ];
+        self.emit("DoWhile".to_string(), children);
+    }
+
+    fn visit_for(
+        &mut self,
+        id: &str,
+        start: &Spanned<AstNode>,
+        end: &Spanned<AstNode>,
+        step: &Spanned<AstNode>,
+        body: &Spanned<AstNode>,
+    ) {
+        let children = vec![
+            self.line_of(start, Some("start")),
+            self.line_of(end, Some("end")),
+            self.line_of(step, Some("step")),
+            self.line_of(body, Some("body")),
+        ];
+        self.emit(format!("For: {}", id), children);
+    }
+
+    fn visit_switch(
+        &mut self,
+        expr: &Spanned<AstNode>,
+        cases: &[Spanned<AstNode>],
+        default: Option<&Spanned<AstNode>>,
+    ) {
+        let mut children = vec![self.line_of(expr, Some("expr"))];
+        children.extend(cases.iter().map(|node| self.line_of(node, None)));
+        children.extend(default.map(|node| self.line_of(node, Some("default"))));
+        self.emit("Switch".to_string(), children);
+    }
+
+    fn visit_case(&mut self, label: &Spanned<AstNode>, body: &Spanned<AstNode>) {
+        let children = vec![
+            self.line_of(label, Some("label")),
+            self.line_of(body, Some("body")),
+        ];
+        self.emit("Case".to_string(), children);
+    }
+
+    fn visit_unary_op(&mut self, op: &OperatorKind, expr: &Spanned<AstNode>) {
+        let expr = self.line_of(expr, None);
+        self.emit(format!("UnaryOp: {}", op), vec![expr]);
+    }
+
+    fn visit_binary_op(
+        &mut self,
+        op: &OperatorKind,
+        left: &Spanned<AstNode>,
+        right: &Spanned<AstNode>,
+    ) {
+        let children = vec![self.line_of(left, None), self.line_of(right, None)];
+        self.emit(format!("BinaryOp: {}", op), children);
+    }
+
+    fn visit_binary_comp(
+        &mut self,
+        relop: &RelopKind,
+        left: &Spanned<AstNode>,
+        right: &Spanned<AstNode>,
+    ) {
+        let children = vec![self.line_of(left, None), self.line_of(right, None)];
+        self.emit(format!("BinaryComp: {}", relop), children);
+    }
+
+    fn visit_number(&mut self, value: &str) {
+        self.emit(format!("Number: {}", value), vec![]);
+    }
+
+    fn visit_identifier(&mut self, name: &str) {
+        self.emit(format!("Identifier: {}", name), vec![]);
+    }
+
+    fn visit_array_access(&mut self, array: &Spanned<AstNode>, index: &Spanned<AstNode>) {
+        let children = vec![
+            self.line_of(array, Some("array")),
+            self.line_of(index, Some("index")),
+        ];
+        self.emit("ArrayAccess".to_string(), children);
+    }
+
+    fn visit_field_access(&mut self, record: &Spanned<AstNode>, field: &str) {
+        let record = self.line_of(record, Some("record"));
+        self.emit(format!("FieldAccess: {}", field), vec![record]);
+    }
+
+    fn visit_literal(&mut self, value: char) {
+        self.emit(format!("Literal: '{}'", value), vec![]);
+    }
+
+    fn visit_bool(&mut self, value: bool) {
+        self.emit(format!("Bool: {}", value), vec![]);
+    }
+
+    fn visit_type_wrapper(&mut self, kind: &Type) {
+        self.emit(format!("Type: {}", kind), vec![]);
+    }
+
+    fn visit_cond_wrapper(&mut self, relop: &RelopKind) {
+        self.emit(format!("Relop: {}", relop), vec![]);
+    }
+
+    fn visit_list(&mut self, items: &[Spanned<AstNode>]) {
+        let children = items.iter().map(|node| self.line_of(node, None)).collect();
+        self.emit("List".to_string(), children);
+    }
+}
+
+// Nó com o intervalo que ocupa no código-fonte. O parser preenche `span` em
+// todos os nós; nós montados à mão ou criados por uma transformação podem ficar
+// sem intervalo. A comparação olha só para `node`, então duas árvores com a
+// mesma forma são iguais mesmo vindo de posições diferentes.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spanned<T> {
+    pub node: T,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub span: Option<Span>,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Self {
+        Spanned {
+            node,
+            span: Some(span),
+        }
+    }
+
+    // Troca o nó mantendo o intervalo
+    pub fn map(self, f: impl FnOnce(T) -> T) -> Self {
+        Spanned {
+            node: f(self.node),
+            span: self.span,
+        }
+    }
+}
+
+impl<T> From<T> for Spanned<T> {
+    fn from(node: T) -> Self {
+        Spanned { node, span: None }
+    }
+}
+
+impl From<AstNode> for Box<Spanned<AstNode>> {
+    fn from(node: AstNode) -> Self {
+        Box::new(node.into())
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T> std::ops::Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+impl<T> std::ops::DerefMut for Spanned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.node
+    }
+}
+
+// Percorre a AST sem modificá-la. Cada `visit_*` recebe os campos de uma variante
+// e, por padrão, visita os filhos através da função `walk_*` correspondente; basta
+// sobrescrever os métodos das variantes de interesse.
+pub trait Visitor {
+    fn visit_node(&mut self, node: &AstNode) {
+        walk_node(self, node);
+    }
+
+    fn visit_program(
+        &mut self,
+        records: &[Spanned<AstNode>],
+        globals: &[Spanned<AstNode>],
+        functions: &[Spanned<AstNode>],
+        _kind: &Type,
+        body: &Spanned<AstNode>,
+    ) {
+        walk_list(self, records);
+        walk_list(self, globals);
+        walk_list(self, functions);
+        self.visit_node(body);
+    }
+
+    fn visit_function(
+        &mut self,
+        _kind: &Type,
+        _name: &str,
+        params: &[Spanned<AstNode>],
+        body: &Spanned<AstNode>,
+    ) {
+        walk_list(self, params);
+        self.visit_node(body);
+    }
+
+    fn visit_record(&mut self, _kind: &Type) {}
+
+    fn visit_block(&mut self, decls: &[Spanned<AstNode>], stmts: &[Spanned<AstNode>]) {
+        walk_block(self, decls, stmts);
+    }
+
+    fn visit_var_decl(
+        &mut self,
+        _kind: &Type,
+        _names: &[String],
+        inits: &[Option<Spanned<AstNode>>],
+        _constant: bool,
+    ) {
+        for init in inits.iter().flatten() {
+            self.visit_node(init);
+        }
+    }
+
+    fn visit_assignment(&mut self, _id: &str, expr: &Spanned<AstNode>) {
+        self.visit_node(expr);
+    }
+
+    fn visit_array_assignment(
+        &mut self,
+        array: &Spanned<AstNode>,
+        index: &Spanned<AstNode>,
+        expr: &Spanned<AstNode>,
+    ) {
+        self.visit_node(array);
+        self.visit_node(index);
+        self.visit_node(expr);
+    }
+
+    fn visit_field_assignment(
+        &mut self,
+        record: &Spanned<AstNode>,
+        _field: &str,
+        expr: &Spanned<AstNode>,
+    ) {
+        self.visit_node(record);
+        self.visit_node(expr);
+    }
+
+    fn visit_call(&mut self, _name: &str, args: &[Spanned<AstNode>]) {
+        walk_list(self, args);
+    }
+
+    fn visit_return(&mut self, expr: &Spanned<AstNode>) {
+        self.visit_node(expr);
+    }
+
+    fn visit_break(&mut self) {}
+
+    fn visit_continue(&mut self) {}
+
+    fn visit_if(
+        &mut self,
+        cond: &Spanned<AstNode>,
+        then_block: &Spanned<AstNode>,
+        else_block: Option<&Spanned<AstNode>>,
+    ) {
+        walk_if(self, cond, then_block, else_block);
+    }
+
+    fn visit_while(&mut self, cond: &Spanned<AstNode>, body: &Spanned<AstNode>) {
+        self.visit_node(cond);
+        self.visit_node(body);
+    }
+
+    fn visit_do_while(&mut self, body: &Spanned<AstNode>, cond: &Spanned<AstNode>) {
+        self.visit_node(body);
+        self.visit_node(cond);
+    }
+
+    fn visit_for(
+        &mut self,
+        _id: &str,
+        start: &Spanned<AstNode>,
+        end: &Spanned<AstNode>,
+        step: &Spanned<AstNode>,
+        body: &Spanned<AstNode>,
+    ) {
+        self.visit_node(start);
+        self.visit_node(end);
+        self.visit_node(step);
+        self.visit_node(body);
+    }
+
+    fn visit_switch(
+        &mut self,
+        expr: &Spanned<AstNode>,
+        cases: &[Spanned<AstNode>],
+        default: Option<&Spanned<AstNode>>,
+    ) {
+        self.visit_node(expr);
+        walk_list(self, cases);
+        if let Some(default) = default {
+            self.visit_node(default);
+        }
+    }
+
+    fn visit_case(&mut self, label: &Spanned<AstNode>, body: &Spanned<AstNode>) {
+        self.visit_node(label);
+        self.visit_node(body);
+    }
+
+    fn visit_unary_op(&mut self, _op: &OperatorKind, expr: &Spanned<AstNode>) {
+        self.visit_node(expr);
+    }
+
+    fn visit_binary_op(
+        &mut self,
+        _op: &OperatorKind,
+        left: &Spanned<AstNode>,
+        right: &Spanned<AstNode>,
+    ) {
+        self.visit_node(left);
+        self.visit_node(right);
+    }
+
+    fn visit_binary_comp(
+        &mut self,
+        _relop: &RelopKind,
+        left: &Spanned<AstNode>,
+        right: &Spanned<AstNode>,
+    ) {
+        self.visit_node(left);
+        self.visit_node(right);
+    }
+
+    fn visit_number(&mut self, _value: &str) {}
+
+    fn visit_identifier(&mut self, _name: &str) {}
+
+    fn visit_array_access(&mut self, array: &Spanned<AstNode>, index: &Spanned<AstNode>) {
+        self.visit_node(array);
+        self.visit_node(index);
+    }
+
+    fn visit_field_access(&mut self, record: &Spanned<AstNode>, _field: &str) {
+        self.visit_node(record);
+    }
+
+    fn visit_literal(&mut self, _value: char) {}
+
+    fn visit_bool(&mut self, _value: bool) {}
+
+    fn visit_type_wrapper(&mut self, _kind: &Type) {}
+
+    fn visit_cond_wrapper(&mut self, _relop: &RelopKind) {}
+
+    fn visit_list(&mut self, items: &[Spanned<AstNode>]) {
+        walk_list(self, items);
+    }
+}
+
+// Despacha `node` para o `visit_*` da sua variante
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &AstNode) {
+    match node {
+        AstNode::Program {
+            records,
+            globals,
+            functions,
+            kind,
+            body,
+        } => visitor.visit_program(records, globals, functions, kind, body),
+        AstNode::Function {
+            kind,
+            name,
+            params,
+            body,
+        } => visitor.visit_function(kind, name, params, body),
+        AstNode::Record { kind } => visitor.visit_record(kind),
+        AstNode::Block { decls, stmts } => visitor.visit_block(decls, stmts),
+        AstNode::VarDecl {
+            kind,
+            names,
+            inits,
+            constant,
+        } => visitor.visit_var_decl(kind, names, inits, *constant),
+        AstNode::Assignment { id, expr } => visitor.visit_assignment(id, expr),
+        AstNode::ArrayAssignment { array, index, expr } => {
+            visitor.visit_array_assignment(array, index, expr)
+        }
+        AstNode::FieldAssignment {
+            record,
+            field,
+            expr,
+        } => visitor.visit_field_assignment(record, field, expr),
+        AstNode::Call { name, args } => visitor.visit_call(name, args),
+        AstNode::Return { expr } => visitor.visit_return(expr),
+        AstNode::Break => visitor.visit_break(),
+        AstNode::Continue => visitor.visit_continue(),
+        AstNode::If {
+            cond,
+            then_block,
+            else_block,
+        } => visitor.visit_if(cond, then_block, else_block.as_deref()),
+        AstNode::While { cond, body } => visitor.visit_while(cond, body),
+        AstNode::DoWhile { body, cond } => visitor.visit_do_while(body, cond),
+        AstNode::For {
+            id,
+            start,
+            end,
+            step,
+            body,
+        } => visitor.visit_for(id, start, end, step, body),
+        AstNode::Switch {
+            expr,
+            cases,
+            default,
+        } => visitor.visit_switch(expr, cases, default.as_deref()),
+        AstNode::Case { label, body } => visitor.visit_case(label, body),
+        AstNode::UnaryOp { op, expr } => visitor.visit_unary_op(op, expr),
+        AstNode::BinaryOp { op, left, right } => visitor.visit_binary_op(op, left, right),
+        AstNode::BinaryComp { relop, left, right } => visitor.visit_binary_comp(relop, left, right),
+        AstNode::Number { value } => visitor.visit_number(value),
+        AstNode::Identifier { name } => visitor.visit_identifier(name),
+        AstNode::ArrayAccess { array, index } => visitor.visit_array_access(array, index),
+        AstNode::FieldAccess { record, field } => visitor.visit_field_access(record, field),
+        AstNode::Literal { value } => visitor.visit_literal(*value),
+        AstNode::Bool { value } => visitor.visit_bool(*value),
+        AstNode::TypeWrapper(kind) => visitor.visit_type_wrapper(kind),
+        AstNode::CondWrapper(relop) => visitor.visit_cond_wrapper(relop),
+        AstNode::List(items) => visitor.visit_list(items),
+    }
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    decls: &[Spanned<AstNode>],
+    stmts: &[Spanned<AstNode>],
+) {
+    for node in decls.iter().chain(stmts) {
+        visitor.visit_node(node);
+    }
+}
+
+pub fn walk_if<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    cond: &Spanned<AstNode>,
+    then_block: &Spanned<AstNode>,
+    else_block: Option<&Spanned<AstNode>>,
+) {
+    visitor.visit_node(cond);
+    visitor.visit_node(then_block);
+    if let Some(else_block) = else_block {
+        visitor.visit_node(else_block);
+    }
+}
+
+pub fn walk_list<V: Visitor + ?Sized>(visitor: &mut V, items: &[Spanned<AstNode>]) {
+    for node in items {
+        visitor.visit_node(node);
+    }
+}
+
+// Reescreve a AST consumindo-a e devolvendo uma nova. Por padrão `fold_node`
+// reconstrói o nó com os filhos reescritos (`walk_fold`), então uma transformação
+// só precisa tratar as variantes que altera e repassar as demais para `walk_fold`.
+// Os filhos reescritos mantêm o intervalo do nó original.
+pub trait Folder {
+    fn fold_node(&mut self, node: AstNode) -> AstNode {
+        walk_fold(self, node)
+    }
+}
+
+// Reescreve os filhos de `node` com `fold_node`, mantendo o próprio nó
+pub fn walk_fold<F: Folder + ?Sized>(folder: &mut F, node: AstNode) -> AstNode {
+    let mut fold_box =
+        |node: Box<Spanned<AstNode>>| Box::new((*node).map(|node| folder.fold_node(node)));
+
+    match node {
+        AstNode::Program {
+            records,
+            globals,
+            functions,
+            kind,
+            body,
+        } => AstNode::Program {
+            records,
+            globals: globals
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+            functions: functions
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+            kind,
+            body: Box::new((*body).map(|node| folder.fold_node(node))),
+        },
+        AstNode::Function {
+            kind,
+            name,
+            params,
+            body,
+        } => AstNode::Function {
+            kind,
+            name,
+            params: params
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+            body: Box::new((*body).map(|node| folder.fold_node(node))),
+        },
+        AstNode::Block { decls, stmts } => AstNode::Block {
+            decls: decls
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+            stmts: stmts
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+        },
+        AstNode::VarDecl {
+            kind,
+            names,
+            inits,
+            constant,
+        } => AstNode::VarDecl {
+            kind,
+            names,
+            inits: inits
+                .into_iter()
+                .map(|init| init.map(|node| node.map(|node| folder.fold_node(node))))
+                .collect(),
+            constant,
+        },
+        AstNode::Assignment { id, expr } => AstNode::Assignment {
+            id,
+            expr: fold_box(expr),
+        },
+        AstNode::ArrayAssignment { array, index, expr } => AstNode::ArrayAssignment {
+            array: fold_box(array),
+            index: fold_box(index),
+            expr: fold_box(expr),
+        },
+        AstNode::FieldAssignment {
+            record,
+            field,
+            expr,
+        } => AstNode::FieldAssignment {
+            record: fold_box(record),
+            field,
+            expr: fold_box(expr),
+        },
+        AstNode::ArrayAccess { array, index } => AstNode::ArrayAccess {
+            array: fold_box(array),
+            index: fold_box(index),
+        },
+        AstNode::FieldAccess { record, field } => AstNode::FieldAccess {
+            record: fold_box(record),
+            field,
+        },
+        AstNode::Call { name, args } => AstNode::Call {
+            name,
+            args: args
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+        },
+        AstNode::Return { expr } => AstNode::Return {
+            expr: fold_box(expr),
+        },
+        AstNode::If {
+            cond,
+            then_block,
+            else_block,
+        } => AstNode::If {
+            cond: fold_box(cond),
+            then_block: fold_box(then_block),
+            else_block: else_block.map(fold_box),
+        },
+        AstNode::While { cond, body } => AstNode::While {
+            cond: fold_box(cond),
+            body: fold_box(body),
+        },
+        AstNode::DoWhile { body, cond } => AstNode::DoWhile {
+            body: fold_box(body),
+            cond: fold_box(cond),
+        },
+        AstNode::For {
+            id,
+            start,
+            end,
+            step,
+            body,
+        } => AstNode::For {
+            id,
+            start: fold_box(start),
+            end: fold_box(end),
+            step: fold_box(step),
+            body: fold_box(body),
+        },
+        AstNode::Switch {
+            expr,
+            cases,
+            default,
+        } => AstNode::Switch {
+            expr: Box::new((*expr).map(|node| folder.fold_node(node))),
+            cases: cases
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+            default: default.map(|node| Box::new(node.map(|node| folder.fold_node(node)))),
+        },
+        AstNode::Case { label, body } => AstNode::Case {
+            label: fold_box(label),
+            body: fold_box(body),
+        },
+        AstNode::UnaryOp { op, expr } => AstNode::UnaryOp {
+            op,
+            expr: fold_box(expr),
+        },
+        AstNode::BinaryOp { op, left, right } => AstNode::BinaryOp {
+            op,
+            left: fold_box(left),
+            right: fold_box(right),
+        },
+        AstNode::BinaryComp { relop, left, right } => AstNode::BinaryComp {
+            relop,
+            left: fold_box(left),
+            right: fold_box(right),
+        },
+        AstNode::List(items) => AstNode::List(
+            items
+                .into_iter()
+                .map(|node| node.map(|node| folder.fold_node(node)))
+                .collect(),
+        ),
+        leaf @ (AstNode::Record { .. }
+        | AstNode::Break
+        | AstNode::Continue
+        | AstNode::Number { .. }
+        | AstNode::Identifier { .. }
+        | AstNode::Literal { .. }
+        | AstNode::Bool { .. }
+        | AstNode::TypeWrapper(_)
+        | AstNode::CondWrapper(_)) => leaf,
     }
 }